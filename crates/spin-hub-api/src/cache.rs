@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// A copy of a Hub index kept on disk, so that it needn't be downloaded every time.
+pub struct CachedIndex {
+    /// The index as the Hub served it.
+    pub body: Vec<u8>,
+    /// How long ago it was written.
+    pub age: Duration,
+    /// What the Hub said about it, to ask whether it has changed.
+    pub validators: Validators,
+}
+
+/// The HTTP validators of a cached index, sent with a request for the index
+/// so the Hub can answer that it hasn't changed instead of sending it again.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+/// A directory of cached Hub indexes, one per Hub URL.
+#[derive(Clone, Debug)]
+pub struct IndexCache {
+    dir: PathBuf,
+}
+
+impl IndexCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &std::path::Path {
+        &self.dir
+    }
+
+    /// The cached index for the Hub, if there is one.
+    pub fn read(&self, hub_url: &url::Url) -> Option<CachedIndex> {
+        let path = self.index_path(hub_url);
+        let body = std::fs::read(&path).ok()?;
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+        let validators = std::fs::read(validators_path(&path)).ok()
+            .and_then(|v| serde_json::from_slice(&v).ok())
+            .unwrap_or_default();
+        Some(CachedIndex { body, age, validators })
+    }
+
+    /// Stores the index for the Hub. The cache is an optimisation, so failing
+    /// to write it is not an error.
+    pub fn write(&self, hub_url: &url::Url, body: &[u8], validators: &Validators) {
+        let path = self.index_path(hub_url);
+        _ = std::fs::create_dir_all(&self.dir);
+        let validators_path = validators_path(&path);
+        _ = std::fs::write(&path, body);
+        match serde_json::to_vec(validators) {
+            Ok(json) => _ = std::fs::write(validators_path, json),
+            Err(_) => _ = std::fs::remove_file(validators_path),
+        }
+    }
+
+    /// Where the index for the Hub is cached, whether or not it has been yet.
+    // Each Hub gets its own cache file so that switching Hubs doesn't serve the wrong index
+    pub fn index_path(&self, hub_url: &url::Url) -> PathBuf {
+        let key: String = hub_url.as_str()
+            .trim_end_matches('/')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+fn validators_path(index_path: &std::path::Path) -> PathBuf {
+    index_path.with_extension("validators.json")
+}
@@ -0,0 +1,209 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::{HubError, IndexCache, IndexEntry, Validators};
+
+/// Credentials for a Hub that needs you to log in.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case", tag = "type")]
+pub enum Credentials {
+    Bearer { token: String },
+    Basic { username: String, password: String },
+}
+
+/// The result of asking a Hub for its index.
+pub enum Fetched {
+    Modified { body: Vec<u8>, validators: Validators },
+    /// The index matches the validators that were sent.
+    NotModified,
+}
+
+/// Fetches the index from a Hub, falling back to mirrors of it, and
+/// optionally caching it.
+pub struct HubClient {
+    http: reqwest::Client,
+    hub_urls: Vec<url::Url>,
+    credentials: Vec<(url::Url, Credentials)>,
+    cache: Option<(IndexCache, Duration)>,
+    refresh: bool,
+    on_stale: Option<Box<dyn Fn(&HubError, Duration) + Send + Sync>>,
+}
+
+impl HubClient {
+    pub fn new(hub_url: url::Url) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            hub_urls: vec![hub_url],
+            credentials: vec![],
+            cache: None,
+            refresh: false,
+            on_stale: None,
+        }
+    }
+
+    /// Uses a client with your own proxy, certificate or timeout settings.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Other Hubs serving the same index, tried in order if the Hub can't be reached.
+    pub fn with_mirrors(mut self, mirrors: impl IntoIterator<Item = url::Url>) -> Self {
+        self.hub_urls.extend(mirrors);
+        self
+    }
+
+    pub fn with_credentials(mut self, hub_url: url::Url, credentials: Credentials) -> Self {
+        self.credentials.push((hub_url, credentials));
+        self
+    }
+
+    /// Keeps the index in `cache`, and uses it without asking the Hub for up
+    /// to `ttl` after it was fetched.
+    pub fn with_cache(mut self, cache: IndexCache, ttl: Duration) -> Self {
+        self.cache = Some((cache, ttl));
+        self
+    }
+
+    /// Asks the Hub for the index even if the cached one is fresh, and without
+    /// the validators that would let the Hub vouch for the cached copy.
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Called with the last error, and how old the cached index is, when no Hub
+    /// responds and the stale cached index is used instead.
+    pub fn on_stale_index(mut self, notice: impl Fn(&HubError, Duration) + Send + Sync + 'static) -> Self {
+        self.on_stale = Some(Box::new(notice));
+        self
+    }
+
+    /// The Hub, as opposed to its mirrors.
+    pub fn hub_url(&self) -> &url::Url {
+        &self.hub_urls[0]
+    }
+
+    pub fn hub_urls(&self) -> &[url::Url] {
+        &self.hub_urls
+    }
+
+    /// The Hub index, from the cache if it's fresh and otherwise from the first
+    /// of the Hub and its mirrors to respond. If none of them respond, a stale
+    /// cached index is used instead.
+    pub async fn index(&self) -> Result<Vec<IndexEntry>, HubError> {
+        self.index_with(|hub_url, validators| async move { self.fetch_index_from(&hub_url, validators.as_ref()).await }).await
+    }
+
+    /// Like [`index`](Self::index), but each request for the index is made by
+    /// `fetch`, for callers that retry failed requests or show progress. It's
+    /// given the Hub or mirror to ask, and the validators to send.
+    pub async fn index_with<F, Fut>(&self, fetch: F) -> Result<Vec<IndexEntry>, HubError>
+    where
+        F: Fn(url::Url, Option<Validators>) -> Fut,
+        Fut: Future<Output = Result<Fetched, HubError>>,
+    {
+        let cached = self.cache.as_ref().and_then(|(cache, ttl)| Some((cache, cache.read(self.hub_url())?, ttl)));
+
+        if let Some((_, cached, ttl)) = &cached {
+            if !self.refresh && cached.age < **ttl {
+                tracing::info!(age_secs = cached.age.as_secs(), "Using cached Hub index");
+                return Ok(serde_json::from_slice(&cached.body)?);
+            }
+            tracing::info!(age_secs = cached.age.as_secs(), "Cached Hub index is stale");
+        }
+
+        // Mirrors serve the same index, so the validators from one hold for the others
+        let validators = cached.as_ref().filter(|_| !self.refresh).map(|(_, cached, _)| cached.validators.clone());
+        let mut last_error = HubError::NoHubUrl;
+        for hub_url in &self.hub_urls {
+            match fetch(hub_url.clone(), validators.clone()).await {
+                Ok(Fetched::Modified { body, validators }) => {
+                    let entries = serde_json::from_slice(&body)?;
+                    if let Some((cache, _)) = &self.cache {
+                        cache.write(self.hub_url(), &body, &validators);
+                    }
+                    return Ok(entries);
+                }
+                Ok(Fetched::NotModified) => {
+                    // Only possible if `fetch` didn't send the validators it was given
+                    let Some((cache, cached, _)) = &cached else {
+                        last_error = HubError::NotModified { url: hub_url.clone() };
+                        continue;
+                    };
+                    tracing::info!("Cached Hub index is unchanged");
+                    // Rewriting it restarts the cache TTL
+                    cache.write(self.hub_url(), &cached.body, &cached.validators);
+                    return Ok(serde_json::from_slice(&cached.body)?);
+                }
+                Err(e) => last_error = e,
+            }
+        }
+
+        match cached {
+            Some((_, cached, _)) => {
+                if let Some(notice) = &self.on_stale {
+                    notice(&last_error, cached.age);
+                }
+                Ok(serde_json::from_slice(&cached.body)?)
+            }
+            None => Err(last_error),
+        }
+    }
+
+    /// Makes a single request for the index, without retrying or using the
+    /// cache. With `validators`, the Hub may say it hasn't changed instead
+    /// of sending it.
+    pub async fn fetch_index_from(&self, hub_url: &url::Url, validators: Option<&Validators>) -> Result<Fetched, HubError> {
+        let network_error = |source| HubError::Network { url: hub_url.clone(), source };
+
+        let url = index_url(hub_url);
+        let mut request = self.authorize(self.http.get(url.clone()), hub_url);
+        if let Some(validators) = validators {
+            if let Some(etag) = &validators.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        tracing::debug!(%url, "GET");
+        let started = std::time::Instant::now();
+        let response = request.send().await.map_err(|e| {
+            tracing::debug!(%url, error = %e, "Request failed");
+            network_error(e)
+        })?;
+        tracing::debug!(%url, status = %response.status(), elapsed_ms = started.elapsed().as_millis() as u64, headers = ?response.headers(), "Response");
+        match response.status() {
+            reqwest::StatusCode::NOT_MODIFIED if validators.is_some() => Ok(Fetched::NotModified),
+            status if status.is_success() => {
+                let header = |name| response.headers().get(name).and_then(|v| v.to_str().ok()).map(|v| v.to_owned());
+                let validators = Validators {
+                    etag: header(reqwest::header::ETAG),
+                    last_modified: header(reqwest::header::LAST_MODIFIED),
+                };
+                let body = response.bytes().await.map_err(network_error)?.to_vec();
+                Ok(Fetched::Modified { body, validators })
+            }
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => Err(HubError::Unauthorized { url: hub_url.clone() }),
+            reqwest::StatusCode::NOT_FOUND => Err(HubError::IndexNotFound { url: hub_url.clone() }),
+            reqwest::StatusCode::TOO_MANY_REQUESTS => Err(HubError::RateLimited { url: hub_url.clone() }),
+            status => Err(HubError::Response { url: hub_url.clone(), status }),
+        }
+    }
+
+    fn authorize(&self, request: reqwest::RequestBuilder, hub_url: &url::Url) -> reqwest::RequestBuilder {
+        match self.credentials.iter().find(|(url, _)| url == hub_url).map(|(_, c)| c) {
+            Some(Credentials::Bearer { token }) => request.bearer_auth(token),
+            Some(Credentials::Basic { username, password }) => request.basic_auth(username, Some(password)),
+            None => request,
+        }
+    }
+}
+
+/// Where a Hub serves its index.
+pub fn index_url(hub_url: &url::Url) -> url::Url {
+    hub_url
+        .join("api/hub/get_list")
+        .expect("Index URL was malformed")
+}
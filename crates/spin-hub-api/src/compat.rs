@@ -0,0 +1,59 @@
+//! Comparing Spin versions with the version requirements of Hub entries.
+
+use semver::{Version, VersionReq};
+
+// Hub entries write requirements in a few ways, e.g. `>=v2.0`, `v1.5` or
+// `>= 2.0, < 3.0`. A bare version is a minimum rather than semver's caret.
+pub fn parse_requirement(requirement: &str) -> Option<VersionReq> {
+    let normalised = requirement
+        .split(',')
+        .map(|part| {
+            let part = part.trim();
+            let op_len = part.find(|c: char| c.is_ascii_digit() || c == 'v').unwrap_or(part.len());
+            let (op, version) = part.split_at(op_len);
+            let op = if op.trim().is_empty() { ">=" } else { op.trim() };
+            format!("{op}{}", version.trim_start_matches('v'))
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    VersionReq::parse(&normalised).ok()
+}
+
+// Pre-release Spin builds are treated as the release they lead up to, because
+// semver requirements never match pre-releases unless they name one. Users may
+// also type a short version such as `2.0`.
+pub fn parse_spin_version(version: &str) -> Option<Version> {
+    let version = version.trim_start_matches('v');
+    let version = match version.matches('.').count() {
+        0 => format!("{version}.0.0"),
+        1 => format!("{version}.0"),
+        _ => version.to_owned(),
+    };
+    let mut version = Version::parse(&version).ok()?;
+    version.pre = semver::Prerelease::EMPTY;
+    Some(version)
+}
+
+// `None` if either version can't be understood, in which case the caller
+// shouldn't block anything.
+pub fn is_compatible(requirement: &str, spin_version: &str) -> Option<bool> {
+    let requirement = parse_requirement(requirement)?;
+    let spin_version = parse_spin_version(spin_version)?;
+    Some(requirement.matches(&spin_version))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hub_style_requirements_are_understood() {
+        assert_eq!(Some(true), is_compatible(">=v2.0", "2.1.0"));
+        assert_eq!(Some(false), is_compatible(">=v2.0", "1.5.1"));
+        assert_eq!(Some(true), is_compatible("v1.5", "2.0.0"));
+        assert_eq!(Some(false), is_compatible(">= 2.0, < 3.0", "3.0.0"));
+        assert_eq!(Some(true), is_compatible(">=2.1", "2.1.0-pre0"));
+        assert_eq!(Some(false), is_compatible(">=2.1", "2.0"));
+        assert_eq!(None, is_compatible("whenever", "2.0.0"));
+    }
+}
@@ -0,0 +1,479 @@
+use itertools::Itertools;
+
+use crate::HubError;
+
+/// Finds an entry by its ID, or failing that by its title.
+pub fn find_entry<'a>(entries: &'a [IndexEntry], id: &str) -> Result<&'a IndexEntry, HubError> {
+    entries.iter()
+        .find(|e| e.id() == id)
+        .or_else(|| entries.iter().find(|e| e.title().eq_ignore_ascii_case(id)))
+        .ok_or_else(|| HubError::EntryNotFound(id.to_owned()))
+}
+
+/// An item on the Hub, such as a template or a plugin.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct IndexEntry {
+    title: String,
+    summary: String,
+    #[serde(default)]
+    description: Option<String>,
+    category: String,
+    language: String,
+    author: String,
+    tags: Vec<String>,
+    repo_url: String,
+    template_id: String,
+    path: String,
+    #[serde(default)]
+    artifact_source: Option<String>,
+    #[serde(default)]
+    artifact_signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    artifact_digest: Option<String>,
+    #[serde(default)]
+    last_updated: Option<String>,
+    #[serde(default)]
+    spin_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    required_plugins: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trigger: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    git_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    versions: Vec<EntryVersion>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deprecated: Option<Deprecation>,
+}
+
+/// Why an entry shouldn't be used any more, and what to use instead.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Deprecation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    /// The ID of the entry to use instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    replacement: Option<String>,
+    /// When the entry stops being maintained or is removed, as `YYYY-MM-DD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end_of_life: Option<String>,
+}
+
+impl Deprecation {
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn replacement(&self) -> Option<&str> {
+        self.replacement.as_deref()
+    }
+
+    pub fn end_of_life(&self) -> Option<&str> {
+        self.end_of_life.as_deref()
+    }
+}
+
+/// A published version of an entry, for entries that keep older versions
+/// available. Anything it doesn't say is the same as for the entry.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct EntryVersion {
+    version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    git_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    artifact_source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    artifact_signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    artifact_digest: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    spin_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    released: Option<String>,
+}
+
+impl EntryVersion {
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The tag or commit of the entry's repository for this version. Versions
+    /// that don't say are taken to be tagged `v` and the version, as in `v2.1.0`.
+    pub fn git_ref(&self) -> String {
+        self.git_ref.clone().unwrap_or_else(|| format!("v{}", self.version))
+    }
+
+    pub fn artifact_source(&self) -> Option<&str> {
+        self.artifact_source.as_deref()
+    }
+
+    pub fn spin_version(&self) -> Option<&str> {
+        self.spin_version.as_deref()
+    }
+
+    pub fn released(&self) -> Option<&str> {
+        self.released.as_deref()
+    }
+
+    fn is_version(&self, version: &str) -> bool {
+        self.version.trim_start_matches('v') == version.trim_start_matches('v')
+    }
+}
+
+const SHORT_SUMMARY_LEN: usize = 60;
+
+// Triggers that are recognised in tags, for entries that don't say their trigger
+const KNOWN_TRIGGERS: &[&str] = &["http", "redis", "cron", "command", "mqtt", "sqs"];
+
+fn absolute(path: &std::path::Path) -> std::path::PathBuf {
+    std::env::current_dir().map(|dir| dir.join(path)).unwrap_or_else(|_| path.to_owned())
+}
+
+impl IndexEntry {
+    pub fn id(&self) -> &str {
+        self.path
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .last()
+            .unwrap_or(self.template_id.as_str())
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn deprecation(&self) -> Option<&Deprecation> {
+        self.deprecated.as_ref()
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated.is_some()
+    }
+
+    /// The title, marked if the entry is deprecated, for listings.
+    pub fn listed_title(&self) -> String {
+        if self.is_deprecated() {
+            format!("{} (deprecated)", self.title)
+        } else {
+            self.title.clone()
+        }
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn short_summary(&self) -> String {
+        if self.summary.len() < SHORT_SUMMARY_LEN {
+            self.summary.clone()
+        } else {
+            let suffix = "...";
+            let max_len = SHORT_SUMMARY_LEN - suffix.len();
+            let truncated = truncate_to_word_boundary(&self.summary, max_len, 5);
+            format!("{truncated}{suffix}")
+        }
+    }
+
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    pub fn language(&self) -> Language {
+        match self.language.to_lowercase().as_str() {
+            "rust" => Language::Rust,
+            "js/ts" | "javascript" | "typescript" => Language::JavaScript,
+            "python" => Language::Python,
+            "go" | "tinygo" => Language::Go,
+            _ => Language::Other(self.language.clone()),
+        }
+    }
+
+    pub fn category(&self) -> Category {
+        Category::parse(&self.category)
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        self.tags.iter().map(|t| t.to_lowercase()).collect_vec()
+    }
+
+    pub fn title_words(&self) -> Vec<String> {
+        self.title.split_whitespace().map(|t| t.to_lowercase()).collect_vec()
+    }
+
+    pub fn repo_url(&self) -> &str {
+        &self.repo_url
+    }
+    
+    pub fn template_id(&self) -> &str {
+        &self.template_id
+    }
+
+    /// The entry's page on the Hub website.
+    pub fn hub_url(&self, hub_url: &url::Url) -> Option<url::Url> {
+        hub_url.join(self.path.trim_start_matches('/')).ok()
+    }
+
+    pub fn artifact_source(&self) -> Option<&str> {
+        self.artifact_source.as_deref()
+    }
+
+    /// URL of a cosign signature of the artifact source.
+    pub fn artifact_signature(&self) -> Option<&str> {
+        self.artifact_signature.as_deref()
+    }
+
+    /// SHA-256 digest of the artifact source, as hex optionally prefixed with `sha256:`.
+    pub fn artifact_digest(&self) -> Option<&str> {
+        self.artifact_digest.as_deref().map(|d| d.strip_prefix("sha256:").unwrap_or(d))
+    }
+
+    pub fn last_updated(&self) -> Option<&str> {
+        self.last_updated.as_deref()
+    }
+
+    pub fn spin_version(&self) -> Option<&str> {
+        self.spin_version.as_deref()
+    }
+
+    /// The tag or commit of the repository the entry is published at, if the
+    /// Hub names one rather than leaving it to the default branch.
+    pub fn git_ref(&self) -> Option<&str> {
+        self.git_ref.as_deref()
+    }
+
+    /// The kind of trigger the template's component uses, such as `http` or
+    /// `redis`. Entries that don't say are taken to use the first trigger
+    /// named in their tags.
+    pub fn trigger(&self) -> Option<String> {
+        match &self.trigger {
+            Some(trigger) => Some(trigger.to_lowercase()),
+            None => self.tags.iter().map(|t| t.to_lowercase()).find(|t| KNOWN_TRIGGERS.contains(&t.as_str())),
+        }
+    }
+
+    /// Spin plugins that applications made from the template need, such as `js2wasm`.
+    pub fn required_plugins(&self) -> &[String] {
+        &self.required_plugins
+    }
+
+    /// `None` if the entry doesn't say which Spin versions it works with, or
+    /// either version can't be understood.
+    pub fn is_compatible_with(&self, spin_version: &str) -> Option<bool> {
+        crate::compat::is_compatible(self.spin_version()?, spin_version)
+    }
+
+    /// The published versions of the entry, newest first. Empty for entries
+    /// that only have the current version.
+    pub fn versions(&self) -> Vec<&EntryVersion> {
+        self.versions.iter()
+            .sorted_by(|v1, v2| {
+                let parsed = |v: &EntryVersion| crate::compat::parse_spin_version(&v.version);
+                // Versions that aren't semver go last, in the order listed
+                match (parsed(v1), parsed(v2)) {
+                    (Some(p1), Some(p2)) => p2.cmp(&p1),
+                    (p1, p2) => p2.is_some().cmp(&p1.is_some()),
+                }
+            })
+            .collect()
+    }
+
+    /// The entry as it was at an earlier version, with that version's sources
+    /// and requirements. A leading `v` is ignored, so `2.1.0` and `v2.1.0` are the same.
+    pub fn at_version(&self, version: &str) -> Result<IndexEntry, HubError> {
+        let found = self.versions.iter().find(|v| v.is_version(version)).ok_or_else(|| HubError::VersionNotFound {
+            entry: self.id().to_owned(),
+            version: version.to_owned(),
+            available: if self.versions.is_empty() {
+                "none, only the current one".to_owned()
+            } else {
+                self.versions().iter().map(|v| v.version.clone()).join(", ")
+            },
+        })?;
+        let mut entry = self.clone();
+        entry.git_ref = Some(found.git_ref());
+        if found.artifact_source.is_some() {
+            entry.artifact_source = found.artifact_source.clone();
+            entry.artifact_signature = found.artifact_signature.clone();
+            entry.artifact_digest = found.artifact_digest.clone();
+        }
+        entry.spin_version = found.spin_version.clone().or(entry.spin_version);
+        entry.last_updated = found.released.clone().or(entry.last_updated);
+        Ok(entry)
+    }
+
+    /// Points the entry at other copies of its sources, as `hub mirror` does.
+    pub fn set_sources(&mut self, repo_url: String, artifact_source: Option<String>) {
+        self.repo_url = repo_url;
+        self.artifact_source = artifact_source;
+    }
+
+    /// Makes sources that are paths, as in an index file written by `hub mirror`,
+    /// into file URLs relative to `base`.
+    pub fn resolve_relative_sources(&mut self, base: &std::path::Path) {
+        let resolve = |source: &mut String| {
+            if url::Url::parse(source).is_err() {
+                if let Ok(file_url) = url::Url::from_file_path(absolute(&base.join(&*source))) {
+                    *source = file_url.to_string();
+                }
+            }
+        };
+        resolve(&mut self.repo_url);
+        if let Some(artifact_source) = &mut self.artifact_source {
+            resolve(artifact_source);
+        }
+        if let Some(signature) = &mut self.artifact_signature {
+            resolve(signature);
+        }
+        for version in &mut self.versions {
+            version.artifact_source.iter_mut().chain(&mut version.artifact_signature).for_each(&resolve);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Language {
+    Neutral,
+    Rust,
+    JavaScript,
+    Python,
+    Go,
+    Other(String),
+}
+
+impl Language {
+    pub fn is_match(&self, lang: &str) -> bool {
+        let lang = lang.to_lowercase();
+        match self {
+            Self::Neutral => true, // TODO: or... false?
+            Self::Rust => lang == "rust" || lang == "rs",
+            Self::JavaScript => lang == "javascript" || lang == "js" || lang == "typescript" || lang == "ts",
+            Self::Python => lang == "python" || lang == "python3" || lang == "py",
+            Self::Go => lang == "go" || lang == "tinygo" || lang == "golang",
+            Self::Other(name) => lang == name.as_str(),
+        }
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Neutral => f.write_str("Neutral"),
+            Self::Rust => f.write_str("Rust"),
+            Self::JavaScript => f.write_str("JS/TS"),
+            Self::Python => f.write_str("Python"),
+            Self::Go => f.write_str("Go"),
+            Self::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Category {
+    Component,
+    Library,
+    Plugin,
+    Template,
+    Sample,
+    Other(String),
+}
+
+impl Category {
+    pub fn parse(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "component" => Category::Component,
+            "library" => Category::Library,
+            "plugin" => Category::Plugin,
+            "sample" => Category::Sample,
+            "template" => Category::Template,
+            _ => Category::Other(value.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Component => f.write_str("Component"),
+            Self::Library => f.write_str("Library"),
+            Self::Plugin => f.write_str("Plugin"),
+            Self::Template => f.write_str("Template"),
+            Self::Sample => f.write_str("Sample"),
+            Self::Other(name) => f.write_str(name),
+        }
+    }
+}
+
+fn truncate_to_word_boundary(source: &str, max_len: usize, min_len: usize) -> &str {
+    if source.len() <= max_len {
+        return source;
+    }
+
+    let mut index = max_len - 1;
+    loop {
+        let ch = source.chars().nth(index).unwrap();
+        if ch.is_whitespace() {
+            return source[..index].trim();
+        }
+        index = index - 1;
+        if index < min_len {
+            break;
+        }
+    }
+
+    &source[..max_len]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn truncation() {
+        assert_eq!("Hello world", truncate_to_word_boundary("Hello world bibblybobbly", 15, 2));
+        assert_eq!("Hello world", truncate_to_word_boundary("Hello world", 15, 2));
+        assert_eq!("Hello", truncate_to_word_boundary("Hello world", 7, 2));
+        assert_eq!("Hello", truncate_to_word_boundary("Hello world", 5, 2));
+        assert_eq!("Hell", truncate_to_word_boundary("Hello world", 4, 2));
+    }
+
+    #[test]
+    fn trigger_comes_from_tags_if_not_given() {
+        let entry = |extra: serde_json::Value| -> IndexEntry {
+            let mut json = serde_json::json!({
+                "title": "T", "summary": "S", "category": "Template", "language": "Rust", "author": "A",
+                "tags": ["Redis", "rust"], "repo_url": "https://example.com/t", "template_id": "t", "path": "/t",
+            });
+            json.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            serde_json::from_value(json).unwrap()
+        };
+        assert_eq!(Some("redis".to_owned()), entry(serde_json::json!({})).trigger());
+        assert_eq!(Some("cron".to_owned()), entry(serde_json::json!({ "trigger": "Cron" })).trigger());
+    }
+
+    #[test]
+    fn earlier_versions_have_their_own_ref_and_requirements() {
+        let entry: IndexEntry = serde_json::from_value(serde_json::json!({
+            "title": "T", "summary": "S", "category": "Template", "language": "Rust", "author": "A",
+            "tags": [], "repo_url": "https://example.com/t", "template_id": "t", "path": "/t", "spin_version": ">=2.0",
+            "versions": [
+                { "version": "1.9.0", "spin_version": ">=1.5" },
+                { "version": "2.1.0", "git_ref": "release-2.1" },
+            ],
+        })).unwrap();
+
+        assert_eq!(vec!["2.1.0", "1.9.0"], entry.versions().iter().map(|v| v.version()).collect_vec());
+
+        let old = entry.at_version("v1.9.0").unwrap();
+        assert_eq!(Some("v1.9.0"), old.git_ref());
+        assert_eq!(Some(">=1.5"), old.spin_version());
+        let newer = entry.at_version("2.1.0").unwrap();
+        assert_eq!(Some("release-2.1"), newer.git_ref());
+        assert_eq!(Some(">=2.0"), newer.spin_version());
+
+        assert!(entry.at_version("3.0.0").is_err());
+    }
+}
@@ -0,0 +1,51 @@
+/// Why the Hub index couldn't be fetched or used.
+#[derive(thiserror::Error, Debug)]
+pub enum HubError {
+    #[error("Couldn't reach the Hub at {url}. Check your network connection, or use --hub-url to choose a different Hub.")]
+    Network {
+        url: url::Url,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("The Hub index couldn't be read. Try again with --refresh, or check that the Hub URL points to a Spin Hub.")]
+    Decode(#[from] serde_json::Error),
+    #[error("There's no Hub index at {url}. Check the Hub URL setting.")]
+    IndexNotFound { url: url::Url },
+    #[error("No Hub entry with ID '{0}'. Use `search` to find entry IDs.")]
+    EntryNotFound(String),
+    #[error("{entry} has no version {version}. The versions on the Hub are: {available}")]
+    VersionNotFound { entry: String, version: String, available: String },
+    #[error("The Hub at {url} needs you to log in. Use `hub login` to store your credentials.")]
+    Unauthorized { url: url::Url },
+    #[error("The Hub at {url} is rate limiting requests. Wait a few minutes and try again.")]
+    RateLimited { url: url::Url },
+    #[error("The Hub at {url} responded with {status}.")]
+    Response {
+        url: url::Url,
+        status: reqwest::StatusCode,
+    },
+    #[error("Couldn't read the Hub index file {}", path.display())]
+    IndexFile {
+        path: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("The Hub at {url} said the index hasn't changed, but there's no cached copy of it.")]
+    NotModified { url: url::Url },
+    #[error("No Hub URL configured")]
+    NoHubUrl,
+    #[error("Couldn't set up the HTTP client: {0}")]
+    HttpClient(String),
+}
+
+impl HubError {
+    /// Whether trying again might succeed, such as after a timeout or a
+    /// server error.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Network { source, .. } => source.is_timeout() || source.is_connect() || source.is_request() || source.is_body(),
+            Self::Response { status, .. } => status.is_server_error(),
+            _ => false,
+        }
+    }
+}
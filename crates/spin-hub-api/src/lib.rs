@@ -0,0 +1,39 @@
+//! Reads the index of the [Spin Up Hub](https://developer.fermyon.com/hub),
+//! the catalogue of templates, samples, plugins and libraries for Spin.
+//!
+//! This is the part of the Hub CLI that other tools, such as editor
+//! extensions, can use to list and search Hub content without running it.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), spin_hub_api::HubError> {
+//! use spin_hub_api::{Category, HubClient};
+//!
+//! let client = HubClient::new(spin_hub_api::default_hub_url());
+//! let templates = client.index().await?
+//!     .into_iter()
+//!     .filter(|entry| entry.category() == Category::Template);
+//! for template in templates {
+//!     println!("{}: {}", template.title(), template.short_summary());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod cache;
+mod client;
+pub mod compat;
+mod entry;
+mod error;
+
+pub use cache::{CachedIndex, IndexCache, Validators};
+pub use client::{index_url, Credentials, Fetched, HubClient};
+pub use entry::{find_entry, Category, Deprecation, EntryVersion, IndexEntry, Language};
+pub use error::HubError;
+
+/// The URL of the public Spin Up Hub.
+pub const DEFAULT_HUB_URL: &str = "https://developer.fermyon.com/";
+
+/// [`DEFAULT_HUB_URL`], parsed.
+pub fn default_hub_url() -> url::Url {
+    url::Url::parse(DEFAULT_HUB_URL).expect("Default Hub URL was malformed")
+}
@@ -0,0 +1,97 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+
+use crate::{http, style, verify};
+
+const EXTENSIONS: &[&str] = &[".tar.gz", ".tgz", ".zip"];
+
+// A tar.gz or zip of a template repository, for hosts where Git can't be used
+pub fn is_archive(source: &str) -> bool {
+    let path = archive_path(source);
+    EXTENSIONS.iter().any(|ext| path.ends_with(ext))
+}
+
+fn is_zip(source: &str) -> bool {
+    archive_path(source).ends_with(".zip")
+}
+
+// The lowercased path the extension is in, without any URL query or fragment
+fn archive_path(source: &str) -> String {
+    let path = url::Url::parse(source).map(|u| u.path().to_owned()).unwrap_or_else(|_| source.to_owned());
+    path.to_lowercase()
+}
+
+// Downloads (or reads) the archive and extracts it into `dest`, returning the
+// directory that holds its contents, and the archive's SHA-256
+pub async fn fetch(source: &str, expected_sha256: Option<&str>, dest: &Path) -> anyhow::Result<(PathBuf, String)> {
+    let bytes = read(source).await?;
+    let sha256 = verify::sha256(&bytes);
+    match expected_sha256 {
+        Some(expected) => verify::check_sha256(&bytes, expected, source)?,
+        None => style::warning!("no checksum was given for {source}, so it can't be checked."),
+    }
+    Ok((extract(&bytes, source, dest)?, sha256))
+}
+
+// From a URL, a file URL or a path
+pub async fn read(source: &str) -> anyhow::Result<Vec<u8>> {
+    match url::Url::parse(source) {
+        Ok(url) if url.scheme() != "file" => http::download(&http::client()?, source).await,
+        Ok(url) => {
+            let path = url.to_file_path().map_err(|_| anyhow!("'{source}' is not a valid file URL"))?;
+            std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))
+        }
+        Err(_) => std::fs::read(source).with_context(|| format!("Failed to read {source}")),
+    }
+}
+
+// Returns the directory in `dest` that holds the archive's contents
+pub fn extract(bytes: &[u8], source: &str, dest: &Path) -> anyhow::Result<PathBuf> {
+    std::fs::create_dir_all(dest)?;
+    if is_zip(source) {
+        zip::ZipArchive::new(std::io::Cursor::new(bytes))
+            .and_then(|mut archive| archive.extract(dest))
+            .with_context(|| format!("Failed to extract {source}"))?;
+    } else {
+        tar::Archive::new(flate2::read::GzDecoder::new(bytes))
+            .unpack(dest)
+            .with_context(|| format!("Failed to extract {source}"))?;
+    }
+    content_root(dest)
+}
+
+// Archives of repositories, like the ones GitHub makes, usually have
+// everything in one top-level directory
+fn content_root(dir: &Path) -> anyhow::Result<PathBuf> {
+    let entries = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    match entries.as_slice() {
+        [only] if only.file_type()?.is_dir() => Ok(only.path()),
+        _ => Ok(dir.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn archives_are_recognised_by_extension() {
+        assert!(is_archive("https://example.com/templates/v1.2.tar.gz"));
+        assert!(is_archive("https://example.com/archive.zip?token=abc"));
+        assert!(is_archive("./templates.TGZ"));
+        assert!(!is_archive("https://github.com/fermyon/spin"));
+        assert!(is_zip("https://example.com/archive.zip?token=abc"));
+        assert!(!is_zip("https://example.com/archive.tar.gz#.zip"));
+    }
+
+    #[test]
+    fn single_top_level_directories_are_the_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("spin-main")).unwrap();
+        assert_eq!(dir.path().join("spin-main"), content_root(dir.path()).unwrap());
+
+        std::fs::write(dir.path().join("README.md"), "").unwrap();
+        assert_eq!(dir.path(), content_root(dir.path()).unwrap());
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::Context;
+
+use crate::settings;
+
+const KEYRING_SERVICE: &str = "spin-hub";
+
+// Credentials for an authenticated Hub are kept in the OS keyring under the Hub URL
+pub use spin_hub_api::Credentials;
+
+fn keyring_entry(hub_url: &url::Url) -> anyhow::Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, hub_url.as_str()).context("Can't access the OS keyring")
+}
+
+pub fn save(hub_url: &url::Url, credentials: &Credentials) -> anyhow::Result<()> {
+    keyring_entry(hub_url)?
+        .set_password(&serde_json::to_string(credentials)?)
+        .context("Failed to store credentials in the OS keyring")
+}
+
+// SPIN_HUB_TOKEN is for CI, where there's no keyring. It's only sent to the
+// main Hub, or to the host named by SPIN_HUB_TOKEN_HOST, as mirrors may be
+// someone else's.
+pub fn load(hub_url: &url::Url) -> Option<Credentials> {
+    if let Ok(token) = std::env::var("SPIN_HUB_TOKEN") {
+        if env_token_is_for(hub_url) {
+            return Some(Credentials::Bearer { token });
+        }
+    }
+    load_stored(hub_url)
+}
+
+fn env_token_is_for(hub_url: &url::Url) -> bool {
+    match std::env::var("SPIN_HUB_TOKEN_HOST") {
+        Ok(host) => hub_url.host_str().is_some_and(|h| h.eq_ignore_ascii_case(host.trim())),
+        Err(_) => hub_url == settings::get().hub_url(),
+    }
+}
+
+// Just what's in the keyring, for OCI registries, which the Hub's
+// SPIN_HUB_TOKEN is no good for
+pub fn load_stored(url: &url::Url) -> Option<Credentials> {
+    let secret = keyring_entry(url).ok()?.get_password().ok()?;
+    serde_json::from_str(&secret).ok()
+}
+
+// Returns whether there were credentials to delete
+pub fn delete(hub_url: &url::Url) -> anyhow::Result<bool> {
+    match keyring_entry(hub_url)?.delete_password() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e).context("Failed to remove credentials from the OS keyring"),
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use spin_hub_api::IndexCache;
+
+use crate::verify;
+
+pub fn cache_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("spin-hub"))
+}
+
+// The parts of the cache, which can be cleaned separately
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum Area {
+    Index,
+    Repos,
+}
+
+impl Area {
+    pub fn all() -> [Area; 2] {
+        [Area::Index, Area::Repos]
+    }
+
+    pub fn dir(&self) -> Option<PathBuf> {
+        let name = match self {
+            Area::Index => "index",
+            Area::Repos => "repos",
+        };
+        cache_dir().map(|dir| dir.join(name))
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Area::Index => "Hub indexes",
+            Area::Repos => "Template repositories",
+        }
+    }
+}
+
+pub fn index_cache() -> Option<IndexCache> {
+    Area::Index.dir().map(IndexCache::new)
+}
+
+// Where a clone of a template repository at a branch, tag or commit is kept.
+// The key is a hash, as any simpler escaping of URLs lets different repositories
+// share a directory. The repository name in front is only for people.
+pub fn repo_dir(repo: &str, git_ref: Option<&str>) -> Option<PathBuf> {
+    Area::Repos.dir().map(|dir| dir.join(repo_key(repo, git_ref)))
+}
+
+fn repo_key(repo: &str, git_ref: Option<&str>) -> String {
+    let repo = repo.trim_end_matches('/');
+    let hash = verify::sha256(format!("{repo}\n{}", git_ref.unwrap_or("HEAD")).as_bytes());
+    let name: String = repo.rsplit('/').next().unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .take(40)
+        .collect();
+    format!("{name}-{}", &hash[..16])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn similar_repos_get_different_directories() {
+        assert_ne!(repo_key("https://github.com/org/my-repo", None), repo_key("https://github.com/org/my_repo", None));
+        assert_ne!(repo_key("https://github.com/org/x", Some("a-b")), repo_key("https://github.com/org/x", Some("a_b")));
+        assert_eq!(repo_key("https://github.com/org/x/", None), repo_key("https://github.com/org/x", None));
+        assert!(repo_key("https://github.com/org/spin-templates", Some("main")).starts_with("spin-templates-"));
+    }
+}
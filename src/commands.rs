@@ -1,7 +1,82 @@
+mod add;
+mod browse;
+mod cache;
+mod compare;
+mod completions;
+mod component;
+mod config;
+mod conflicts;
+mod deps;
+mod diff;
+mod doctor;
+mod entry;
+mod external;
+mod facets;
+mod fields;
+mod history;
+mod hooks;
+mod index;
+mod login;
+mod mirror;
+mod names;
 mod new;
+mod notify;
+mod open;
+mod outdated;
+mod plugin;
+mod portable;
+mod prompts;
+mod render;
+mod restore;
 mod run;
+mod sample;
 mod search;
+mod selection;
+mod self_update;
+mod serve;
+mod show;
+mod stack;
+mod star;
+mod stats;
+mod summary;
+mod telemetry;
+mod templates;
+mod trust;
+mod upgrade;
+mod values;
 
+pub use add::AddCommand;
+pub use browse::BrowseCommand;
+pub use cache::CacheCommand;
+pub use compare::CompareCommand;
+pub use completions::{CompleteCommand, CompletionsCommand};
+pub use component::ComponentCommand;
+pub use config::ConfigCommand;
+pub use diff::DiffCommand;
+pub use doctor::DoctorCommand;
+pub use entry::EntryCommand;
+pub use external::run_external;
+pub use facets::{AuthorsCommand, CategoriesCommand, TagsCommand};
+pub use history::HistoryCommand;
+pub use index::IndexCommand;
+pub use login::{LoginCommand, LogoutCommand};
+pub use mirror::MirrorCommand;
 pub use new::NewCommand;
+pub use notify::UpdateNotice;
+pub use open::OpenCommand;
+pub use outdated::OutdatedCommand;
+pub use plugin::PluginCommand;
+pub use restore::RestoreCommand;
 pub use run::RunCommand;
+pub use sample::SampleCommand;
 pub use search::SearchCommand;
+pub use self_update::SelfCommand;
+pub use serve::ServeCommand;
+pub use show::ShowCommand;
+pub use stack::StackCommand;
+pub use star::{StarCommand, StarsCommand, UnstarCommand};
+pub use stats::StatsCommand;
+pub use telemetry::TelemetryCommand;
+pub use templates::TemplatesCommand;
+pub use trust::TrustCommand;
+pub use upgrade::UpgradeCommand;
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use clap::{Parser};
+use spin_templates::{RunOptions, TemplateVariantInfo};
+
+use crate::{history, hub_api::{self, Category}, output};
+use super::{deps, names};
+use super::new::{check_compatibility, record_history, run_template, warn_if_deprecated, TemplateArgs, TemplateRef};
+use super::selection::{resolve_entry, SelectionArgs};
+
+const DEFAULT_MANIFEST_FILE: &str = "spin.toml";
+
+#[derive(Parser, Debug)]
+#[clap(about = "Add a component to an existing application from a template on the Hub")]
+pub struct AddCommand {
+    #[clap(flatten)]
+    selection: SelectionArgs,
+
+    #[clap(name = "name", help = "Name of the component to add")]
+    name: Option<String>,
+
+    #[clap(long = "to", help = "The application manifest (or its directory) to add the component to [default: spin.toml]")]
+    manifest: Option<PathBuf>,
+
+    #[clap(flatten)]
+    template_args: TemplateArgs,
+}
+
+impl AddCommand {
+    pub async fn run(&self) -> Result<()> {
+        let manifest_path = resolve_manifest(self.manifest.as_deref())?;
+
+        let (index_entry, ()) = tokio::join!(
+            resolve_entry(Category::Template, &self.selection, self.template_args.no_prompt),
+            crate::spin::preload_version(),
+        );
+        let Some(index_entry) = index_entry? else {
+            return Ok(());
+        };
+        add_from_entry(&index_entry, self.name.as_deref(), manifest_path, &self.template_args).await
+    }
+}
+
+pub(super) async fn add_from_entry(index_entry: &hub_api::IndexEntry, name: Option<&str>, manifest_path: PathBuf, args: &TemplateArgs) -> Result<()> {
+    output::note!("Template {} by {}", index_entry.title(), index_entry.author());
+    output::note!("{}", index_entry.summary());
+
+    warn_if_deprecated(index_entry);
+    check_compatibility(index_entry, args)?;
+
+    let component_name = names::resolve_name(name, "component", args.no_prompt)?;
+
+    let options = RunOptions {
+        variant: TemplateVariantInfo::AddComponent { manifest_path },
+        name: component_name.clone(),
+        output_path: PathBuf::from(&component_name),
+        values: args.values()?,
+        accept_defaults: args.no_prompt || args.accept_defaults,
+    };
+
+    let template = TemplateRef::from_entry(index_entry);
+    let values = run_template(&template, options, args).await?;
+    deps::check_required_plugins(&template, args).await;
+    record_history(history::Kind::Add, &template, args.git_ref.clone(), &component_name, values.into_iter().collect());
+    Ok(())
+}
+
+pub(super) fn resolve_manifest(path: Option<&Path>) -> Result<PathBuf> {
+    let path = path.unwrap_or(Path::new(DEFAULT_MANIFEST_FILE));
+    let manifest_path = if path.is_dir() {
+        path.join(DEFAULT_MANIFEST_FILE)
+    } else {
+        path.to_owned()
+    };
+
+    if manifest_path.is_file() {
+        Ok(manifest_path)
+    } else {
+        Err(anyhow!("No application manifest found at {}. Run from an application directory or use --to to specify the manifest.", manifest_path.display()))
+    }
+}
@@ -0,0 +1,204 @@
+use clap::{Parser};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use itertools::Itertools;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+
+use crate::{hub_api::{self, Category}, interrupt};
+use super::add::{add_from_entry, resolve_manifest};
+use super::new::{new_from_entry, AppOptions, TemplateArgs};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Browse the Hub interactively")]
+pub struct BrowseCommand {}
+
+impl BrowseCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let entries = hub_api::index().await?;
+        let entries = entries.into_iter().sorted_by(|e1, e2| e1.title().cmp(e2.title())).collect_vec();
+
+        let choice = {
+            let _terminal_guard = TerminalGuard::enter()?;
+            let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+            Browser::new(&entries).run(&mut terminal)?
+        };
+
+        let entry = match choice {
+            Choice::Use(entry) => entry,
+            Choice::Quit => return Ok(()),
+            // Only now that the terminal is back to normal
+            Choice::Interrupted => interrupt::exit(),
+        };
+
+        if entry.category() == Category::Template {
+            // Inside an application, the template may be for adding a component to it
+            match resolve_manifest(None) {
+                Ok(manifest_path) if choose_add(&manifest_path)? => add_from_entry(entry, None, manifest_path, &TemplateArgs::default()).await,
+                _ => new_from_entry(entry, &AppOptions::default(), &TemplateArgs::default()).await,
+            }
+        } else {
+            println!("{} is a {} - see {} for how to use it.", entry.title(), entry.category(), entry.repo_url());
+            Ok(())
+        }
+    }
+}
+
+fn choose_add(manifest_path: &std::path::Path) -> anyhow::Result<bool> {
+    let items = [
+        "Create a new application".to_owned(),
+        format!("Add a component to {}", manifest_path.display()),
+    ];
+    let selected = dialoguer::Select::new()
+        .with_prompt("What would you like to do with the template?")
+        .items(&items)
+        .default(1)
+        .interact()?;
+    Ok(selected == 1)
+}
+
+enum Choice<'a> {
+    Use(&'a hub_api::IndexEntry),
+    Quit,
+    // Raw mode turns Ctrl-C into a key press, so there's no signal for the handler
+    Interrupted,
+}
+
+struct Browser<'a> {
+    entries: &'a [hub_api::IndexEntry],
+    filter: String,
+    visible: Vec<&'a hub_api::IndexEntry>,
+    list_state: ListState,
+}
+
+impl<'a> Browser<'a> {
+    fn new(entries: &'a [hub_api::IndexEntry]) -> Self {
+        let mut browser = Self {
+            entries,
+            filter: String::new(),
+            visible: vec![],
+            list_state: ListState::default(),
+        };
+        browser.apply_filter();
+        browser
+    }
+
+    fn run(mut self, terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>) -> anyhow::Result<Choice<'a>> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                return Ok(Choice::Interrupted);
+            }
+            match key.code {
+                KeyCode::Esc => return Ok(Choice::Quit),
+                KeyCode::Enter => return Ok(self.selected().map_or(Choice::Quit, Choice::Use)),
+                KeyCode::Up => self.move_selection(-1),
+                KeyCode::Down => self.move_selection(1),
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                    self.apply_filter();
+                }
+                KeyCode::Char(c) => {
+                    self.filter.push(c);
+                    self.apply_filter();
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        let terms = self.filter.split_whitespace().map(|t| t.to_lowercase()).collect_vec();
+        self.visible = self.entries.iter()
+            .filter(|e| {
+                let text = format!("{} {} {}", e.title(), e.tags().join(" "), e.summary()).to_lowercase();
+                terms.iter().all(|t| text.contains(t))
+            })
+            .collect();
+        self.list_state.select(if self.visible.is_empty() { None } else { Some(0) });
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.visible.len() as isize - 1);
+        self.list_state.select(Some(next as usize));
+    }
+
+    fn selected(&self) -> Option<&'a hub_api::IndexEntry> {
+        self.list_state.selected().and_then(|idx| self.visible.get(idx).copied())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)])
+            .split(frame.size());
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(rows[1]);
+
+        let filter = Paragraph::new(self.filter.as_str())
+            .block(Block::default().borders(Borders::ALL).title("Filter"));
+        frame.render_widget(filter, rows[0]);
+
+        let items = self.visible.iter().map(|e| ListItem::new(e.listed_title())).collect_vec();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!("Entries ({})", self.visible.len())))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, columns[0], &mut self.list_state);
+
+        let preview = Paragraph::new(self.selected().map(preview).unwrap_or_default())
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title("Details"));
+        frame.render_widget(preview, columns[1]);
+
+        let help = Paragraph::new("Type to filter - Up/Down to move - Enter to use - Esc or Ctrl-C to quit");
+        frame.render_widget(help, rows[2]);
+    }
+}
+
+fn preview(entry: &hub_api::IndexEntry) -> Text<'static> {
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let field = |name: &str, value: String| Line::from(vec![Span::styled(format!("{name}: "), bold), Span::raw(value)]);
+
+    Text::from(vec![
+        Line::from(Span::styled(entry.title().to_owned(), bold)),
+        Line::from(""),
+        field("Category", entry.category().to_string()),
+        field("Language", entry.language().to_string()),
+        field("Author", entry.author().to_owned()),
+        field("Tags", entry.tags().join(", ")),
+        field("Repository", entry.repo_url().to_owned()),
+        Line::from(""),
+        Line::from(entry.summary().to_owned()),
+    ])
+}
+
+// Restores the terminal even if the browser returns an error
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> anyhow::Result<Self> {
+        terminal::enable_raw_mode()?;
+        crossterm::execute!(std::io::stdout(), EnterAlternateScreen)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        _ = crossterm::execute!(std::io::stdout(), LeaveAlternateScreen);
+        _ = terminal::disable_raw_mode();
+    }
+}
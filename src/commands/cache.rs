@@ -0,0 +1,168 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use clap::{Parser, Subcommand};
+
+use crate::cache::{self, Area};
+use crate::{output, settings};
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    Info(InfoCommand),
+    Clean(CleanCommand),
+    Path(PathCommand),
+}
+
+impl CacheCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Info(cmd) => cmd.run().await,
+            Self::Clean(cmd) => cmd.run().await,
+            Self::Path(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Show what is in the cache and how much space it takes")]
+pub struct InfoCommand {}
+
+impl InfoCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let dir = cache_dir()?;
+        println!("Cache directory: {}", dir.display());
+
+        let mut table = output::table(settings::get().output);
+        table.set_header(["Contents", "Entries", "Size", "Location"]);
+        let mut total = 0;
+        for area in Area::all() {
+            let Some(dir) = area.dir() else {
+                continue;
+            };
+            let usage = Usage::of(area, &dir);
+            total += usage.bytes;
+            table.add_row(output::single_line_row(vec![
+                area.describe().to_owned(),
+                usage.entries.to_string(),
+                format_size(usage.bytes),
+                dir.display().to_string(),
+            ]));
+        }
+        println!("{table}");
+        println!("Total: {}", format_size(total));
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Delete cached data, which is fetched again when it's next needed")]
+pub struct CleanCommand {
+    #[clap(value_enum, help = "What to delete [default: everything]")]
+    areas: Vec<Area>,
+}
+
+impl CleanCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        // Fails early with a helpful message if there's no cache directory
+        cache_dir()?;
+        let areas = if self.areas.is_empty() { Area::all().to_vec() } else { self.areas.clone() };
+        for area in areas {
+            let Some(dir) = area.dir() else {
+                continue;
+            };
+            let usage = Usage::of(area, &dir);
+            if dir.exists() {
+                std::fs::remove_dir_all(&dir)
+                    .map_err(|e| anyhow!("Failed to delete {}: {e}", dir.display()))?;
+            }
+            println!("Deleted {}: {} entries, {}", area.describe(), usage.entries, format_size(usage.bytes));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Print the location of the cache directory")]
+pub struct PathCommand {
+    #[clap(value_enum, help = "Print the location of this part of the cache instead")]
+    area: Option<Area>,
+}
+
+impl PathCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let dir = match self.area {
+            Some(area) => area.dir(),
+            None => cache::cache_dir(),
+        };
+        let dir = dir.ok_or_else(no_cache_dir)?;
+        println!("{}", dir.display());
+        Ok(())
+    }
+}
+
+fn cache_dir() -> anyhow::Result<std::path::PathBuf> {
+    cache::cache_dir().ok_or_else(no_cache_dir)
+}
+
+fn no_cache_dir() -> anyhow::Error {
+    anyhow!("Couldn't determine a cache directory. Set XDG_CACHE_HOME (or HOME) to a writable location.")
+}
+
+struct Usage {
+    entries: usize,
+    bytes: u64,
+}
+
+impl Usage {
+    // Missing or unreadable directories count as empty
+    fn of(area: Area, dir: &Path) -> Self {
+        let entries = std::fs::read_dir(dir).map(|entries| {
+            entries.filter_map(|e| e.ok())
+                // Each index has a file of validators alongside it
+                .filter(|e| area != Area::Index || !e.file_name().to_string_lossy().ends_with(".validators.json"))
+                .count()
+        });
+        Self {
+            entries: entries.unwrap_or(0),
+            bytes: dir_size(dir),
+        }
+    }
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| dir_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sizes_are_shown_in_binary_units() {
+        assert_eq!("512 B", format_size(512));
+        assert_eq!("1.5 KiB", format_size(1536));
+        assert_eq!("3.0 MiB", format_size(3 * 1024 * 1024));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use clap::{Parser};
+
+use crate::{hub_api::{self, Category}, output, settings, style};
+use super::new::TemplateRef;
+use super::render::{self, Rendered};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Compare two Hub entries side by side, and the applications two templates create")]
+pub struct CompareCommand {
+    #[clap(name = "first", help = "The ID or title of the first entry")]
+    first: String,
+
+    #[clap(name = "second", help = "The ID or title of the second entry")]
+    second: String,
+
+    #[clap(long, help = "Also show how the files that both templates create differ")]
+    diff: bool,
+
+    #[clap(long = "no-files", conflicts_with = "diff", help = "Only compare the entries' details, without creating applications from templates")]
+    no_files: bool,
+}
+
+// Both applications get the same name, so it doesn't show up as a difference
+const APP_NAME: &str = "my-app";
+
+enum FileStatus {
+    OnlyFirst,
+    OnlySecond,
+    Same,
+    Different,
+}
+
+impl CompareCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let entries = hub_api::index().await?;
+        let first = hub_api::find_entry(&entries, &self.first)?;
+        let second = hub_api::find_entry(&entries, &self.second)?;
+
+        print_details(first, second);
+
+        let both_templates = first.category() == Category::Template && second.category() == Category::Template;
+        if self.no_files || !both_templates {
+            return Ok(());
+        }
+
+        output::note!("Creating applications from both templates with their default values...");
+        let first_app = render::render(&TemplateRef::from_entry(first), APP_NAME, Default::default(), first.git_ref().map(|r| r.to_owned())).await?;
+        let second_app = render::render(&TemplateRef::from_entry(second), APP_NAME, Default::default(), second.git_ref().map(|r| r.to_owned())).await?;
+        let files = compare_files(&first_app, &second_app)?;
+
+        println!();
+        let mut table = output::table(settings::get().output);
+        table.set_header(["File", first.id(), second.id()]);
+        for (path, status) in &files {
+            let (a, b) = match status {
+                FileStatus::OnlyFirst => ("yes", ""),
+                FileStatus::OnlySecond => ("", "yes"),
+                FileStatus::Same => ("same", "same"),
+                FileStatus::Different => ("differs", "differs"),
+            };
+            table.add_row(output::single_line_row(vec![path.display().to_string(), a.to_owned(), b.to_owned()]));
+        }
+        println!("{table}");
+
+        if self.diff {
+            for (path, status) in &files {
+                if let FileStatus::Different = status {
+                    print_diff(&first_app, &second_app, path, first.id(), second.id())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn print_details(first: &hub_api::IndexEntry, second: &hub_api::IndexEntry) {
+    let settings = settings::get();
+    let rows = [
+        ("ID", first.id().to_owned(), second.id().to_owned()),
+        ("Category", first.category().to_string(), second.category().to_string()),
+        ("Language", first.language().to_string(), second.language().to_string()),
+        ("Trigger", first.trigger().unwrap_or_default(), second.trigger().unwrap_or_default()),
+        ("Author", first.author().to_owned(), second.author().to_owned()),
+        ("Tags", first.tags().join(", "), second.tags().join(", ")),
+        ("Repository", first.repo_url().to_owned(), second.repo_url().to_owned()),
+        ("Spin version", first.spin_version().unwrap_or_default().to_owned(), second.spin_version().unwrap_or_default().to_owned()),
+        ("Needs plugins", first.required_plugins().join(", "), second.required_plugins().join(", ")),
+        ("Last updated", first.last_updated().unwrap_or_default().to_owned(), second.last_updated().unwrap_or_default().to_owned()),
+        ("Deprecated", deprecated(first), deprecated(second)),
+        ("Summary", first.summary().to_owned(), second.summary().to_owned()),
+    ];
+
+    let mut table = output::table(settings.output);
+    table.set_header(["", first.title(), second.title()]);
+    for (name, a, b) in rows {
+        // Where they differ is what the user is looking for
+        let name = if a == b { name.to_owned() } else { style::attention(name) };
+        table.add_row(vec![name, a, b]);
+    }
+    println!("{table}");
+}
+
+fn deprecated(entry: &hub_api::IndexEntry) -> String {
+    match entry.deprecation() {
+        Some(deprecation) => deprecation.message().unwrap_or("yes").to_owned(),
+        None => String::new(),
+    }
+}
+
+fn compare_files(first: &Rendered, second: &Rendered) -> anyhow::Result<Vec<(PathBuf, FileStatus)>> {
+    let first_files = first.files.iter().collect::<BTreeSet<_>>();
+    let second_files = second.files.iter().collect::<BTreeSet<_>>();
+    let mut files = vec![];
+    for path in first_files.union(&second_files) {
+        let status = match (first_files.contains(path), second_files.contains(path)) {
+            (true, false) => FileStatus::OnlyFirst,
+            (false, _) => FileStatus::OnlySecond,
+            (true, true) => {
+                if std::fs::read(first.path().join(path))? == std::fs::read(second.path().join(path))? {
+                    FileStatus::Same
+                } else {
+                    FileStatus::Different
+                }
+            }
+        };
+        files.push(((*path).clone(), status));
+    }
+    Ok(files)
+}
+
+fn print_diff(first: &Rendered, second: &Rendered, path: &std::path::Path, first_id: &str, second_id: &str) -> anyhow::Result<()> {
+    let old = std::fs::read(first.path().join(path))?;
+    let new = std::fs::read(second.path().join(path))?;
+    let name = path.display().to_string().replace('\\', "/");
+    match (std::str::from_utf8(&old), std::str::from_utf8(&new)) {
+        (Ok(old), Ok(new)) => print!("{}", similar::TextDiff::from_lines(old, new)
+            .unified_diff()
+            .header(&format!("{first_id}/{name}"), &format!("{second_id}/{name}"))),
+        _ => println!("Binary file {name} differs"),
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use clap::{CommandFactory, Parser, ValueEnum};
+use clap_complete::Shell;
+use itertools::Itertools;
+
+use crate::hub_api;
+
+const BIN_NAME: &str = "hub";
+
+#[derive(Parser, Debug)]
+#[clap(about = "Generate shell completions. Search terms and entry IDs are completed from the cached Hub index in bash and fish")]
+pub struct CompletionsCommand {
+    #[clap(value_enum)]
+    shell: Shell,
+}
+
+impl CompletionsCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let mut command = crate::Hub::command();
+        clap_complete::generate(self.shell, &mut command, BIN_NAME, &mut std::io::stdout());
+
+        match self.shell {
+            Shell::Bash => println!("{BASH_DYNAMIC}"),
+            Shell::Fish => println!("{FISH_DYNAMIC}"),
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+// Wraps the clap-generated `_hub` function so that the value of -t is
+// completed from the index rather than as a file name.
+const BASH_DYNAMIC: &str = r#"
+_hub_dynamic() {
+    if [[ "${COMP_WORDS[COMP_CWORD-1]}" == "-t" ]]; then
+        COMPREPLY=( $(compgen -W "$(hub __complete terms 2>/dev/null)" -- "${COMP_WORDS[COMP_CWORD]}") )
+        return 0
+    fi
+    _hub "$@"
+}
+complete -F _hub_dynamic -o bashdefault -o default hub"#;
+
+const FISH_DYNAMIC: &str = r#"
+complete -c hub -s t -x -a '(hub __complete terms 2>/dev/null)'
+complete -c hub -n '__fish_seen_subcommand_from show' -f -a '(hub __complete ids 2>/dev/null)'"#;
+
+#[derive(Parser, Debug)]
+pub struct CompleteCommand {
+    #[clap(value_enum)]
+    kind: CompletionKind,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum CompletionKind {
+    Terms,
+    Ids,
+}
+
+impl CompleteCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let entries = hub_api::cached_index().unwrap_or_default();
+        let values = match self.kind {
+            CompletionKind::Terms => entries.iter()
+                .flat_map(|e| e.tags().into_iter().chain(e.title_words()))
+                .unique()
+                .sorted()
+                .collect_vec(),
+            CompletionKind::Ids => entries.iter().map(|e| e.id().to_owned()).sorted().collect_vec(),
+        };
+        for value in values {
+            println!("{value}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+
+use crate::{http, hub_api::{self, Category}, oci, output, progress::Progress, style, trust, verify};
+use super::add::resolve_manifest;
+use super::plugin::{check_signature, VerifyArgs};
+use super::selection::{resolve_entry, SelectionArgs};
+
+#[derive(Subcommand, Debug)]
+pub enum ComponentCommand {
+    Add(AddCommand),
+}
+
+impl ComponentCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Add(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Add a prebuilt Wasm component from the Hub to an application")]
+pub struct AddCommand {
+    #[clap(name = "name", help = "Search terms identifying the component to add")]
+    terms: Vec<String>,
+
+    #[clap(long = "name", value_name = "NAME", help = "Name of the component in spin.toml [default: the Hub entry ID]")]
+    component_name: Option<String>,
+
+    #[clap(long = "to", help = "The application manifest (or its directory) to add the component to [default: spin.toml]")]
+    manifest: Option<PathBuf>,
+
+    #[clap(long, help = "The HTTP route for the component [default: /NAME/...]")]
+    route: Option<String>,
+
+    #[clap(short = 'y', long = "yes", help = "Add without asking for confirmation")]
+    yes: bool,
+
+    #[clap(flatten)]
+    verification: VerifyArgs,
+}
+
+impl AddCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let manifest_path = resolve_manifest(self.manifest.as_deref())?;
+        let manifest_text = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+        let manifest: toml::Table = toml::from_str(&manifest_text)
+            .with_context(|| format!("{} is not a valid Spin manifest", manifest_path.display()))?;
+        if manifest.get("spin_manifest_version").and_then(|v| v.as_integer()) != Some(2) {
+            return Err(anyhow!("{} is not a version 2 Spin manifest, which Spin 2.0 and later use. Only version 2 manifests are supported.", manifest_path.display()));
+        }
+
+        let Some(index_entry) = resolve_entry(Category::Component, &SelectionArgs::with_terms(&self.terms), self.yes).await? else {
+            return Ok(());
+        };
+        output::note!("Component {} by {}", index_entry.title(), index_entry.author());
+        output::note!("{}", index_entry.summary());
+
+        let name = self.component_name.clone().unwrap_or_else(|| index_entry.id().to_owned());
+        let exists = manifest.get("component").and_then(|c| c.get(&name)).is_some();
+        if exists {
+            return Err(anyhow!("{} already has a component named '{name}'. Use --name to choose another name.", manifest_path.display()));
+        }
+        trust::check(&trust::Publisher { author: Some(index_entry.author()), repo_url: index_entry.repo_url() }, self.yes)?;
+
+        let app_dir = manifest_path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let source = PathBuf::from("components").join(format!("{name}.wasm"));
+        download_component(&index_entry, &self.verification, &app_dir.join(&source)).await?;
+
+        let trigger = index_entry.trigger().unwrap_or_else(|| "http".to_owned());
+        let route = self.route.clone().unwrap_or_else(|| format!("/{name}/..."));
+        let stanza = component_stanza(&name, &source, &trigger, &route);
+        let mut text = manifest_text;
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&stanza);
+        std::fs::write(&manifest_path, text)
+            .with_context(|| format!("Failed to update {}", manifest_path.display()))?;
+
+        output::print_outcome(&format!("Added {name} to {} (source {})", manifest_path.display(), source.display()), &name);
+        if trigger != "http" {
+            output::note!("Add a [[trigger.{trigger}]] entry for {name} to {} to say when it runs", manifest_path.display());
+        }
+        Ok(())
+    }
+}
+
+async fn download_component(index_entry: &hub_api::IndexEntry, verification: &VerifyArgs, dest: &Path) -> anyhow::Result<()> {
+    let url = index_entry.artifact_source().ok_or_else(|| anyhow!(
+        "{} doesn't publish a Wasm file on the Hub. See {} for how to use it.",
+        index_entry.title(),
+        index_entry.repo_url()
+    ))?;
+    let client = http::client()?;
+    let progress = Progress::start(format!("Downloading {url}"));
+    let wasm = if oci::is_oci(url) {
+        // Registries check layers against their digests as they're pulled
+        oci::pull_wasm(url).await?
+    } else {
+        http::download(&client, url).await?
+    };
+    progress.finish();
+
+    if !verification.no_verify {
+        match index_entry.artifact_digest() {
+            Some(expected) => verify::check_sha256(&wasm, expected, url)?,
+            None => style::warning!("{} doesn't publish a checksum, so the download can't be checked.", index_entry.title()),
+        }
+        let tempdir = tempfile::tempdir()?;
+        let wasm_path = tempdir.path().join("component.wasm");
+        std::fs::write(&wasm_path, &wasm)?;
+        check_signature(index_entry, verification, &client, &wasm_path, tempdir.path()).await?;
+    }
+
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(dest, &wasm).with_context(|| format!("Failed to write {}", dest.display()))
+}
+
+// Only HTTP triggers can be set up without knowing more about the component
+fn component_stanza(name: &str, source: &Path, trigger: &str, route: &str) -> String {
+    let source = source.to_string_lossy().replace('\\', "/");
+    let mut stanza = String::new();
+    if trigger == "http" {
+        stanza.push_str(&format!("\n[[trigger.http]]\nroute = {}\ncomponent = {}\n", toml_string(route), toml_string(name)));
+    }
+    stanza.push_str(&format!("\n[component.{}]\nsource = {}\nallowed_outbound_hosts = []\n", toml_key(name), toml_string(&source)));
+    stanza
+}
+
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_owned()).to_string()
+}
+
+fn toml_key(key: &str) -> String {
+    if key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        key.to_owned()
+    } else {
+        toml_string(key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn http_components_get_a_route() {
+        let stanza = component_stanza("kv-explorer", Path::new("components/kv-explorer.wasm"), "http", "/kv/...");
+        let parsed: toml::Table = toml::from_str(&stanza).unwrap();
+        assert_eq!("/kv/...", parsed["trigger"]["http"][0]["route"].as_str().unwrap());
+        assert_eq!("components/kv-explorer.wasm", parsed["component"]["kv-explorer"]["source"].as_str().unwrap());
+
+        let stanza = component_stanza("worker", Path::new("components/worker.wasm"), "redis", "/worker/...");
+        assert!(!stanza.contains("trigger"));
+    }
+}
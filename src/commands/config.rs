@@ -0,0 +1,131 @@
+use clap::{Parser, Subcommand};
+
+use crate::{config, settings};
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    Get(GetCommand),
+    Set(SetCommand),
+    List(ListCommand),
+}
+
+impl ConfigCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Get(cmd) => cmd.run(),
+            Self::Set(cmd) => cmd.run(),
+            Self::List(cmd) => cmd.run(),
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Print the value of a setting")]
+pub struct GetCommand {
+    key: String,
+
+    #[clap(long = "show-secrets", help = "Show tokens in full instead of only their last characters")]
+    show_secrets: bool,
+}
+
+impl GetCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        let mut config = config::load()?;
+        if let Some(value) = config.section_mut(profile()).get(&self.key)? {
+            println!("{}", shown(&self.key, value, self.show_secrets));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Change a setting. Use an empty value to clear it")]
+pub struct SetCommand {
+    key: String,
+
+    #[clap(help = "The new value. Lists such as mirrors are comma-separated")]
+    value: String,
+}
+
+impl SetCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        let mut config = config::load()?;
+        config.section_mut(profile()).set(&self.key, &self.value)?;
+        let path = config.save()?;
+        println!("Updated {}", path.display());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "List all settings")]
+pub struct ListCommand {
+    #[clap(long = "show-secrets", help = "Show tokens in full instead of only their last characters")]
+    show_secrets: bool,
+}
+
+impl ListCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        let mut config = config::load()?;
+        if let Some(path) = config::config_path() {
+            println!("# {}", path.display());
+        }
+        if let Some(profile) = profile() {
+            println!("# profile {profile}");
+        }
+        let section = config.section_mut(profile());
+        for key in config::KEYS {
+            match section.get(key)? {
+                Some(value) => println!("{key} = {}", shown(key, value, self.show_secrets)),
+                None => println!("{key} (not set)"),
+            }
+        }
+        Ok(())
+    }
+}
+
+// Tokens are masked so they don't end up in scrollback or pasted output
+fn shown(key: &str, value: String, show_secrets: bool) -> String {
+    match key {
+        _ if show_secrets => value,
+        "github-token" => mask(&value),
+        // HOST=TOKEN pairs
+        "git-credentials" => value.split(',')
+            .map(|credential| match credential.split_once('=') {
+                Some((host, token)) => format!("{host}={}", mask(token)),
+                None => mask(credential),
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => value,
+    }
+}
+
+// The last few characters are enough to tell tokens apart, unless the token
+// is so short that they'd give away much of it
+fn mask(secret: &str) -> String {
+    let chars = secret.chars().collect::<Vec<_>>();
+    if chars.len() < 12 {
+        return "****".to_owned();
+    }
+    format!("****{}", chars[chars.len() - 4..].iter().collect::<String>())
+}
+
+// With --profile, settings are read from and written to that profile
+fn profile() -> Option<&'static str> {
+    settings::get().profile.as_deref()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokens_are_masked_unless_asked_for() {
+        let token = "ghp_abcdefghijklmnop".to_owned();
+        assert_eq!("****mnop", shown("github-token", token.clone(), false));
+        assert_eq!(token, shown("github-token", token.clone(), true));
+        assert_eq!("git.example.com=****mnop,other.example.com=****", shown("git-credentials", format!("git.example.com={token},other.example.com=short"), false));
+        assert_eq!("https://hub.example.com", shown("hub-url", "https://hub.example.com".to_owned(), false));
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::output;
+
+// What to do with a generated file when the output directory already has one
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub(super) enum OnConflict {
+    Overwrite,
+    Skip,
+    Prompt,
+    Abort,
+}
+
+// Generated files, relative to the application, that were or weren't written
+// over existing ones
+#[derive(Debug, Default)]
+pub(super) struct Resolution {
+    pub overwritten: Vec<PathBuf>,
+    pub skipped: BTreeSet<PathBuf>,
+}
+
+// Paths in `generated` that `existing` already has, relative to both. A
+// directory in both is only a conflict if files in it are.
+pub(super) fn find_conflicts(generated: &Path, existing: &Path) -> Result<Vec<PathBuf>> {
+    let mut conflicts = vec![];
+    if existing.is_dir() {
+        collect_conflicts(generated, existing, Path::new(""), &mut conflicts)?;
+    }
+    conflicts.sort();
+    Ok(conflicts)
+}
+
+fn collect_conflicts(generated: &Path, existing: &Path, relative: &Path, conflicts: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(generated.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        let dest = existing.join(&path);
+        if entry.file_type()?.is_dir() && dest.is_dir() {
+            collect_conflicts(generated, existing, &path, conflicts)?;
+        } else if dest.exists() {
+            conflicts.push(path);
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn resolve(conflicts: Vec<PathBuf>, on_conflict: OnConflict, output_path: &Path, no_prompt: bool) -> Result<Resolution> {
+    let mut resolution = Resolution::default();
+    if conflicts.is_empty() {
+        return Ok(resolution);
+    }
+    match on_conflict {
+        OnConflict::Overwrite => resolution.overwritten = conflicts,
+        OnConflict::Skip => resolution.skipped = conflicts.into_iter().collect(),
+        OnConflict::Abort => {
+            let list = conflicts.iter().map(|p| format!("  {}", p.display())).collect::<Vec<_>>().join("\n");
+            return Err(anyhow::anyhow!("These files already exist in {}, so nothing was changed:\n{list}\nUse --on-conflict overwrite or skip to generate anyway.", output_path.display()));
+        }
+        OnConflict::Prompt if no_prompt => {
+            return Err(anyhow::anyhow!("{} files already exist in {}. Use --on-conflict overwrite, skip or abort instead of prompt.", conflicts.len(), output_path.display()));
+        }
+        OnConflict::Prompt => {
+            for path in conflicts {
+                let overwrite = dialoguer::Confirm::new()
+                    .with_prompt(format!("{} already exists. Replace it with the template's?", path.display()))
+                    .default(false)
+                    .interact()?;
+                if overwrite {
+                    resolution.overwritten.push(path);
+                } else {
+                    resolution.skipped.insert(path);
+                }
+            }
+        }
+    }
+    Ok(resolution)
+}
+
+pub(super) fn print_report(resolution: &Resolution) {
+    if !resolution.overwritten.is_empty() {
+        output::note!("Replaced {} existing files:", resolution.overwritten.len());
+        for path in &resolution.overwritten {
+            output::note!("  {}", path.display());
+        }
+    }
+    if !resolution.skipped.is_empty() {
+        output::note!("Kept {} existing files instead of the template's:", resolution.skipped.len());
+        for path in &resolution.skipped {
+            output::note!("  {}", path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_files_in_both_directories_conflict() {
+        let generated = tempfile::tempdir().unwrap();
+        let existing = tempfile::tempdir().unwrap();
+        for dir in [generated.path(), existing.path()] {
+            std::fs::create_dir_all(dir.join("src")).unwrap();
+            std::fs::write(dir.join("spin.toml"), "").unwrap();
+        }
+        std::fs::write(generated.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(existing.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(generated.path().join("Cargo.toml"), "").unwrap();
+        std::fs::write(existing.path().join("README.md"), "").unwrap();
+
+        let conflicts = find_conflicts(generated.path(), existing.path()).unwrap();
+        assert_eq!(vec![PathBuf::from("spin.toml"), Path::new("src").join("lib.rs")], conflicts);
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::anyhow;
+use itertools::Itertools;
+
+use crate::{hub_api::{self, Category}, style};
+use super::new::{TemplateArgs, TemplateRef};
+use super::plugin::{install_plugin, VerifyArgs};
+
+// Templates on the Hub can say which Spin plugins their applications need to
+// build. Without them, the first `spin build` fails with an unhelpful error,
+// so offer to install any that are missing.
+pub(super) async fn check_required_plugins(template: &TemplateRef, args: &TemplateArgs) {
+    let Some(required) = required_plugins(template).await else {
+        return;
+    };
+    let missing = match missing_plugins(&required).await {
+        Ok(missing) => missing,
+        Err(e) => {
+            style::warning!("can't check for the Spin plugins this template needs ({}): {e:#}", required.join(", "));
+            return;
+        }
+    };
+    if missing.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("The template needs Spin plugins that aren't installed: {}", missing.join(", "));
+    let install = args.install_deps || (!args.no_prompt && dialoguer::Confirm::new()
+        .with_prompt("Install them now?")
+        .default(true)
+        .interact_opt()
+        .ok()
+        .flatten()
+        .unwrap_or_default());
+    if !install {
+        println!("Install them with: {}", missing.iter().map(|p| format!("spin plugins install {p}")).join(" && "));
+        return;
+    }
+
+    for plugin in &missing {
+        if let Err(e) = install(plugin, args.install_deps || args.no_prompt).await {
+            style::warning!("failed to install the {plugin} plugin: {e:#}");
+        }
+    }
+}
+
+async fn required_plugins(template: &TemplateRef) -> Option<Vec<String>> {
+    let id = template.entry.as_ref()?;
+    let entries = hub_api::index().await
+        .map_err(|e| tracing::debug!("Can't look up required plugins: {e:#}"))
+        .ok()?;
+    let required = hub_api::find_entry(&entries, id).ok()?.required_plugins().to_vec();
+    (!required.is_empty()).then_some(required)
+}
+
+async fn missing_plugins(required: &[String]) -> anyhow::Result<Vec<String>> {
+    let output = crate::spin::bin().args(["plugins", "list", "--installed"]).output().await?;
+    if !output.status.success() {
+        return Err(anyhow!("spin plugins list failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    let installed = parse_installed(&String::from_utf8_lossy(&output.stdout));
+    Ok(required.iter().filter(|p| !installed.contains(p)).cloned().collect())
+}
+
+// `spin plugins list --installed` prints a line like `js2wasm 0.6.1 [installed]` for each plugin
+fn parse_installed(output: &str) -> Vec<String> {
+    output.lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|name| name.to_owned())
+        .collect()
+}
+
+// Prefer the Hub's entry for the plugin, so it's verified the same way as
+// `hub plugin install`, and otherwise use Spin's plugin catalogue.
+async fn install(plugin: &str, yes: bool) -> anyhow::Result<()> {
+    let entries = hub_api::index().await?;
+    if let Some(entry) = hub_api::find_entry(&entries, plugin).ok().filter(|e| e.category() == Category::Plugin) {
+        return install_plugin(entry, &VerifyArgs::default(), yes).await;
+    }
+    let mut command = crate::spin::bin();
+    command.args(["plugins", "install", plugin]);
+    if yes {
+        command.arg("--yes");
+    }
+    if !command.status().await?.success() {
+        return Err(anyhow!("spin plugins install failed - see output for details"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn installed_plugin_names_are_the_first_word_of_each_line() {
+        let output = "cloud 0.7.0 [installed]\njs2wasm 0.6.1 [installed]\n\n";
+        assert_eq!(vec!["cloud", "js2wasm"], parse_installed(output));
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use clap::{Parser};
+
+use crate::{hub_api::Category, provenance::{self, Provenance}};
+use super::new::TemplateRef;
+use super::render;
+use super::selection::{resolve_entry, SelectionArgs};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Show how an application differs from the template it was created from")]
+pub struct DiffCommand {
+    #[clap(flatten)]
+    selection: SelectionArgs,
+
+    #[clap(name = "name", help = "The application directory")]
+    dir: PathBuf,
+
+    #[clap(long = "git-ref", alias = "branch", value_name = "REF", help = "Branch, tag or commit of the template repository to compare with [default: the default branch]")]
+    git_ref: Option<String>,
+}
+
+impl DiffCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        if !self.dir.is_dir() {
+            return Err(anyhow!("{} is not a directory", self.dir.display()));
+        }
+
+        // Applications created by `hub new` say which template they came from;
+        // for others, the user picks one
+        let (template, name, values) = if self.dir.join(provenance::FILE_NAME).exists() {
+            let provenance = Provenance::read(&self.dir)?;
+            (
+                render::provenance_template(&provenance).await?,
+                provenance.name.clone(),
+                provenance.values.into_iter().collect(),
+            )
+        } else {
+            let Some(index_entry) = resolve_entry(Category::Template, &self.selection, false).await? else {
+                return Ok(());
+            };
+            (TemplateRef::from_entry(&index_entry), self.app_name()?, Default::default())
+        };
+
+        let rendered = render::render(&template, &name, values, self.git_ref.clone()).await?;
+        let changes = render::changes(&rendered, &self.dir)?;
+
+        if changes.is_empty() {
+            println!("{} matches the template", self.dir.display());
+        } else {
+            render::print_changes(&changes);
+        }
+        Ok(())
+    }
+
+    fn app_name(&self) -> anyhow::Result<String> {
+        let dir = self.dir.canonicalize()?;
+        dir.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| anyhow!("Can't determine the application name from {}", self.dir.display()))
+    }
+}
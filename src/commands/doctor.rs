@@ -0,0 +1,86 @@
+use anyhow::anyhow;
+use clap::{Parser};
+
+use crate::{cache, hub_api, settings, spin};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Check that the Hub CLI has everything it needs to work")]
+pub struct DoctorCommand {}
+
+struct Check {
+    name: String,
+    result: Result<String, String>,
+}
+
+impl DoctorCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let mut checks = vec![check_spin(), check_git().await];
+        for hub_url in &settings::get().hub_urls {
+            checks.push(check_hub(hub_url).await);
+        }
+        checks.push(check_cache_dir());
+
+        let mut failures = 0;
+        for check in &checks {
+            match &check.result {
+                Ok(detail) => println!("[ok]   {}: {detail}", check.name),
+                Err(fix) => {
+                    failures += 1;
+                    println!("[FAIL] {}: {fix}", check.name);
+                }
+            }
+        }
+
+        if failures == 0 {
+            Ok(())
+        } else {
+            Err(anyhow!("{failures} of {} checks failed", checks.len()))
+        }
+    }
+}
+
+fn check_spin() -> Check {
+    let path = spin::bin_path();
+    let result = match spin::detect_version() {
+        Some(version) => Ok(format!("Spin {version} at {}", path.display())),
+        None => Err(format!(
+            "Couldn't run {}. Install Spin from https://developer.fermyon.com/spin/install, or set SPIN_BIN_PATH to its location.",
+            path.display()
+        )),
+    };
+    Check { name: "Spin".to_owned(), result }
+}
+
+async fn check_git() -> Check {
+    let output = tokio::process::Command::new("git").arg("--version").output().await;
+    let result = match output {
+        Ok(output) if output.status.success() => Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned()),
+        _ => Err("Couldn't run git. Install git and make sure it is on your PATH - it is needed to fetch templates.".to_owned()),
+    };
+    Check { name: "Git".to_owned(), result }
+}
+
+async fn check_hub(hub_url: &url::Url) -> Check {
+    let result = match hub_api::check_reachable(hub_url).await {
+        Ok(()) => Ok("reachable".to_owned()),
+        Err(e) => Err(e.to_string()),
+    };
+    Check { name: format!("Hub {hub_url}"), result }
+}
+
+fn check_cache_dir() -> Check {
+    let result = match cache::cache_dir() {
+        None => Err("Couldn't determine a cache directory. Set XDG_CACHE_HOME (or HOME) to a writable location.".to_owned()),
+        Some(dir) => {
+            let probe = dir.join(".doctor");
+            let writable = std::fs::create_dir_all(&dir)
+                .and_then(|_| std::fs::write(&probe, b""))
+                .and_then(|_| std::fs::remove_file(&probe));
+            match writable {
+                Ok(()) => Ok(format!("{} is writable", dir.display())),
+                Err(e) => Err(format!("{} is not writable ({e}). The Hub index can't be cached; check the directory's permissions.", dir.display())),
+            }
+        }
+    };
+    Check { name: "Cache".to_owned(), result }
+}
@@ -0,0 +1,528 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use clap::{Parser, Subcommand};
+use itertools::Itertools;
+
+use base64::Engine;
+
+use crate::{git, github::{self, GitHub}, http, hub_api, output::{self, OutputFormat}, ranking, settings};
+use super::{search, selection::SelectionArgs, show};
+use crate::hub_entry::{self, Extra, HubEntry, Metadata};
+
+#[derive(Subcommand, Debug)]
+pub enum EntryCommand {
+    Init(InitCommand),
+    Validate(ValidateCommand),
+    Preview(PreviewCommand),
+    Submit(SubmitCommand),
+}
+
+impl EntryCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Init(cmd) => cmd.run().await,
+            Self::Validate(cmd) => cmd.run().await,
+            Self::Preview(cmd) => cmd.run().await,
+            Self::Submit(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Write the Hub entry file for a project, asking for each field")]
+pub struct InitCommand {
+    #[clap(name = "file", default_value = hub_entry::DEFAULT_FILE, help = "Where to write the entry")]
+    file: PathBuf,
+
+    #[clap(long, help = "Replace the file if it already exists")]
+    force: bool,
+}
+
+impl InitCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        if self.file.exists() && !self.force {
+            return Err(anyhow!("{} already exists. Use --force to replace it.", self.file.display()));
+        }
+        let project_dir = std::env::current_dir()?;
+        let guesses = Guesses::for_project(&project_dir).await;
+
+        let title = input("Title", guesses.title, hub_entry::check_title)?;
+        let summary = input("Summary (one sentence)", None, hub_entry::check_summary)?;
+        let category = select("Category", hub_entry::CATEGORIES, guesses.category)?;
+        let language = select("Language", hub_entry::LANGUAGES, guesses.language)?;
+        let tags = input("Tags (comma-separated)", guesses.tags, |v| hub_entry::check_tags(&split_tags(v)))?;
+        let author = input("Author", guesses.author, hub_entry::check_not_empty)?;
+        let url = input("Repository URL", guesses.url, hub_entry::check_url)?;
+        let template_id = if category == "Template" {
+            Some(input("Template ID", guesses.template_id, hub_entry::check_not_empty)?)
+        } else {
+            None
+        };
+        let spin_version = optional_input("Spin versions it works with, e.g. >=v2.0 (optional)", hub_entry::check_spin_version)?;
+        let artifact_source = if matches!(category, "Plugin" | "Component") {
+            optional_input("Artifact URL, such as a plugin manifest or Wasm file (optional)", hub_entry::check_url)?
+        } else {
+            None
+        };
+
+        let today = hub_entry::today();
+        let entry = HubEntry {
+            metadata: Metadata {
+                title,
+                template: "render_hub_content_body".to_owned(),
+                date: today.clone(),
+                tags: split_tags(&tags),
+                extra: Extra {
+                    author,
+                    document_type: "hub_document".to_owned(),
+                    category: category.to_owned(),
+                    language: language.to_owned(),
+                    created_at: today.clone(),
+                    last_updated: today,
+                    spin_version,
+                    summary,
+                    url,
+                    template_id,
+                    artifact_source,
+                },
+            },
+            description: "Describe what the entry does and how to use it.".to_owned(),
+        };
+        std::fs::write(&self.file, entry.render()?)?;
+        println!("Wrote {}. Add a description after the `---` line, then check it with `hub entry validate`", self.file.display());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Check Hub entry files for problems before submitting them")]
+pub struct ValidateCommand {
+    #[clap(name = "path", default_value = hub_entry::DEFAULT_FILE, help = "An entry file, or a directory of them")]
+    path: PathBuf,
+
+    #[clap(long, help = "Don't check that URLs can be reached or compare with the entries already on the Hub")]
+    offline: bool,
+
+    #[clap(long, help = "Fail on warnings as well as errors")]
+    strict: bool,
+
+    #[clap(short = 'o', long = "output", alias = "format", value_enum, env = "SPIN_HUB_OUTPUT")]
+    output: Option<OutputFormat>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Finding {
+    file: String,
+    field: String,
+    severity: Severity,
+    message: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Severity {
+    Error,
+    Warning,
+}
+
+impl ValidateCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let files = entry_files(&self.path)?;
+        let live_index = if self.offline { None } else { Some(hub_api::index().await?) };
+
+        let mut findings = vec![];
+        for file in &files {
+            findings.extend(self.check_file(file, live_index.as_deref()).await);
+        }
+
+        match settings::get().output_format(self.output) {
+            OutputFormat::Json => output::print_json(&findings)?,
+            OutputFormat::Jsonl => output::print_json_lines(&findings)?,
+            _ if findings.is_empty() => {
+                output::note!("No problems found in {} {}", files.len(), if files.len() == 1 { "entry" } else { "entries" });
+            }
+            format => {
+                let mut table = output::table(format);
+                table.set_header(["File", "Severity", "Field", "Problem"]);
+                for finding in &findings {
+                    let severity = match finding.severity {
+                        Severity::Error => "error",
+                        Severity::Warning => "warning",
+                    };
+                    table.add_row([finding.file.as_str(), severity, finding.field.as_str(), finding.message.as_str()]);
+                }
+                println!("{table}");
+            }
+        }
+
+        let failures = findings.iter().filter(|f| self.strict || f.severity == Severity::Error).count();
+        if failures > 0 {
+            return Err(anyhow!("{failures} {} found", if failures == 1 { "problem" } else { "problems" }));
+        }
+        Ok(())
+    }
+
+    async fn check_file(&self, file: &Path, live_index: Option<&[hub_api::IndexEntry]>) -> Vec<Finding> {
+        let finding = |field: &str, severity, message: String| Finding {
+            file: file.display().to_string(),
+            field: field.to_owned(),
+            severity,
+            message,
+        };
+        let entry = match HubEntry::load(file) {
+            Ok(entry) => entry,
+            Err(e) => return vec![finding("", Severity::Error, format!("{e:#}"))],
+        };
+
+        let mut findings = entry.problems().into_iter()
+            .map(|p| finding(p.field, if p.warning { Severity::Warning } else { Severity::Error }, p.message))
+            .collect_vec();
+        if self.offline {
+            return findings;
+        }
+
+        let extra = &entry.metadata.extra;
+        let urls = [("url", Some(&extra.url)), ("artifact_source", extra.artifact_source.as_ref())];
+        for (field, url) in urls {
+            let Some(url) = url.filter(|u| u.starts_with("http")) else {
+                continue;
+            };
+            if let Err(e) = check_reachable(url).await {
+                findings.push(finding(field, Severity::Error, format!("{url} can't be reached: {e:#}")));
+            }
+        }
+
+        // The Hub names entries after their file, so a file with the same
+        // name as another project's entry would replace it
+        let id = file.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let existing = live_index.unwrap_or_default().iter()
+            .find(|e| e.id() == id || e.title().eq_ignore_ascii_case(&entry.metadata.title));
+        if let Some(existing) = existing.filter(|e| e.repo_url() != extra.url) {
+            let field = if existing.id() == id { "file name" } else { "title" };
+            findings.push(finding(field, Severity::Error, format!("The Hub already has an entry '{}' ({}) for {}", existing.title(), existing.id(), existing.repo_url())));
+        }
+        findings
+    }
+}
+
+// An entry file, or the .md files in a directory
+fn entry_files(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_owned()]);
+    }
+    let files = std::fs::read_dir(path)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+        .sorted()
+        .collect_vec();
+    if files.is_empty() {
+        return Err(anyhow!("No entry files (*.md) in {}", path.display()));
+    }
+    Ok(files)
+}
+
+async fn check_reachable(url: &str) -> anyhow::Result<()> {
+    let client = http::client()?;
+    let mut response = client.head(url).send().await?;
+    // Not every server supports HEAD
+    if response.status() == reqwest::StatusCode::METHOD_NOT_ALLOWED {
+        response = client.get(url).send().await?;
+    }
+    response.error_for_status()?;
+    Ok(())
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Show how an entry file would appear in the Hub CLI")]
+pub struct PreviewCommand {
+    #[clap(name = "file", default_value = hub_entry::DEFAULT_FILE, help = "The entry file")]
+    file: PathBuf,
+
+    #[clap(short = 't', long = "term", value_name = "TERM", help = "Check whether these search terms would find the entry (can be repeated)")]
+    terms: Vec<String>,
+}
+
+impl PreviewCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let entry = HubEntry::load(&self.file)?;
+        let id = self.file.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let index_entry = entry.to_index_entry(&id)?;
+
+        println!("In `hub search` results:");
+        let relevance = ranking::score(&index_entry, &self.terms);
+        let mut header = search::RESULT_HEADER.to_vec();
+        if !self.terms.is_empty() {
+            header.push("Matched");
+        }
+        let mut table = output::table(settings::get().output);
+        table.set_header(header);
+        table.add_row(output::single_line_row(search::result_row(&index_entry, (!self.terms.is_empty()).then_some(&relevance))));
+        println!("{table}");
+
+        println!();
+        println!("In `hub show {id}`:");
+        println!();
+        show::print_entry(&index_entry);
+
+        println!();
+        if self.terms.is_empty() {
+            let words = index_entry.tags().into_iter().chain(index_entry.title_words()).unique().join(", ");
+            println!("`hub new -t TERM` finds the entry with any of: {words}");
+            println!("Use --term to check other search terms");
+        } else {
+            let terms = self.terms.join(" ");
+            if relevance.is_match() {
+                println!("`hub search {terms}` finds the entry (matching its {})", relevance.matched_fields());
+            } else {
+                println!("`hub search {terms}` doesn't find the entry");
+            }
+            if SelectionArgs::with_terms(&self.terms).is_match(&self.terms, &index_entry) {
+                println!("`hub new -t {}` finds the entry", self.terms.join(" -t "));
+            } else {
+                println!("`hub new -t {}` doesn't find the entry, as it only matches tags and title words unless --deep is used", self.terms.join(" -t "));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Submit an entry file to the Hub by opening a pull request")]
+pub struct SubmitCommand {
+    #[clap(name = "file", default_value = hub_entry::DEFAULT_FILE, help = "The entry file")]
+    file: PathBuf,
+
+    #[clap(long, value_name = "ID", help = "The entry's ID on the Hub, which names its file [default: the name of the project directory]")]
+    id: Option<String>,
+
+    #[clap(long, value_name = "OWNER/REPO", default_value = "fermyon/developer", help = "The GitHub repository of the Hub website")]
+    hub_repo: String,
+
+    #[clap(long, value_name = "DIR", default_value = "content/hub", help = "Where entry files go in the Hub repository")]
+    content_dir: String,
+
+    #[clap(long, env = "SPIN_HUB_GITHUB_TOKEN", hide_env_values = true, help = "GitHub token to use [default: GITHUB_TOKEN, the github-token setting, or the one stored with `hub login --github`]")]
+    token: Option<String>,
+
+    #[clap(short = 'y', long = "yes", help = "Submit without asking for confirmation")]
+    yes: bool,
+}
+
+impl SubmitCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let entry = HubEntry::load(&self.file)?;
+        let errors = entry.problems().into_iter().filter(|p| !p.warning).collect_vec();
+        if !errors.is_empty() {
+            for problem in &errors {
+                eprintln!("{}: {}", problem.field, problem.message);
+            }
+            return Err(anyhow!("{} has problems to fix before it can be submitted. See `hub entry validate`", self.file.display()));
+        }
+        let id = match &self.id {
+            Some(id) => id.clone(),
+            None => default_entry_id()?,
+        };
+        let token = self.token.clone().or_else(github::token)
+            .ok_or_else(|| anyhow!("Submitting needs a GitHub token. Set GITHUB_TOKEN, store one with `hub login --github`, or use --token"))?;
+
+        let path = format!("{}/{id}.md", self.content_dir.trim_end_matches('/'));
+        if !self.yes {
+            let prompt = format!("Open a pull request adding {path} to {}?", self.hub_repo);
+            if !dialoguer::Confirm::new().with_prompt(prompt).default(true).interact_opt()?.unwrap_or_default() {
+                return Ok(());
+            }
+        }
+
+        let github = GitHub::new(token)?;
+        let url = submit(&github, &self.hub_repo, &path, &id, &entry).await?;
+        println!("Opened {url}");
+        Ok(())
+    }
+}
+
+// Entries are named like the project, as the Hub's existing ones are
+fn default_entry_id() -> anyhow::Result<String> {
+    let dir = std::env::current_dir()?;
+    let name = dir.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let id = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' }).collect::<String>();
+    let id = id.trim_matches('-').to_owned();
+    if id.is_empty() {
+        return Err(anyhow!("Can't make an entry ID from the directory name. Use --id"));
+    }
+    Ok(id)
+}
+
+async fn submit(github: &GitHub, hub_repo: &str, path: &str, id: &str, entry: &HubEntry) -> anyhow::Result<String> {
+    let user = github.get("/user").await?;
+    let login = user["login"].as_str().ok_or_else(|| anyhow!("GitHub didn't say who the token belongs to"))?;
+    let upstream = github.get(&format!("/repos/{hub_repo}")).await?;
+    let base = upstream["default_branch"].as_str().unwrap_or("main").to_owned();
+    let repo_name = upstream["name"].as_str().ok_or_else(|| anyhow!("GitHub didn't return the name of {hub_repo}"))?;
+
+    // Forking returns straight away, but the fork may take a moment to be usable
+    println!("Forking {hub_repo}...");
+    let fork = github.post(&format!("/repos/{hub_repo}/forks"), serde_json::json!({})).await?;
+    let fork_name = fork["full_name"].as_str().map(|n| n.to_owned()).unwrap_or_else(|| format!("{login}/{repo_name}"));
+    let mut attempts = 0;
+    while github.get_optional(&format!("/repos/{fork_name}")).await?.is_none() {
+        attempts += 1;
+        if attempts > 10 {
+            return Err(anyhow!("The fork {fork_name} isn't ready yet. Try again in a minute"));
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    }
+
+    let base_ref = github.get(&format!("/repos/{hub_repo}/git/ref/heads/{base}")).await?;
+    let base_sha = base_ref["object"]["sha"].as_str().ok_or_else(|| anyhow!("GitHub didn't return the commit of {base}"))?;
+    let branch = format!("hub-entry-{id}");
+    if github.get_optional(&format!("/repos/{fork_name}/git/ref/heads/{branch}")).await?.is_none() {
+        github.post(&format!("/repos/{fork_name}/git/refs"), serde_json::json!({ "ref": format!("refs/heads/{branch}"), "sha": base_sha })).await?;
+    }
+
+    // Replacing a file needs the one it replaces, e.g. when submitting again
+    let existing = github.get_optional(&format!("/repos/{fork_name}/contents/{path}?ref={branch}")).await?;
+    let title = format!("Add {} to the Hub", entry.metadata.title);
+    let mut content = serde_json::json!({
+        "message": title,
+        "content": base64::engine::general_purpose::STANDARD.encode(entry.render()?),
+        "branch": branch,
+    });
+    if let Some(sha) = existing.as_ref().and_then(|e| e["sha"].as_str()) {
+        content["sha"] = sha.into();
+    }
+    github.put(&format!("/repos/{fork_name}/contents/{path}"), content).await?;
+
+    let body = format!("{}\n\nRepository: {}\n\nSubmitted with `hub entry submit`.", entry.metadata.extra.summary, entry.metadata.extra.url);
+    let pull = github.post(&format!("/repos/{hub_repo}/pulls"), serde_json::json!({
+        "title": title,
+        "head": format!("{login}:{branch}"),
+        "base": base,
+        "body": body,
+    })).await?;
+    Ok(pull["html_url"].as_str().unwrap_or_default().to_owned())
+}
+
+fn input(prompt: &str, default: Option<String>, check: impl Fn(&str) -> Result<(), String>) -> anyhow::Result<String> {
+    let mut input = dialoguer::Input::<String>::new()
+        .with_prompt(prompt)
+        .validate_with(|value: &String| check(value.trim()));
+    if let Some(default) = default {
+        input = input.default(default);
+    }
+    Ok(input.interact_text()?.trim().to_owned())
+}
+
+fn optional_input(prompt: &str, check: impl Fn(&str) -> Result<(), String>) -> anyhow::Result<Option<String>> {
+    let value = dialoguer::Input::<String>::new()
+        .with_prompt(prompt)
+        .allow_empty(true)
+        .validate_with(|value: &String| if value.trim().is_empty() { Ok(()) } else { check(value.trim()) })
+        .interact_text()?;
+    Ok(Some(value.trim().to_owned()).filter(|v| !v.is_empty()))
+}
+
+fn select(prompt: &str, items: &'static [&'static str], default: Option<&str>) -> anyhow::Result<&'static str> {
+    let default = default.and_then(|d| items.iter().position(|i| *i == d)).unwrap_or(0);
+    let index = dialoguer::Select::new()
+        .with_prompt(prompt)
+        .items(items)
+        .default(default)
+        .interact()?;
+    Ok(items[index])
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',').map(|t| t.trim().to_owned()).filter(|t| !t.is_empty()).collect()
+}
+
+// Defaults worked out from the project, so most fields only need Enter
+#[derive(Default)]
+struct Guesses {
+    title: Option<String>,
+    category: Option<&'static str>,
+    language: Option<&'static str>,
+    tags: Option<String>,
+    author: Option<String>,
+    url: Option<String>,
+    template_id: Option<String>,
+}
+
+impl Guesses {
+    async fn for_project(dir: &Path) -> Self {
+        let url = origin_url(dir).await;
+        let language = detect_language(dir);
+        let template_id = first_template_id(dir);
+        Self {
+            title: dir.file_name().map(|n| n.to_string_lossy().into_owned()),
+            category: Some(if template_id.is_some() { "Template" } else { "Sample" }),
+            language,
+            tags: language.map(|l| l.split('/').next().unwrap_or(l).to_lowercase()),
+            author: url.as_deref().and_then(repo_owner),
+            url,
+            template_id,
+        }
+    }
+}
+
+fn detect_language(dir: &Path) -> Option<&'static str> {
+    let has = |file: &str| dir.join(file).exists();
+    if has("Cargo.toml") {
+        Some("Rust")
+    } else if has("package.json") {
+        Some("JS/TS")
+    } else if has("go.mod") {
+        Some("Go")
+    } else if has("requirements.txt") || has("pyproject.toml") {
+        Some("Python")
+    } else {
+        None
+    }
+}
+
+fn first_template_id(dir: &Path) -> Option<String> {
+    let mut ids = std::fs::read_dir(dir.join("templates")).ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().join("metadata").join("spin-template.toml").exists())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.into_iter().next()
+}
+
+async fn origin_url(dir: &Path) -> Option<String> {
+    let output = git::git_command()
+        .arg("-C")
+        .arg(dir)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .await
+        .ok()?;
+    output.status.success().then(|| https_url(String::from_utf8_lossy(&output.stdout).trim()))
+}
+
+// The Hub links to repositories, so SSH remotes become their web URLs
+fn https_url(remote: &str) -> String {
+    let url = match remote.strip_prefix("git@").and_then(|r| r.split_once(':')) {
+        Some((host, path)) => format!("https://{host}/{path}"),
+        None => remote.to_owned(),
+    };
+    url.strip_suffix(".git").map(|u| u.to_owned()).unwrap_or(url)
+}
+
+fn repo_owner(url: &str) -> Option<String> {
+    url::Url::parse(url).ok()?.path_segments()?.next().filter(|s| !s.is_empty()).map(|s| s.to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn remotes_become_web_urls() {
+        assert_eq!("https://github.com/fermyon/spin", https_url("git@github.com:fermyon/spin.git"));
+        assert_eq!("https://github.com/fermyon/spin", https_url("https://github.com/fermyon/spin.git"));
+        assert_eq!(Some("fermyon".to_owned()), repo_owner("https://github.com/fermyon/spin"));
+    }
+}
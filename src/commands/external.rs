@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+
+use crate::{cache, exit::{self, Failure}, hub_api, settings};
+
+// Subcommands the Hub CLI doesn't have are run as `spin-hub-NAME` from PATH,
+// like cargo's, so they can be added without changing the CLI
+const PREFIX: &str = "spin-hub-";
+
+pub async fn run_external(args: &[String]) -> anyhow::Result<()> {
+    let (name, args) = args.split_first().ok_or_else(|| anyhow!("No subcommand given"))?;
+    if name.contains(['/', '\\']) {
+        return Err(anyhow!("No such command '{name}'"));
+    }
+    let program = format!("{PREFIX}{name}");
+    let path = find_program(&program)
+        .ok_or_else(|| anyhow!("No such command '{name}'. Hub CLI extensions are run as `{program}`, but there is none on PATH. See `hub --help` for the commands there are."))?;
+
+    // So the extension can read the index without fetching it itself
+    if let Err(e) = hub_api::index().await {
+        tracing::debug!("Couldn't update the index for {program}: {e:#}");
+    }
+
+    let settings = settings::get();
+    let mut command = tokio::process::Command::new(&path);
+    command.args(args)
+        .env("SPIN_HUB_URL", settings.hub_url().as_str())
+        .env("SPIN_HUB_EXTENSION", name);
+    if let Ok(exe) = std::env::current_exe() {
+        command.env("SPIN_HUB", exe);
+    }
+    if let Some(index) = hub_api::index_path().filter(|p| p.exists()) {
+        command.env("SPIN_HUB_INDEX", index);
+    }
+    if let Some(dir) = cache::cache_dir() {
+        command.env("SPIN_HUB_CACHE_DIR", dir);
+    }
+    if let Some(profile) = &settings.profile {
+        command.env("SPIN_HUB_PROFILE", profile);
+    }
+
+    tracing::debug!(?path, ?args, "Running extension");
+    let status = command.status().await.with_context(|| format!("Failed to run {}", path.display()))?;
+    if status.success() {
+        return Ok(());
+    }
+    // Killed by a signal if there's no code
+    let code = status.code().and_then(|c| u8::try_from(c).ok()).unwrap_or(exit::FAILURE);
+    Err(Failure::External { program, code }.into())
+}
+
+fn find_program(program: &str) -> Option<PathBuf> {
+    let file_name = format!("{program}{}", std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.is_file())
+}
@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use clap::{Parser};
+use itertools::Itertools;
+
+use crate::{hub_api, output::{self, OutputFormat}, settings};
+
+// Shared by the commands that list the values of one field across the index,
+// so users can see what vocabulary to search with
+#[derive(clap::Args, Debug)]
+struct FacetArgs {
+    #[clap(long, value_enum, default_value_t = FacetOrder::Count, help = "How to order the values")]
+    sort: FacetOrder,
+
+    #[clap(long, value_name = "TEXT", help = "Only show values containing this text")]
+    filter: Option<String>,
+
+    #[clap(long, alias = "cat", help = "Only count entries in this category")]
+    category: Option<String>,
+
+    #[clap(long, alias = "lang", help = "Only count entries in this language")]
+    language: Option<String>,
+
+    #[clap(long, help = "Show at most this many values")]
+    limit: Option<usize>,
+
+    #[clap(short = 'o', long = "output", alias = "format", value_enum, env = "SPIN_HUB_OUTPUT")]
+    output: Option<OutputFormat>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+enum FacetOrder {
+    Count,
+    Name,
+}
+
+#[derive(serde::Serialize)]
+pub(super) struct FacetCount {
+    pub value: String,
+    pub count: usize,
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "List the tags used on the Hub, with how many entries have each")]
+pub struct TagsCommand {
+    #[clap(flatten)]
+    facet: FacetArgs,
+}
+
+impl TagsCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        self.facet.run("Tag", |e| e.tags()).await
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "List the categories of Hub entries, with how many entries are in each")]
+pub struct CategoriesCommand {
+    #[clap(flatten)]
+    facet: FacetArgs,
+}
+
+impl CategoriesCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        self.facet.run("Category", |e| vec![e.category().to_string()]).await
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "List the authors of Hub entries, with how many entries each has written")]
+pub struct AuthorsCommand {
+    #[clap(flatten)]
+    facet: FacetArgs,
+}
+
+impl AuthorsCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        self.facet.run("Author", |e| vec![e.author().to_owned()]).await
+    }
+}
+
+impl FacetArgs {
+    async fn run(&self, name: &str, values: impl Fn(&hub_api::IndexEntry) -> Vec<String>) -> anyhow::Result<()> {
+        let entries = hub_api::index().await?;
+        let entries = entries.iter().filter(|e| self.is_match(e));
+        let mut counts = self.filtered(count_values(entries, values));
+        if let Some(limit) = self.limit {
+            counts.truncate(limit);
+        }
+
+        match settings::get().output_format(self.output) {
+            OutputFormat::Json => output::print_json(&counts),
+            OutputFormat::Jsonl => output::print_json_lines(&counts),
+            format => {
+                if counts.is_empty() {
+                    output::note!("No matches");
+                    return Ok(());
+                }
+                let mut table = output::table(format);
+                table.set_header([name, "Entries"]);
+                for FacetCount { value, count } in counts {
+                    table.add_row(output::single_line_row(vec![value, count.to_string()]));
+                }
+                println!("{table}");
+                Ok(())
+            }
+        }
+    }
+
+    fn filtered(&self, counts: Vec<FacetCount>) -> Vec<FacetCount> {
+        let filter = self.filter.as_ref().map(|f| f.to_lowercase());
+        counts.into_iter()
+            .filter(|c| filter.as_ref().map_or(true, |f| c.value.to_lowercase().contains(f)))
+            .sorted_by(|c1, c2| match self.sort {
+                // Already in this order
+                FacetOrder::Count => std::cmp::Ordering::Equal,
+                FacetOrder::Name => c1.value.to_lowercase().cmp(&c2.value.to_lowercase()),
+            })
+            .collect()
+    }
+
+    fn is_match(&self, entry: &hub_api::IndexEntry) -> bool {
+        let category_match = self.category.as_ref().map_or(true, |c| entry.category() == hub_api::Category::parse(c));
+        let language_match = self.language.as_ref().map_or(true, |l| entry.language().is_match(l));
+        category_match && language_match
+    }
+}
+
+// An entry with the same value twice, such as a repeated tag, counts once.
+// Most used first.
+pub(super) fn count_values<'a>(entries: impl Iterator<Item = &'a hub_api::IndexEntry>, values: impl Fn(&hub_api::IndexEntry) -> Vec<String>) -> Vec<FacetCount> {
+    let mut counts = BTreeMap::<String, usize>::new();
+    for entry in entries {
+        for value in values(entry).into_iter().unique() {
+            *counts.entry(value).or_default() += 1;
+        }
+    }
+    counts.into_iter()
+        .map(|(value, count)| FacetCount { value, count })
+        .sorted_by(|c1, c2| c2.count.cmp(&c1.count).then_with(|| c1.value.cmp(&c2.value)))
+        .collect()
+}
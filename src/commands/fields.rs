@@ -0,0 +1,116 @@
+use crate::{hub_api, settings};
+
+// Lets listings of Hub entries print just the fields the user asks for, in
+// place of their usual columns
+#[derive(clap::Args, Clone, Debug, Default)]
+pub(super) struct FieldArgs {
+    #[clap(long, value_enum, value_delimiter = ',', value_name = "FIELD,...", help = "Show only these fields of each entry, in this order")]
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub(super) enum Field {
+    Id,
+    Title,
+    Category,
+    Language,
+    Trigger,
+    Author,
+    Tags,
+    Summary,
+    Url,
+    HubUrl,
+    TemplateId,
+    SpinVersion,
+    Updated,
+}
+
+impl Field {
+    fn header(&self) -> &'static str {
+        match self {
+            Self::Id => "ID",
+            Self::Title => "Name",
+            Self::Category => "Category",
+            Self::Language => "Language",
+            Self::Trigger => "Trigger",
+            Self::Author => "Author",
+            Self::Tags => "Tags",
+            Self::Summary => "Description",
+            Self::Url => "Repository",
+            Self::HubUrl => "Hub page",
+            Self::TemplateId => "Template ID",
+            Self::SpinVersion => "Spin version",
+            Self::Updated => "Last updated",
+        }
+    }
+
+    fn value(&self, entry: &hub_api::IndexEntry) -> String {
+        match self {
+            Self::Id => entry.id().to_owned(),
+            Self::Title => entry.listed_title(),
+            Self::Category => entry.category().to_string(),
+            Self::Language => entry.language().to_string(),
+            Self::Trigger => entry.trigger().unwrap_or_default(),
+            Self::Author => entry.author().to_owned(),
+            Self::Tags => entry.tags().join(", "),
+            Self::Summary => entry.short_summary(),
+            Self::Url => entry.repo_url().to_owned(),
+            Self::HubUrl => entry.hub_url(settings::get().hub_url()).map(|u| u.to_string()).unwrap_or_default(),
+            Self::TemplateId => entry.template_id().to_owned(),
+            Self::SpinVersion => entry.spin_version().unwrap_or_default().to_owned(),
+            Self::Updated => entry.last_updated().unwrap_or_default().to_owned(),
+        }
+    }
+
+    // The fields that search terms are matched against
+    pub fn is_searched(&self) -> bool {
+        matches!(self, Self::Title | Self::Tags | Self::Summary)
+    }
+}
+
+impl FieldArgs {
+    pub fn is_chosen(&self) -> bool {
+        !self.fields.is_empty()
+    }
+
+    // The command's usual columns, unless the user chose others
+    pub fn or_default(&self, default: &[Field]) -> Self {
+        if self.is_chosen() {
+            self.clone()
+        } else {
+            Self { fields: default.to_vec() }
+        }
+    }
+
+    pub fn header(&self) -> Vec<&'static str> {
+        self.fields.iter().map(|f| f.header()).collect()
+    }
+
+    pub fn row(&self, entry: &hub_api::IndexEntry) -> Vec<String> {
+        self.fields.iter().map(|f| f.value(entry)).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rows_have_the_chosen_fields_in_order() {
+        let entry: hub_api::IndexEntry = serde_json::from_value(serde_json::json!({
+            "title": "Rust HTTP",
+            "summary": "Handles HTTP requests",
+            "category": "Template",
+            "language": "Rust",
+            "author": "fermyon",
+            "tags": ["http", "rust"],
+            "repo_url": "https://github.com/fermyon/rust-http",
+            "template_id": "http-rust",
+            "path": "/hub/rust-http",
+        }))
+        .unwrap();
+        let fields = FieldArgs { fields: vec![Field::Url, Field::Title, Field::Tags] };
+        assert_eq!(vec!["Repository", "Name", "Tags"], fields.header());
+        assert_eq!(vec!["https://github.com/fermyon/rust-http", "Rust HTTP", "http, rust"], fields.row(&entry));
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use clap::{Parser};
+use itertools::Itertools;
+
+use crate::{history, output::{self, OutputFormat}, settings};
+
+#[derive(Parser, Debug)]
+#[clap(about = "List the applications and components you have created from templates, most recent first")]
+pub struct HistoryCommand {
+    #[clap(long, default_value_t = 20, help = "Show at most this many entries")]
+    limit: usize,
+
+    #[clap(short = 'o', long = "output", alias = "format", value_enum, env = "SPIN_HUB_OUTPUT")]
+    output: Option<OutputFormat>,
+}
+
+impl HistoryCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let invocations = history::invocations()?.into_iter().take(self.limit).collect_vec();
+
+        let format = settings::get().output_format(self.output);
+        if format == OutputFormat::Json {
+            return output::print_json(&invocations);
+        }
+        if format == OutputFormat::Jsonl {
+            return output::print_json_lines(&invocations);
+        }
+        if invocations.is_empty() {
+            output::note!("Nothing has been created from a template yet");
+            return Ok(());
+        }
+
+        let mut table = output::table(format);
+        table.set_header(["#", "When", "Command", "Template", "Name", "Values"]);
+        for (idx, invocation) in invocations.iter().enumerate() {
+            table.add_row(output::single_line_row(vec![
+                (idx + 1).to_string(),
+                format_age(invocation.age()),
+                invocation.kind.to_string(),
+                invocation.entry.clone().unwrap_or_else(|| format!("{} ({})", invocation.template_id, invocation.repo)),
+                invocation.name.clone(),
+                invocation.values.iter().map(|(k, v)| format!("{k}={v}")).join(", "),
+            ]));
+        }
+        println!("{table}");
+        println!("Use `hub new --again <#> <name>` to create another application the same way.");
+        Ok(())
+    }
+}
+
+pub(super) fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    match minutes {
+        0 => "just now".to_owned(),
+        1..=59 => format!("{minutes} min ago"),
+        60..=1439 => format!("{} h ago", minutes / 60),
+        1440..=2879 => "yesterday".to_owned(),
+        _ => format!("{} days ago", minutes / 1440),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ages_are_rounded_down_to_a_readable_unit() {
+        assert_eq!("just now", format_age(Duration::from_secs(59)));
+        assert_eq!("5 min ago", format_age(Duration::from_secs(5 * 60 + 30)));
+        assert_eq!("2 h ago", format_age(Duration::from_secs(2 * 3600 + 59 * 60)));
+        assert_eq!("3 days ago", format_age(Duration::from_secs(3 * 86400)));
+    }
+}
@@ -0,0 +1,128 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use itertools::Itertools;
+
+use crate::{git, output, style};
+use super::new::{AppOptions, TemplateRef};
+
+// Runs the steps asked for after the application was created. A failed step
+// doesn't stop the later ones, since they don't depend on each other, but the
+// command still fails so scripts notice.
+pub(super) async fn run_post_create(dir: &Path, template: &TemplateRef, app: &AppOptions) -> anyhow::Result<()> {
+    let mut failed = vec![];
+
+    // Before building, so the first commit is just what the template generated
+    if app.git {
+        output::note!();
+        output::note!("Initialising a Git repository...");
+        if let Err(e) = init_repo(dir, template).await {
+            style::warning!("failed to initialise a Git repository in {}: {e:#}", dir.display());
+            failed.push("git");
+        }
+    }
+
+    if app.build {
+        output::note!();
+        output::note!("Running spin build...");
+        if let Err(e) = build(dir).await {
+            style::warning!("{e:#}");
+            failed.push("build");
+        }
+    }
+
+    if let Some(editor) = &app.open {
+        if let Err(e) = open_editor(editor, dir).await {
+            style::warning!("failed to open {} in '{editor}': {e:#}", dir.display());
+            failed.push("open");
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("The application was created, but these steps failed: {}", failed.join(", ")))
+    }
+}
+
+async fn init_repo(dir: &Path, template: &TemplateRef) -> anyhow::Result<()> {
+    if dir.join(".git").exists() {
+        return Err(anyhow!("{} is already a Git repository", dir.display()));
+    }
+    add_gitignore(dir)?;
+    git::init_with_commit(dir, &format!("Create application from template {}", template.id)).await
+}
+
+async fn build(dir: &Path) -> anyhow::Result<()> {
+    let status = crate::spin::bin().arg("build").current_dir(dir).status().await?;
+    if !status.success() {
+        return Err(anyhow!("spin build failed in {} - see output for details", dir.display()));
+    }
+    Ok(())
+}
+
+// The editor can have arguments, e.g. `code -n`. It's run directly rather than
+// through a shell, so the path doesn't need quoting.
+async fn open_editor(editor: &str, dir: &Path) -> anyhow::Result<()> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or_else(|| anyhow!("No editor given"))?;
+    let status = tokio::process::Command::new(program)
+        .args(words)
+        .arg(dir)
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow!("the editor exited with {status}"));
+    }
+    Ok(())
+}
+
+// Adds the build output of the languages the application uses to any
+// .gitignore the template made
+fn add_gitignore(dir: &Path) -> anyhow::Result<()> {
+    let path = dir.join(".gitignore");
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    let patterns = ignore_patterns(&|file| dir.join(file).exists());
+    let missing = patterns.iter().filter(|p| !existing.lines().any(|l| l.trim() == **p)).collect_vec();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for pattern in missing {
+        content.push_str(pattern);
+        content.push('\n');
+    }
+    Ok(std::fs::write(&path, content)?)
+}
+
+fn ignore_patterns(exists: &dyn Fn(&str) -> bool) -> Vec<&'static str> {
+    let mut patterns = vec![".spin/"];
+    if exists("Cargo.toml") {
+        patterns.push("target/");
+    }
+    if exists("package.json") {
+        patterns.extend(["node_modules/", "dist/"]);
+    }
+    if exists("go.mod") {
+        patterns.push("main.wasm");
+    }
+    if exists("requirements.txt") || exists("pyproject.toml") {
+        patterns.extend(["__pycache__/", "venv/", "*.wasm"]);
+    }
+    patterns
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ignore_patterns_follow_the_languages_used() {
+        assert_eq!(vec![".spin/"], ignore_patterns(&|_| false));
+        assert_eq!(vec![".spin/", "target/"], ignore_patterns(&|f| f == "Cargo.toml"));
+        assert_eq!(vec![".spin/", "node_modules/", "dist/"], ignore_patterns(&|f| f == "package.json"));
+    }
+}
@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+
+use crate::{cache, hub_api, settings};
+
+// Bump when snapshots change in a way older versions can't read
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCommand {
+    Export(ExportCommand),
+    Import(ImportCommand),
+}
+
+impl IndexCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Export(cmd) => cmd.run().await,
+            Self::Import(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Snapshot {
+    schema_version: u32,
+    hub_url: String,
+    // Seconds since the Unix epoch
+    exported_at: u64,
+    entries: Vec<hub_api::IndexEntry>,
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Save the Hub index to a file, to import on a machine that can't reach the Hub")]
+pub struct ExportCommand {
+    file: PathBuf,
+}
+
+impl ExportCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let snapshot = Snapshot {
+            schema_version: SCHEMA_VERSION,
+            hub_url: settings::get().hub_url().to_string(),
+            exported_at: SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs(),
+            entries: hub_api::index().await?,
+        };
+        std::fs::write(&self.file, serde_json::to_vec_pretty(&snapshot)?)
+            .with_context(|| format!("Failed to write {}", self.file.display()))?;
+        println!("Exported {} entries to {}", snapshot.entries.len(), self.file.display());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Use a Hub index exported with `index export` in place of the cached index")]
+pub struct ImportCommand {
+    file: PathBuf,
+}
+
+impl ImportCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let text = std::fs::read_to_string(&self.file)
+            .with_context(|| format!("Failed to read {}", self.file.display()))?;
+
+        // Check the version before the rest, so a newer format gets a clear error
+        let version: serde_json::Value = serde_json::from_str(&text)
+            .with_context(|| format!("{} is not an index snapshot", self.file.display()))?;
+        match version["schema-version"].as_u64() {
+            Some(v) if v == u64::from(SCHEMA_VERSION) => (),
+            Some(v) => return Err(anyhow!("{} has schema version {v}, but this version of the Hub CLI reads version {SCHEMA_VERSION}. Export it with a matching version.", self.file.display())),
+            None => return Err(anyhow!("{} is not an index snapshot (it has no schema-version)", self.file.display())),
+        }
+        let snapshot: Snapshot = serde_json::from_str(&text)
+            .with_context(|| format!("{} is not a valid index snapshot", self.file.display()))?;
+
+        let hub_url = settings::get().hub_url();
+        let index_cache = cache::index_cache().ok_or_else(|| anyhow!("Couldn't determine a cache directory"))?;
+        // No validators, as the Hub didn't serve this index
+        index_cache.write(hub_url, &serde_json::to_vec(&snapshot.entries)?, &Default::default());
+        if index_cache.read(hub_url).is_none() {
+            return Err(anyhow!("Failed to write the index cache"));
+        }
+
+        println!("Imported {} entries from {} as the index for {hub_url}", snapshot.entries.len(), snapshot.hub_url);
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use anyhow::anyhow;
+use clap::{Parser};
+
+use crate::{auth::{self, Credentials}, github, hub_api, oci, settings};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Store credentials for a Hub that requires authentication")]
+pub struct LoginCommand {
+    #[clap(long, conflicts_with = "username", help = "A bearer token for the Hub [default: prompt for one]")]
+    token: Option<String>,
+
+    #[clap(long, help = "Use basic authentication with this user name. The password is prompted for")]
+    username: Option<String>,
+
+    #[clap(long, value_name = "HOST", conflicts_with = "github", help = "Log in to this OCI registry, e.g. ghcr.io, instead of the Hub")]
+    registry: Option<String>,
+
+    #[clap(long, conflicts_with = "username", help = "Store a GitHub token, which `hub entry submit` uses, instead of logging in to the Hub")]
+    github: bool,
+}
+
+impl LoginCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let registry_url = match &self.registry {
+            Some(registry) => Some(oci::registry_url(registry)?),
+            None => self.github.then(github::api_url),
+        };
+        let hub_url = registry_url.as_ref().unwrap_or(settings::get().hub_url());
+
+        let credentials = match (&self.token, &self.username) {
+            (Some(token), _) => Credentials::Bearer { token: token.clone() },
+            (None, Some(username)) => Credentials::Basic {
+                username: username.clone(),
+                password: dialoguer::Password::new().with_prompt(format!("Password for {username}")).interact()?,
+            },
+            (None, None) => Credentials::Bearer {
+                token: dialoguer::Password::new().with_prompt(format!("Token for {hub_url}")).interact()?,
+            },
+        };
+        auth::save(hub_url, &credentials)?;
+        if registry_url.is_some() {
+            println!("Saved credentials for {hub_url}");
+            return Ok(());
+        }
+
+        // Don't keep credentials the Hub won't accept
+        if let Err(e @ hub_api::HubError::Unauthorized { .. }) = hub_api::check_reachable(hub_url).await {
+            _ = auth::delete(hub_url);
+            return Err(anyhow!(e));
+        }
+
+        println!("Logged in to {hub_url}");
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Remove stored credentials for a Hub")]
+pub struct LogoutCommand {
+    #[clap(long, value_name = "HOST", conflicts_with = "github", help = "Log out of this OCI registry instead of the Hub")]
+    registry: Option<String>,
+
+    #[clap(long, help = "Remove the stored GitHub token instead")]
+    github: bool,
+}
+
+impl LogoutCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let registry_url = match &self.registry {
+            Some(registry) => Some(oci::registry_url(registry)?),
+            None => self.github.then(github::api_url),
+        };
+        let hub_url = registry_url.as_ref().unwrap_or(settings::get().hub_url());
+        if auth::delete(hub_url)? {
+            println!("Logged out of {hub_url}");
+        } else {
+            println!("Not logged in to {hub_url}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use clap::{Parser};
+
+use crate::{git, http, hub_api, progress::Progress, ranking, style};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Copy Hub entries into a directory that can be used offline with --hub-file")]
+pub struct MirrorCommand {
+    #[clap(name = "dir", help = "Directory to write the mirror to")]
+    dir: PathBuf,
+
+    #[clap(short = 't', help = "Only mirror entries matching this search term (can be repeated)")]
+    terms: Vec<String>,
+
+    #[clap(long, alias = "cat", help = "Only mirror entries in this category")]
+    category: Option<String>,
+}
+
+impl MirrorCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let mut entries = hub_api::index().await?.into_iter()
+            .filter(|e| self.is_match(e))
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return Err(anyhow!("No Hub entries match"));
+        }
+
+        std::fs::create_dir_all(self.dir.join("repos"))?;
+        let client = http::client()?;
+        let progress = Progress::start("Mirroring Hub entries");
+
+        // Many templates share a repository, so each is only bundled once
+        let mut bundles: HashMap<String, String> = HashMap::new();
+        let mut failures = 0;
+        for entry in &mut entries {
+            progress.set_message(format!("Mirroring {}", entry.title()));
+
+            let repo = entry.repo_url().to_owned();
+            let repo_path = match bundles.get(&repo) {
+                Some(path) => path.clone(),
+                None => {
+                    let path = format!("repos/{}.bundle", file_key(&repo));
+                    if let Err(e) = git::bundle(&repo, &self.dir.join(&path)).await {
+                        style::warning!("couldn't mirror {}: {e:#}", entry.title());
+                        failures += 1;
+                        continue;
+                    }
+                    bundles.insert(repo, path.clone());
+                    path
+                }
+            };
+
+            let artifact_path = match entry.artifact_source().map(|s| s.to_owned()) {
+                Some(url) => match mirror_artifact(&client, &url, &self.dir, entry.id()).await {
+                    Ok(path) => Some(path),
+                    Err(e) => {
+                        style::warning!("couldn't mirror the artifacts of {}: {e:#}", entry.title());
+                        failures += 1;
+                        Some(url)
+                    }
+                },
+                None => None,
+            };
+
+            entry.set_sources(repo_path, artifact_path);
+        }
+        progress.finish();
+
+        let index_path = self.dir.join("index.json");
+        std::fs::write(&index_path, serde_json::to_vec_pretty(&entries)?)?;
+
+        println!("Mirrored {} entries to {}", entries.len() - failures, self.dir.display());
+        println!("To use the mirror offline:");
+        println!("    hub --hub-file {} search", index_path.display());
+        Ok(())
+    }
+
+    fn is_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
+        let category_match = match &self.category {
+            None => true,
+            Some(cat) => index_entry.category() == hub_api::Category::parse(cat),
+        };
+        category_match && (self.terms.is_empty() || ranking::score(index_entry, &self.terms).is_match())
+    }
+}
+
+// Plugin manifests are mirrored with all their packages, with package URLs
+// made relative to the manifest
+async fn mirror_artifact(client: &reqwest::Client, url: &str, dir: &Path, id: &str) -> anyhow::Result<String> {
+    let relative_dir = format!("artifacts/{id}");
+    let artifact_dir = dir.join(&relative_dir);
+    std::fs::create_dir_all(&artifact_dir)?;
+
+    let file_name = file_name(url);
+    let mut bytes = http::download(client, url).await?;
+
+    if let Ok(mut manifest) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+        if let Some(packages) = manifest["packages"].as_array_mut() {
+            for package in packages {
+                let Some(package_url) = package["url"].as_str().map(|u| u.to_owned()) else {
+                    continue;
+                };
+                let package_file = file_name(&package_url);
+                std::fs::write(artifact_dir.join(&package_file), http::download(client, &package_url).await?)?;
+                package["url"] = package_file.into();
+            }
+            bytes = serde_json::to_vec_pretty(&manifest)?;
+        }
+    }
+
+    std::fs::write(artifact_dir.join(&file_name), bytes)?;
+    Ok(format!("{relative_dir}/{file_name}"))
+}
+
+fn file_name(url: &str) -> String {
+    url.rsplit('/').find(|s| !s.is_empty()).unwrap_or("artifact").to_owned()
+}
+
+fn file_key(repo: &str) -> String {
+    repo.trim_start_matches("https://")
+        .trim_end_matches(".git")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
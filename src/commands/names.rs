@@ -0,0 +1,102 @@
+use anyhow::{anyhow, Result};
+
+use super::portable;
+
+// Gets a valid application or component name (`kind`), from the command line
+// if given, otherwise by prompting
+pub(super) fn resolve_name(name: Option<&str>, kind: &str, no_prompt: bool) -> Result<String> {
+    let Some(name) = name else {
+        if no_prompt {
+            return Err(anyhow!("A {kind} name is required when using --no-prompt"));
+        }
+        return prompt_name(&format!("Enter a name for your new {kind}"));
+    };
+
+    let Err(reason) = validate(name) else {
+        return Ok(name.to_owned());
+    };
+    let suggestion = sanitize(name);
+    if suggestion.is_empty() {
+        return Err(anyhow!("'{name}' can't be used as a {kind} name: {reason}"));
+    }
+    if no_prompt {
+        return Err(anyhow!("'{name}' can't be used as a {kind} name: {reason}. Try '{suggestion}' instead."));
+    }
+
+    eprintln!("'{name}' can't be used as a {kind} name: {reason}");
+    let accepted = dialoguer::Confirm::new()
+        .with_prompt(format!("Use '{suggestion}' instead?"))
+        .default(true)
+        .interact_opt()?
+        .unwrap_or_default();
+    if accepted {
+        Ok(suggestion)
+    } else {
+        Err(anyhow!("A valid {kind} name is required"))
+    }
+}
+
+fn prompt_name(prompt: &str) -> Result<String> {
+    Ok(dialoguer::Input::<String>::new()
+        .with_prompt(prompt)
+        .validate_with(|name: &String| validate(name.trim()).map_err(|reason| match sanitize(name) {
+            suggestion if suggestion.is_empty() => reason,
+            suggestion => format!("{reason} (try '{suggestion}')"),
+        }))
+        .interact_text()?
+        .trim()
+        .to_string())
+}
+
+// Spin uses names as component IDs, which are lowercase words separated by
+// hyphens, and templates use them as directory names
+fn validate(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("a name is required".to_owned());
+    }
+    if !name.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return Err("names must start with a lowercase letter".to_owned());
+    }
+    if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        return Err("names may only contain lowercase letters, digits and hyphens".to_owned());
+    }
+    if name.ends_with('-') || name.contains("--") {
+        return Err("hyphens must separate words".to_owned());
+    }
+    if portable::is_reserved_name(name) {
+        return Err(format!("'{name}' is a reserved file name on Windows"));
+    }
+    Ok(())
+}
+
+fn sanitize(name: &str) -> String {
+    let words = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_lowercase())
+        .collect::<Vec<_>>();
+    let sanitized = words.join("-");
+    let sanitized = sanitized.trim_start_matches(|c: char| c.is_ascii_digit() || c == '-');
+    if portable::is_reserved_name(sanitized) {
+        format!("{sanitized}-app")
+    } else {
+        sanitized.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn invalid_names_are_sanitized_to_valid_ones() {
+        for name in ["My App", "my_app", "my--app", "2much fun!", "Con"] {
+            assert!(validate(name).is_err(), "{name} should be invalid");
+            assert!(validate(&sanitize(name)).is_ok(), "{name} should sanitize to a valid name");
+        }
+        assert_eq!("my-app", sanitize("My App"));
+        assert_eq!("much-fun", sanitize("2much fun!"));
+        assert_eq!("", sanitize("!!!"));
+        assert!(validate("hello-world2").is_ok());
+    }
+}
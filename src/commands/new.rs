@@ -1,119 +1,657 @@
 use clap::{Parser};
-use itertools::Itertools;
-use std::path::PathBuf;
-use crate::hub_api;
-use anyhow::Result;
+use spin_templates::{RunOptions, TemplateVariantInfo};
+use std::collections::{BTreeSet, HashMap};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use crate::{archive, git, history, hub_api::{self, Category}, installed, interrupt, oci, output, progress::Progress, provenance::Provenance, readme, style, trust};
+use super::{conflicts::{self, OnConflict}, deps, hooks, names, portable::{self, Newline}, prompts, render, selection::{resolve_entry, SelectionArgs}, summary, values};
+use anyhow::{Context, Result};
+use tracing::Instrument;
 
 #[derive(Parser, Debug)]
 #[clap(about = "Create an application from a template on the Hub")]
 pub struct NewCommand {
-    #[clap(short = 't')]
-    terms: Vec<String>,
+    #[clap(flatten)]
+    selection: SelectionArgs,
 
     #[clap(name = "name", help = "Name of the application to create from the template")]
     name: Option<String>,
+
+    #[clap(short = 'o', long = "output", value_name = "PATH", help = "Directory to create the application in, e.g. '.' for the current directory [default: the application name]")]
+    output: Option<PathBuf>,
+
+    #[clap(long = "on-conflict", value_enum, value_name = "STRATEGY", conflicts_with = "dry_run", help = "What to do with each file the template creates that the output directory already has [default: with --force, overwrite]")]
+    on_conflict: Option<OnConflict>,
+
+    #[clap(long, value_enum, conflicts_with = "dry_run", help = "Give the generated text files these line endings; auto uses this platform's [default: as the template has them]")]
+    newline: Option<Newline>,
+
+    #[clap(long = "print-tree", help = "Show the files of the new application as a tree")]
+    print_tree: bool,
+
+    #[clap(long, help = "Write a spin-hub.lock file pinning the template commit, so `hub restore` can create the same application again")]
+    lock: bool,
+
+    #[clap(long = "dry-run", help = "List the files the template would create, without creating anything")]
+    dry_run: bool,
+
+    #[clap(long = "show-content", requires = "dry_run", help = "With --dry-run, also print the content of each file")]
+    show_content: bool,
+
+    #[clap(long, requires = "dry_run", help = "With --dry-run, also show the template's README")]
+    readme: bool,
+
+    #[clap(long, conflicts_with = "dry_run", help = "Run `spin build` in the new application")]
+    build: bool,
+
+    #[clap(long, conflicts_with = "dry_run", help = "Make the new application a Git repository, with a first commit and a .gitignore for its languages")]
+    git: bool,
+
+    #[clap(long, value_name = "EDITOR", conflicts_with = "dry_run", help = "Open the new application in this editor, e.g. 'code'")]
+    open: Option<String>,
+
+    #[clap(long = "from-git", value_name = "URL", group = "template_source", requires = "template_id", conflicts_with = "terms", help = "Use a template from a Git repository instead of the Hub")]
+    from_git: Option<String>,
+
+    #[clap(long = "from-archive", value_name = "URL|PATH", group = "template_source", requires = "template_id", conflicts_with = "terms", help = "Use a template from a tar.gz or zip of a template repository instead of the Hub")]
+    from_archive: Option<String>,
+
+    #[clap(long = "from-path", value_name = "DIR", group = "template_source", requires = "template_id", conflicts_with = "terms", help = "Use a template from a local directory as it is, such as a checkout you are working on, instead of the Hub")]
+    from_path: Option<PathBuf>,
+
+    #[clap(long = "archive-sha256", value_name = "HEX", requires = "from_archive", help = "The SHA-256 checksum the --from-archive file must have")]
+    archive_sha256: Option<String>,
+
+    #[clap(long = "template-id", requires = "template_source", help = "The ID of the template in the --from-git, --from-archive or --from-path source")]
+    template_id: Option<String>,
+
+    #[clap(long = "entry-version", value_name = "VERSION", conflicts_with_all = ["template_source", "git_ref", "again"], help = "Use this published version of the Hub entry, as listed by `hub show --versions` [default: the current one]")]
+    entry_version: Option<String>,
+
+    #[clap(long, value_name = "N", num_args = 0..=1, default_missing_value = "1", conflicts_with_all = ["terms", "id", "from_git", "from_path", "from_archive", "template_id", "entry_version"], help = "Create the application the same way as entry N of `hub history` (default: the most recent), with the new name. Use --value to change values")]
+    again: Option<usize>,
+
+    #[clap(flatten)]
+    template_args: TemplateArgs,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct AppOptions {
+    pub name: Option<String>,
+    pub output: Option<PathBuf>,
+    pub on_conflict: Option<OnConflict>,
+    pub newline: Option<Newline>,
+    pub print_tree: bool,
+    pub lock: bool,
+    pub dry_run: bool,
+    pub show_content: bool,
+    pub readme: bool,
+    pub build: bool,
+    pub git: bool,
+    pub open: Option<String>,
+}
+
+pub(super) struct TemplateRef {
+    // The Hub entry, if the template came from the Hub
+    pub entry: Option<String>,
+    pub author: Option<String>,
+    pub repo: String,
+    pub id: String,
+    // The SHA-256 of the template archive, for templates from archives
+    pub digest: Option<String>,
+}
+
+impl TemplateRef {
+    pub fn from_entry(index_entry: &hub_api::IndexEntry) -> Self {
+        Self {
+            entry: Some(index_entry.id().to_owned()),
+            author: Some(index_entry.author().to_owned()),
+            repo: index_entry.repo_url().to_owned(),
+            id: index_entry.template_id().to_owned(),
+            digest: index_entry.artifact_digest().map(|d| d.to_owned()),
+        }
+    }
+}
+
+#[derive(clap::Args, Clone, Debug, Default)]
+pub(super) struct TemplateArgs {
+    #[clap(long = "no-prompt", alias = "yes", help = "Fail instead of prompting, and accept template defaults")]
+    pub no_prompt: bool,
+
+    #[clap(long = "accept-defaults", help = "Use the template's default for every value not set with --value or --values-file, without asking")]
+    pub accept_defaults: bool,
+
+    #[clap(long = "value", value_name = "KEY=VALUE", value_parser = values::parse_value, help = "Set a template value, e.g. --value project-description=\"My app\" (can be repeated)")]
+    pub values: Vec<(String, String)>,
+
+    #[clap(long = "values-file", help = "A TOML or JSON file of template values. Values set with --value take precedence")]
+    pub values_file: Option<PathBuf>,
+
+    #[clap(long = "values-from-env", value_name = "PREFIX", help = "Set template values from environment variables starting with PREFIX, e.g. with HUB_VALUE_, HUB_VALUE_PROJECT_DESCRIPTION sets project-description. These take precedence over --values-file, and --value over them")]
+    pub values_from_env: Option<String>,
+
+    #[clap(long = "no-git-identity", help = "When prompting, don't offer your Git user.name and user.email as the defaults for template values such as author and email")]
+    pub no_git_identity: bool,
+
+    #[clap(long = "git-ref", alias = "branch", value_name = "REF", help = "Branch, tag or commit of the template repository to use, or the tag or digest of an OCI template [default: the default branch]")]
+    pub git_ref: Option<String>,
+
+    #[clap(long, help = "Use the template even if it may not work with your version of Spin, or generate into a non-empty directory")]
+    pub force: bool,
+
+    #[clap(long, help = "Keep the template installed in your Spin templates, so `spin new` can use it too")]
+    pub keep: bool,
+
+    #[clap(long = "install-deps", help = "Install any Spin plugins the template needs that aren't installed, without asking")]
+    pub install_deps: bool,
+}
+
+impl TemplateArgs {
+    pub fn values(&self) -> Result<HashMap<String, String>> {
+        let mut values = match &self.values_file {
+            Some(path) => values::load_values_file(path)?,
+            None => HashMap::new(),
+        };
+        if let Some(prefix) = &self.values_from_env {
+            // Skipping any that aren't Unicode, where std::env::vars() would panic
+            let vars = std::env::vars_os().filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+            values.extend(values::values_from_env(prefix, vars)?);
+        }
+        values.extend(self.values.iter().cloned());
+        Ok(values)
+    }
 }
 
 impl NewCommand {
     pub async fn run(&self) -> Result<()> {
-        let Some(index_entry) = self.resolve_selection().await? else {
-            return Ok(());
+        let app = AppOptions {
+            name: self.name.clone(),
+            output: self.output.clone(),
+            on_conflict: self.on_conflict,
+            newline: self.newline,
+            print_tree: self.print_tree,
+            lock: self.lock,
+            dry_run: self.dry_run,
+            show_content: self.show_content,
+            readme: self.readme,
+            build: self.build,
+            git: self.git,
+            open: self.open.clone(),
         };
 
-        println!("Template {} by {}", index_entry.title(), index_entry.author());
-        println!("{}", index_entry.summary());
+        if let Some(number) = self.again {
+            return self.run_again(number, &app).await;
+        }
+
+        let source = match (&self.from_git, &self.from_path) {
+            (Some(repo), _) => Some(repo.clone()),
+            (None, Some(dir)) => Some(local_dir_source(dir)?),
+            (None, None) => self.from_archive_url()?,
+        };
+        if let (Some(repo), Some(id)) = (source, &self.template_id) {
+            let template = TemplateRef {
+                entry: None,
+                author: None,
+                repo,
+                id: id.clone(),
+                digest: self.archive_sha256.clone(),
+            };
+            return new_from_template(&template, &app, &self.template_args).await;
+        }
 
-        let app_name = if let Some(ref name) = self.name {
-            name.clone()
+        let selection = if self.is_wizard() {
+            self.prompt_selection()?
         } else {
-            dialoguer::Input::<String>::new()
-                .with_prompt("Enter a name for your new application")
-                .interact_text()?
-                .trim()
-                .to_string()
+            self.selection.clone()
         };
-    
-        let (repo, id) = get_repo_and_id(&index_entry)?;
-        self.run_template(repo, id, app_name).await
-    }
 
-    async fn run_template(&self, repo: String, id: String, app_name: String) -> Result<()> {
-        use spin_templates::*;
+        let (index_entry, ()) = tokio::join!(
+            resolve_entry(Category::Template, &selection, self.template_args.no_prompt),
+            crate::spin::preload_version(),
+        );
+        let Some(mut index_entry) = index_entry? else {
+            return Ok(());
+        };
+        if let Some(version) = &self.entry_version {
+            index_entry = index_entry.at_version(version)?;
+        }
 
-        let tempdir = tempfile::tempdir().unwrap();
-        let manager = spin_templates::TemplateManager::in_dir(tempdir.path());
+        new_from_entry(&index_entry, &app, &self.template_args).await
+    }
 
-        let source = TemplateSource::try_from_git(&repo, &None, &crate::spin::version())?;
-        let options = InstallOptions::default();
-        manager.install(&source, &options, &DiscardingProgressReporter).await?;
-    
-        let template = match manager.get(&id)? {
-            Some(template) => template,
-            None => return Err(anyhow::anyhow!("Template not found in the repository.")),
+    async fn run_again(&self, number: usize, app: &AppOptions) -> Result<()> {
+        let previous = history::get(number)?;
+        if previous.kind != history::Kind::New {
+            return Err(anyhow::anyhow!("History entry {number} added a component rather than creating an application, so it can't be used with `new`"));
+        }
+        let template = TemplateRef {
+            entry: previous.entry,
+            author: previous.author,
+            repo: previous.repo,
+            id: previous.template_id,
+            digest: None,
         };
-            
-        let options = RunOptions {
-            variant: TemplateVariantInfo::NewApplication,
-            name: app_name.clone(),
-            output_path: PathBuf::from(&app_name),
-            values: Default::default(),
-            accept_defaults: false,
+        output::note!("Using template {} from {}", template.id, template.repo);
+
+        // Values given now take precedence over the earlier ones
+        let mut args = self.template_args.clone();
+        args.values = previous.values.into_iter().chain(args.values).collect();
+        args.git_ref = args.git_ref.or(previous.git_ref);
+        new_from_template(&template, app, &args).await
+    }
+
+    // Paths are made absolute, so history and lockfiles still work from elsewhere
+    fn from_archive_url(&self) -> Result<Option<String>> {
+        let Some(source) = &self.from_archive else {
+            return Ok(None);
         };
+        if url::Url::parse(source).is_ok() {
+            return Ok(Some(source.clone()));
+        }
+        let path = std::fs::canonicalize(source).with_context(|| format!("Can't find archive {source}"))?;
+        let url = url::Url::from_file_path(&path).map_err(|_| anyhow::anyhow!("Can't make a URL for {}", path.display()))?;
+        Ok(Some(url.to_string()))
+    }
+
+    // With nothing to go on, guide the user through choosing a template, if
+    // there's someone to answer
+    fn is_wizard(&self) -> bool {
+        self.name.is_none() &&
+            !self.selection.is_given() &&
+            !self.template_args.no_prompt &&
+            std::io::stdin().is_terminal()
+    }
 
-        template.run(options).interactive().await
+    fn prompt_selection(&self) -> Result<SelectionArgs> {
+        println!("Let's create a new Spin application from a Hub template.");
+        let terms: String = dialoguer::Input::new()
+            .with_prompt("What are you building? Enter search terms such as 'http rust', or leave empty to see all templates")
+            .allow_empty(true)
+            .interact_text()?;
+        Ok(SelectionArgs {
+            terms: terms.split_whitespace().map(|t| t.to_owned()).collect(),
+            ..self.selection.clone()
+        })
     }
+}
+
+pub(super) async fn new_from_entry(index_entry: &hub_api::IndexEntry, app: &AppOptions, args: &TemplateArgs) -> Result<()> {
+    output::note!("Template {} by {}", index_entry.title(), index_entry.author());
+    output::note!("{}", index_entry.summary());
 
-    async fn resolve_selection(&self) -> Result<Option<hub_api::IndexEntry>, dialoguer::Error> {
-        let entries = hub_api::index().await.unwrap();
-        let matches = entries.iter().filter(|e| self.is_match(e)).sorted_by_key(|e| e.title()).collect_vec();
+    warn_if_deprecated(index_entry);
+    check_compatibility(index_entry, args)?;
+
+    // A ref given on the command line wins over the one the Hub publishes
+    let args = TemplateArgs {
+        git_ref: args.git_ref.clone().or(index_entry.git_ref().map(|r| r.to_owned())),
+        ..args.clone()
+    };
+    new_from_template(&TemplateRef::from_entry(index_entry), app, &args).await
+}
 
-        match matches.len() {
-            0 => {
-                println!("No templates matches your search terms");
-                return Ok(None);
+pub(super) async fn new_from_template(template: &TemplateRef, app: &AppOptions, args: &TemplateArgs) -> Result<()> {
+    let app_name = names::resolve_name(app.name.as_deref(), "application", args.no_prompt)?;
+    if app.dry_run {
+        return preview(template, &app_name, app, args).await;
+    }
+
+    // Install from the exact commit that goes in the lockfile, in case the branch moves meanwhile
+    let git_ref = args.git_ref.clone();
+    let pinned;
+    let (args, commit) = if app.lock {
+        let commit = pinned_commit(template, git_ref.as_deref()).await?;
+        pinned = TemplateArgs { git_ref: Some(commit.clone()), ..args.clone() };
+        (&pinned, Some(commit))
+    } else {
+        (args, None)
+    };
+
+    let output_path = app.output.clone().unwrap_or_else(|| PathBuf::from(&app_name));
+    check_output_path(&output_path, app, args)?;
+    // Only for the filesystem, as the user's own path reads better in messages
+    let long_output_path = portable::long_path(&output_path)?;
+
+    // Generate next to the output path, so nothing is left half-created if the
+    // template fails, and moving the result into place is a rename
+    let parent = long_output_path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent)?;
+    let staging = tempfile::Builder::new().prefix(".spin-hub-").tempdir_in(parent)?;
+    let _cleanup = interrupt::remove_on_interrupt(staging.path());
+    let generated_path = staging.path().join("app");
+
+    let options = RunOptions {
+        variant: TemplateVariantInfo::NewApplication,
+        name: app_name.clone(),
+        output_path: generated_path.clone(),
+        values: args.values()?,
+        accept_defaults: args.no_prompt || args.accept_defaults,
+    };
+    // Including any answers to our prompts, so the lockfile gives the same application again
+    let values = run_template(template, options, args).await?;
+
+    // List what the template generated before it's mixed in with existing files
+    let files = summary::list_files(&generated_path)?;
+    portable::check_names(&files)?;
+    if let Some(newline) = app.newline {
+        portable::normalize_newlines(&generated_path, &files, newline)?;
+    }
+    let provenance = Provenance {
+        entry: template.entry.clone(),
+        repo: template.repo.clone(),
+        template_id: template.id.clone(),
+        git_ref,
+        commit,
+        name: app_name.clone(),
+        values: values.into_iter().collect(),
+    };
+    provenance.write(&generated_path)?;
+    if app.lock {
+        provenance.write_lock(&generated_path)?;
+    }
+    // Decided before anything is moved, so aborting leaves the directory as it was
+    let on_conflict = app.on_conflict.unwrap_or(OnConflict::Overwrite);
+    let resolution = conflicts::resolve(conflicts::find_conflicts(&generated_path, &long_output_path)?, on_conflict, &output_path, args.no_prompt)?;
+    move_into(&generated_path, &long_output_path, &resolution.skipped)
+        .with_context(|| format!("Failed to move the generated application to {}", output_path.display()))?;
+    conflicts::print_report(&resolution);
+
+    output::print_outcome(
+        &format!("Created {app_name} in {} ({} files)", output_path.display(), files.len()),
+        &output_path.display().to_string(),
+    );
+    if app.print_tree {
+        summary::print_tree(&output_path.display().to_string(), &files);
+    }
+    deps::check_required_plugins(template, args).await;
+    summary::print_next_steps(&output_path);
+
+    record_history(history::Kind::New, template, provenance.git_ref, &app_name, provenance.values);
+    hooks::run_post_create(&output_path, template, app).await
+}
+
+// The exact commit of a Git template, the digest of an OCI one, or the checksum
+// of an archive, so the same template can be used again
+pub(super) async fn pinned_commit(template: &TemplateRef, git_ref: Option<&str>) -> Result<String> {
+    if oci::is_oci(&template.repo) {
+        oci::resolve_digest(&template.repo, git_ref).await
+    } else if archive::is_archive(&template.repo) {
+        template.digest.clone().or(git_ref.map(|r| r.to_owned()))
+            .ok_or_else(|| anyhow::anyhow!("--lock needs the checksum of the template archive. Give it with --archive-sha256"))
+    } else {
+        git::resolve_ref(&template.repo, git_ref).await
+    }
+}
+
+pub(super) fn record_history(kind: history::Kind, template: &TemplateRef, git_ref: Option<String>, name: &str, values: std::collections::BTreeMap<String, String>) {
+    history::record(history::Invocation {
+        kind,
+        time: history::now(),
+        entry: template.entry.clone(),
+        author: template.author.clone(),
+        repo: template.repo.clone(),
+        template_id: template.id.clone(),
+        git_ref,
+        name: name.to_owned(),
+        values,
+    });
+}
+
+async fn preview(template: &TemplateRef, app_name: &str, app: &AppOptions, args: &TemplateArgs) -> Result<()> {
+    let rendered = render::render(template, app_name, args.values()?, args.git_ref.clone()).await?;
+    let output_path = app.output.clone().unwrap_or_else(|| PathBuf::from(app_name));
+
+    println!();
+    println!("Would create {app_name} in {} ({} files)", output_path.display(), rendered.files.len());
+    if app.print_tree {
+        summary::print_tree(&output_path.display().to_string(), &rendered.files);
+    } else {
+        for file in &rendered.files {
+            println!("    {}", file.display());
+        }
+    }
+
+    if app.show_content {
+        for file in &rendered.files {
+            let content = std::fs::read(rendered.path().join(file))?;
+            println!();
+            println!("==> {} <==", file.display());
+            match String::from_utf8(content) {
+                Ok(text) => print!("{text}"),
+                Err(e) => println!("(binary file, {} bytes)", e.as_bytes().len()),
             }
-            1 => {
-                let index_entry = matches[0].clone();
-                return Ok(Some(index_entry))
-            },
-            _ => {
-                dialoguer::Select::new()
-                    .with_prompt("Select a template:")
-                    .items(&matches.iter().map(|entry| format!("{} - {}", entry.title(), entry.summary())).collect_vec())
-                    .interact_opt()?
-                    .map(|idx| Ok(matches[idx].clone()))
-                    .transpose()
+        }
+    }
+
+    if app.readme {
+        println!();
+        readme::print(&template.repo, args.git_ref.as_deref()).await?;
+    }
+    Ok(())
+}
+
+fn check_output_path(path: &Path, app: &AppOptions, args: &TemplateArgs) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    if !path.is_dir() {
+        return Err(anyhow::anyhow!("{} already exists and is not a directory", path.display()));
+    }
+    if path.read_dir()?.next().is_none() || app.on_conflict.is_some() {
+        return Ok(());
+    }
+    if args.force {
+        style::warning!("{} is not empty. Files from the template will replace existing files because of --force.", path.display());
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("{} is not empty. Use a different --output directory, --on-conflict to choose what happens to files already there, or --force to generate into it anyway.", path.display()))
+    }
+}
+
+// Merges `from` into `to`, replacing what's in both except the paths, relative
+// to `from`, in `skipped`
+fn move_into(from: &Path, to: &Path, skipped: &BTreeSet<PathBuf>) -> Result<()> {
+    if !to.exists() {
+        std::fs::rename(from, to)?;
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if skipped.contains(Path::new(&name)) {
+            continue;
+        }
+        let dest = to.join(&name);
+        if entry.file_type()?.is_dir() && dest.is_dir() {
+            let skipped = skipped.iter().filter_map(|p| p.strip_prefix(&name).ok()).map(|p| p.to_owned()).collect();
+            move_into(&entry.path(), &dest, &skipped)?;
+        } else {
+            if dest.is_dir() {
+                std::fs::remove_dir_all(&dest)?;
+            } else if dest.exists() {
+                std::fs::remove_file(&dest)?;
             }
+            std::fs::rename(entry.path(), &dest)?;
         }
     }
+    Ok(())
+}
+
+// Fetches the template repository into `scratch` (or the cache) and installs
+// its templates into the throwaway store `manager`. Kept templates are then
+// installed into the user's Spin store as well, from what was fetched, and
+// recorded. Returns where the templates were fetched to.
+pub(super) async fn install_template(manager: &spin_templates::TemplateManager, template: &TemplateRef, args: &TemplateArgs, scratch: &Path) -> Result<PathBuf> {
+    use spin_templates::*;
+
+    let TemplateRef { entry, repo, id, author, digest } = template;
+    tracing::info!(repo, id, git_ref = ?args.git_ref, keep = args.keep, "Installing template");
+    let local_dir = local_template_dir(repo);
+    // A local directory is the user's own, so there's no one else to trust
+    if local_dir.is_none() {
+        trust::check(&trust::Publisher { author: author.as_deref(), repo_url: repo }, args.no_prompt)?;
+    }
+
+    let progress = Progress::start("");
+    progress.set_step(1, 2, format!("Fetching template from {repo}"));
+    let (checkout_dir, git_ref, commit) = if let Some(dir) = local_dir {
+        (dir, None, None)
+    } else if oci::is_oci(repo) {
+        // For OCI templates the Git ref is a tag or digest
+        let dir = scratch.join("source");
+        let digest = oci::pull(repo, args.git_ref.as_deref(), &dir).await?;
+        (dir, args.git_ref.clone(), Some(digest))
+    } else if archive::is_archive(repo) {
+        // A pinned archive template's Git ref is its checksum
+        let expected = digest.as_deref().or(args.git_ref.as_deref());
+        let (dir, sha256) = archive::fetch(repo, expected, &scratch.join("source")).await?;
+        (dir, None, Some(sha256))
+    } else {
+        let git_ref = match &args.git_ref {
+            Some(git_ref) => Some(git_ref.clone()),
+            None => match crate::spin::templates_branch() {
+                Some(branch) if git::has_branch(repo, &branch).await => Some(branch),
+                _ => None,
+            },
+        };
+        let checkout_dir = git::cached_clone(repo, git_ref.as_deref(), &scratch.join("source"), &progress).await?;
+        let commit = if args.keep { git::head_commit(&checkout_dir).await.ok() } else { None };
+        (checkout_dir, git_ref, commit)
+    };
+    let source = TemplateSource::File(checkout_dir.clone());
+    progress.set_step(2, 2, format!("Installing template {id}"));
+    manager.install(&source, &InstallOptions::default(), &progress).instrument(tracing::info_span!("install template")).await?;
 
-    fn is_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
-        self.is_terms_match(index_entry) &&
-            self.is_category_match(index_entry)
+    if args.keep {
+        // Everything that can fail or take long is done by now, so only this
+        // local copy is left, and Ctrl-C waits for it rather than leave a
+        // half-installed template in the user's store. Like `spin templates
+        // install --update`, so a kept template is the version we asked for.
+        let _deferral = interrupt::defer();
+        let install_options = InstallOptions::default().update(true);
+        TemplateManager::try_default()?.install(&source, &install_options, &progress)
+            .instrument(tracing::info_span!("keep template")).await?;
+        // The branch actually used and its commit, so `hub outdated` can tell
+        // when the template has changed
+        installed::record_template(installed::InstalledTemplate {
+            id: id.clone(),
+            repo: repo.clone(),
+            git_ref,
+            entry: entry.clone(),
+            commit,
+        })?;
     }
+    progress.finish();
+    Ok(checkout_dir)
+}
 
-    fn is_terms_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
-        let tags = index_entry.tags();
-        let title = index_entry.title_words();
-        self.terms.iter()
-            .map(|t| t.to_lowercase())
-            .all(|t| tags.contains(&t) || title.contains(&t))
+// Templates from --from-path are recorded by their absolute path, so history
+// and lockfiles still work from elsewhere
+fn local_dir_source(dir: &Path) -> Result<String> {
+    let path = std::fs::canonicalize(dir).with_context(|| format!("Can't find template directory {}", dir.display()))?;
+    if !path.is_dir() {
+        return Err(anyhow::anyhow!("{} is not a directory", dir.display()));
     }
+    Ok(path.display().to_string())
+}
+
+fn local_template_dir(repo: &str) -> Option<PathBuf> {
+    let path = Path::new(repo);
+    (path.is_absolute() && path.is_dir()).then(|| path.to_owned())
+}
 
-    fn is_category_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
-        index_entry.category() == hub_api::Category::Template
+// Not an error, since an old template may be exactly what the user wants
+pub(super) fn warn_if_deprecated(index_entry: &hub_api::IndexEntry) {
+    let Some(deprecation) = index_entry.deprecation() else {
+        return;
+    };
+    let mut message = format!("{} is deprecated", index_entry.title());
+    if let Some(reason) = deprecation.message() {
+        message.push_str(&format!(": {}", reason.trim_end_matches('.')));
+    }
+    message.push('.');
+    if let Some(end_of_life) = deprecation.end_of_life() {
+        message.push_str(&format!(" It reaches end of life on {end_of_life}."));
     }
+    if let Some(replacement) = deprecation.replacement() {
+        message.push_str(&format!(" Use {replacement} instead (`--id {replacement}`)."));
+    }
+    style::warning!("{message}");
 }
 
-fn get_repo_and_id(index_entry: &hub_api::IndexEntry) -> Result<(String, String)> {
-    let repo_url = index_entry.repo_url();
-    let template_id = index_entry.template_id(); 
+pub(super) fn check_compatibility(index_entry: &hub_api::IndexEntry, args: &TemplateArgs) -> Result<()> {
+    let spin_version = crate::spin::version();
+    if index_entry.is_compatible_with(&spin_version) != Some(false) {
+        return Ok(());
+    }
 
-    Ok((repo_url.to_string(), template_id.to_string()))
+    let message = format!(
+        "{} requires Spin {}, but this is Spin {spin_version}",
+        index_entry.title(),
+        index_entry.spin_version().unwrap_or_default()
+    );
+    if args.force {
+        style::warning!("{message}. Continuing because of --force.");
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("{message}. Upgrade Spin, or use --force to try the template anyway."))
+    }
 }
 
-struct DiscardingProgressReporter;
+// Returns the values the template was run with: those given and those the user
+// answered here. Values Spin asks for itself, when we can't read the manifest,
+// aren't known.
+pub(super) async fn run_template(template: &TemplateRef, mut options: RunOptions, args: &TemplateArgs) -> Result<HashMap<String, String>> {
+    use spin_templates::*;
+
+    let tempdir = tempfile::tempdir()?;
+    let _cleanup = interrupt::remove_on_interrupt(tempdir.path());
+    // Templates are used from a throwaway store, so using a Hub template doesn't
+    // change what `spin new` offers unless the user wants to keep it
+    let manager = TemplateManager::in_dir(tempdir.path().join("templates"));
+    let source_dir = install_template(&manager, template, args, tempdir.path()).await
+        .with_context(|| format!("Failed to get template {} from {}", template.id, template.repo))?;
+
+    let id = &template.id;
+    let template = match manager.get(id)? {
+        Some(template) => template,
+        None => return Err(anyhow::anyhow!("Template {id} not found in the repository.")),
+    };
+
+    if !template.supports_variant(&options.variant) {
+        return Err(anyhow::anyhow!("Template {} does not support this operation", id));
+    }
+
+    // Spin's own prompts show only the question, so ask here when we can read the
+    // manifest. The user's Git identity is only ever a default to accept or change,
+    // so runs that don't ask come out the same on every machine.
+    let prompting = !args.no_prompt && !options.accept_defaults && std::io::stdin().is_terminal();
+    if prompting {
+        let adding_component = matches!(options.variant, TemplateVariantInfo::AddComponent { .. });
+        if let Some(mut parameters) = prompts::template_parameters(&source_dir, id, adding_component) {
+            if !args.no_git_identity {
+                let identity = prompts::git_identity_values(&parameters).await;
+                for parameter in &mut parameters {
+                    if let Some(value) = identity.get(&parameter.id) {
+                        parameter.default = Some(value.clone());
+                    }
+                }
+            }
+            options.values = prompts::prompt_values(&parameters, options.values)?;
+        }
+    }
 
-impl spin_templates::ProgressReporter for DiscardingProgressReporter {
-    fn report(&self, _message: impl AsRef<str>) {}
+    let values = options.values.clone();
+    let generate = async {
+        if args.no_prompt {
+            // Nothing is asked, so there's no prompt for the spinner to draw over
+            let _progress = Progress::start(format!("Generating from template {id}"));
+            template.run(options).silent().await
+        } else {
+            template.run(options).interactive().await
+        }
+    };
+    generate.instrument(tracing::info_span!("generate")).await?;
+    Ok(values)
 }
+
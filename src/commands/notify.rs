@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use itertools::Itertools;
+
+use crate::{cache, hub_api, pager, settings, stars, style};
+use super::outdated::{find_outdated, is_newer};
+use super::self_update::{latest_release, RELEASE_REPO, VERSION};
+
+// How long to hold up exiting for a check that is still running
+const FINISH_WAIT: Duration = Duration::from_millis(500);
+
+// What the last check found, kept until it has been shown
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+struct State {
+    checked_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notice: Option<String>,
+    // When each starred entry was last updated, to notice changes
+    #[serde(default)]
+    starred: BTreeMap<String, String>,
+}
+
+// Looks for updates while a command runs, at most once per `update-check-hours`,
+// and tells the user about them after the command's own output
+pub struct UpdateNotice {
+    check: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl UpdateNotice {
+    pub fn start() -> Self {
+        let Some(interval) = interval() else {
+            return Self { check: None };
+        };
+        let state = load();
+        let due = now().saturating_sub(state.checked_at) >= interval.as_secs();
+        let check = due.then(|| tokio::spawn(async move {
+            if let Err(e) = check(state).await {
+                tracing::debug!("Update check failed: {e:#}");
+            }
+        }));
+        Self { check }
+    }
+
+    pub async fn finish(self) {
+        if interval().is_none() {
+            return;
+        }
+        if let Some(check) = self.check {
+            // A check that doesn't finish in time saves nothing, so the next command checks again
+            _ = tokio::time::timeout(FINISH_WAIT, check).await;
+        }
+        let mut state = load();
+        if let Some(notice) = state.notice.take() {
+            eprintln!();
+            eprintln!("{} {notice}", style::attention("Update:"));
+            _ = save(&state);
+        }
+    }
+}
+
+// Nobody would read the notice if output is for a script
+fn interval() -> Option<Duration> {
+    let settings = settings::get();
+    if settings.quiet || settings.porcelain || pager::is_child() || !std::io::stderr().is_terminal() {
+        return None;
+    }
+    settings.update_check
+}
+
+async fn check(mut state: State) -> anyhow::Result<()> {
+    let mut notices = vec![];
+    match latest_release(RELEASE_REPO).await {
+        Ok(release) if is_newer(&release.version, VERSION) => {
+            notices.push(format!("hub {} is available (run `hub self update`)", release.version));
+        }
+        Ok(_) => (),
+        Err(e) => tracing::debug!("Couldn't find the latest release: {e:#}"),
+    }
+
+    // Only what is already cached, so the check never fetches the index
+    if let Some(entries) = hub_api::cached_index() {
+        match find_outdated(&entries).await {
+            Ok((outdated, _)) if !outdated.is_empty() => {
+                notices.push(format!("{} installed from the Hub can be updated (run `hub outdated`)", count(outdated.len(), "item")));
+            }
+            Ok(_) => (),
+            Err(e) => tracing::debug!("Couldn't check for outdated templates and plugins: {e:#}"),
+        }
+
+        let starred = stars::starred()?;
+        let updated = entries.iter()
+            .filter(|e| starred.iter().any(|id| id == e.id()))
+            .filter_map(|e| Some((e.id().to_owned(), e.last_updated()?.to_owned())))
+            .collect::<BTreeMap<_, _>>();
+        let changed = updated.iter()
+            .filter(|(id, last_updated)| state.starred.get(*id).is_some_and(|previous| previous != *last_updated))
+            .map(|(id, _)| id)
+            .collect_vec();
+        if !changed.is_empty() {
+            notices.push(format!("{} updated: {}", count(changed.len(), "starred entry"), changed.iter().join(", ")));
+        }
+        state.starred = updated;
+    }
+
+    if !notices.is_empty() {
+        state.notice = Some(notices.join("; "));
+    }
+    // Only now, with what was found, so a check that was cut short is done again.
+    // One that failed isn't, as it would most likely fail again.
+    state.checked_at = now();
+    save(&state)
+}
+
+fn count(n: usize, noun: &str) -> String {
+    match (n, noun.strip_suffix('y')) {
+        (1, _) => format!("1 {noun}"),
+        (n, Some(stem)) => format!("{n} {stem}ies"),
+        (n, None) => format!("{n} {noun}s"),
+    }
+}
+
+fn state_path() -> Option<PathBuf> {
+    cache::cache_dir().map(|dir| dir.join("update-check.json"))
+}
+
+// A missing or unreadable file just means checking again
+fn load() -> State {
+    state_path()
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save(state: &State) -> anyhow::Result<()> {
+    let Some(path) = state_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_vec(state)?)?;
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
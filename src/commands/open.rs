@@ -0,0 +1,77 @@
+use clap::{Parser};
+
+use crate::settings;
+use super::selection::{resolve_any_entry, SelectionArgs};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Open the Hub page or repository of an entry in your browser")]
+pub struct OpenCommand {
+    #[clap(flatten)]
+    selection: SelectionArgs,
+
+    #[clap(name = "id-or-terms", help = "The ID of the entry, or search terms identifying it")]
+    words: Vec<String>,
+
+    #[clap(long, help = "Open the entry's repository instead of its Hub page")]
+    repo: bool,
+
+    #[clap(long = "print", help = "Print the URL instead of opening it")]
+    print_only: bool,
+}
+
+impl OpenCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let selection = self.selection_with_words().await;
+        let Some(entry) = resolve_any_entry(&selection, false).await? else {
+            return Ok(());
+        };
+
+        let hub_page = entry.hub_url(settings::get().hub_url()).map(|u| u.to_string());
+        let url = match hub_page {
+            Some(hub_page) if !self.repo => hub_page,
+            _ => entry.repo_url().to_owned(),
+        };
+
+        if self.print_only || !open_in_browser(&url) {
+            println!("{url}");
+        }
+        Ok(())
+    }
+
+    // A single word that is an entry ID opens that entry; otherwise the words are search terms
+    async fn selection_with_words(&self) -> SelectionArgs {
+        let mut selection = self.selection.clone();
+        if let [word] = self.words.as_slice() {
+            let entries = crate::hub_api::index().await.unwrap_or_default();
+            if entries.iter().any(|e| e.id() == word) {
+                selection.id = Some(word.clone());
+                return selection;
+            }
+        }
+        selection.terms.extend(self.words.iter().cloned());
+        selection
+    }
+}
+
+// Returns false if there's no browser to open, e.g. over SSH, so the caller
+// can print the URL instead
+fn open_in_browser(url: &str) -> bool {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        if std::env::var_os("DISPLAY").is_none() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+            return false;
+        }
+        std::process::Command::new("xdg-open")
+    };
+    command.arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
@@ -0,0 +1,197 @@
+use anyhow::anyhow;
+use clap::Parser;
+use spin_hub_api::compat::parse_spin_version;
+
+use crate::{archive, git, hub_api, installed, interrupt, oci, output, settings, style};
+use super::new::{install_template, TemplateArgs, TemplateRef};
+use super::plugin::{install_plugin, latest_version, VerifyArgs};
+
+#[derive(Parser, Debug)]
+#[clap(about = "List templates and plugins installed from the Hub that have newer versions")]
+pub struct OutdatedCommand {
+    #[clap(long, help = "Install the newer versions")]
+    update: bool,
+
+    #[clap(short = 'y', long = "yes", requires = "update", help = "With --update, install without asking for confirmation")]
+    yes: bool,
+
+    #[clap(flatten)]
+    verification: VerifyArgs,
+}
+
+pub(super) struct Outdated {
+    kind: &'static str,
+    name: String,
+    installed: String,
+    latest: String,
+    update: Update,
+}
+
+enum Update {
+    Template { template: TemplateRef, git_ref: Option<String> },
+    Plugin(hub_api::IndexEntry),
+}
+
+impl OutdatedCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let entries = hub_api::index().await?;
+
+        let (outdated, problems) = find_outdated(&entries).await?;
+        for problem in problems {
+            style::warning!("{problem}");
+        }
+
+        if outdated.is_empty() {
+            output::note!("Everything installed from the Hub is up to date");
+            return Ok(());
+        }
+
+        let mut table = output::table(settings::get().output);
+        table.set_header(["Kind", "Name", "Installed", "Latest"]);
+        for item in &outdated {
+            table.add_row(output::single_line_row(vec![
+                item.kind.to_owned(),
+                item.name.clone(),
+                item.installed.clone(),
+                item.latest.clone(),
+            ]));
+        }
+        println!("{table}");
+
+        if !self.update {
+            output::note!("Use `hub outdated --update` to install the newer versions.");
+            return Ok(());
+        }
+        for item in outdated {
+            println!("Updating {} {}", item.kind.to_lowercase(), item.name);
+            self.update(item.update).await?;
+        }
+        Ok(())
+    }
+
+    async fn update(&self, update: Update) -> anyhow::Result<()> {
+        match update {
+            Update::Template { template, git_ref } => {
+                let tempdir = tempfile::tempdir()?;
+                let _cleanup = interrupt::remove_on_interrupt(tempdir.path());
+                let manager = spin_templates::TemplateManager::in_dir(tempdir.path().join("templates"));
+                let args = TemplateArgs {
+                    no_prompt: self.yes,
+                    git_ref,
+                    keep: true,
+                    ..Default::default()
+                };
+                install_template(&manager, &template, &args, tempdir.path()).await.map(|_| ())
+            }
+            Update::Plugin(entry) => install_plugin(&entry, &self.verification, self.yes).await,
+        }
+    }
+}
+
+// Also returns what couldn't be checked, for the caller to report or not
+pub(super) async fn find_outdated(entries: &[hub_api::IndexEntry]) -> anyhow::Result<(Vec<Outdated>, Vec<String>)> {
+    let mut outdated = vec![];
+    let mut problems = vec![];
+    for template in installed::templates()? {
+        match check_template(entries, &template).await {
+            Ok(found) => outdated.extend(found),
+            Err(e) => problems.push(format!("couldn't check template {}: {e:#}", template.id)),
+        }
+    }
+    for plugin in installed::plugins()? {
+        match check_plugin(entries, &plugin).await {
+            Ok(found) => outdated.extend(found),
+            Err(e) => problems.push(format!("couldn't check plugin {}: {e:#}", plugin.name)),
+        }
+    }
+    Ok((outdated, problems))
+}
+
+async fn check_template(entries: &[hub_api::IndexEntry], template: &installed::InstalledTemplate) -> anyhow::Result<Option<Outdated>> {
+    // Pinned to a commit or digest, so there's never anything newer
+    if template.git_ref.as_deref().is_some_and(|r| git::is_commit_sha(r) || r.starts_with("sha256:")) {
+        return Ok(None);
+    }
+    // Archives have no way of saying there's a newer version
+    if archive::is_archive(&template.repo) {
+        return Ok(None);
+    }
+    let entry = match &template.entry {
+        Some(id) => Some(hub_api::find_entry(entries, id).map_err(|_| anyhow!("it is no longer on the Hub"))?),
+        None => None,
+    };
+
+    // The entry may have moved to another repository since it was installed
+    let repo = entry.map(|e| e.repo_url()).unwrap_or(template.repo.as_str());
+    let latest = if oci::is_oci(repo) {
+        oci::resolve_digest(repo, template.git_ref.as_deref()).await?
+    } else {
+        git::resolve_ref(repo, template.git_ref.as_deref()).await?
+    };
+    if repo == template.repo && template.commit.as_deref() == Some(latest.as_str()) {
+        return Ok(None);
+    }
+
+    Ok(Some(Outdated {
+        kind: "Template",
+        name: template.id.clone(),
+        installed: template.commit.as_deref().map(short_commit).unwrap_or("unknown").to_owned(),
+        latest: short_commit(&latest).to_owned(),
+        update: Update::Template {
+            template: TemplateRef {
+                entry: template.entry.clone(),
+                author: entry.map(|e| e.author().to_owned()),
+                repo: repo.to_owned(),
+                id: template.id.clone(),
+                digest: None,
+            },
+            git_ref: template.git_ref.clone(),
+        },
+    }))
+}
+
+async fn check_plugin(entries: &[hub_api::IndexEntry], plugin: &installed::InstalledPlugin) -> anyhow::Result<Option<Outdated>> {
+    let entry = hub_api::find_entry(entries, &plugin.entry).map_err(|_| anyhow!("it is no longer on the Hub"))?;
+    let Some(latest) = latest_version(entry).await? else {
+        return Ok(None);
+    };
+    if plugin.version.as_deref().is_some_and(|installed| !is_newer(&latest, installed)) {
+        return Ok(None);
+    }
+
+    Ok(Some(Outdated {
+        kind: "Plugin",
+        name: plugin.name.clone(),
+        installed: plugin.version.clone().unwrap_or_else(|| "unknown".to_owned()),
+        latest,
+        update: Update::Plugin(entry.clone()),
+    }))
+}
+
+// Versions that aren't semver are only compared for equality
+pub(super) fn is_newer(latest: &str, installed: &str) -> bool {
+    match (parse_spin_version(latest), parse_spin_version(installed)) {
+        (Some(latest), Some(installed)) => latest > installed,
+        _ => latest != installed,
+    }
+}
+
+// Also shortens OCI digests, which are `sha256:` and the hex
+fn short_commit(commit: &str) -> &str {
+    let commit = commit.strip_prefix("sha256:").unwrap_or(commit);
+    &commit[..commit.len().min(7)]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn newer_versions_are_detected() {
+        assert!(is_newer("1.2.0", "1.1.9"));
+        assert!(!is_newer("1.2.0", "1.2.0"));
+        assert!(!is_newer("1.2.0", "v1.10.0"));
+        assert!(is_newer("canary", "nightly"));
+        assert!(!is_newer("canary", "canary"));
+    }
+}
@@ -0,0 +1,179 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+
+use crate::{exit::Failure, http, hub_api::{self, Category}, installed, output, progress::Progress, settings, style, trust, verify};
+use super::selection::{resolve_entry, SelectionArgs};
+
+#[derive(Subcommand, Debug)]
+pub enum PluginCommand {
+    Install(InstallCommand),
+}
+
+impl PluginCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Install(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Install a Spin plugin from the Hub")]
+pub struct InstallCommand {
+    #[clap(name = "name", help = "Search terms identifying the plugin to install")]
+    terms: Vec<String>,
+
+    #[clap(short = 'y', long = "yes", help = "Install without asking for confirmation")]
+    yes: bool,
+
+    #[clap(flatten)]
+    verification: VerifyArgs,
+}
+
+#[derive(clap::Args, Clone, Debug, Default)]
+pub(super) struct VerifyArgs {
+    #[clap(long = "no-verify", conflicts_with = "require_signatures", help = "Don't check the SHA-256 checksum of what's downloaded before installing it")]
+    pub no_verify: bool,
+
+    #[clap(long = "require-signatures", env = "SPIN_HUB_REQUIRE_SIGNATURES", help = "Refuse to install plugins, components and samples that aren't signed by the configured trust-root key")]
+    pub require_signatures: bool,
+}
+
+impl InstallCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let Some(index_entry) = resolve_entry(Category::Plugin, &SelectionArgs::with_terms(&self.terms), self.yes).await? else {
+            return Ok(());
+        };
+
+        output::note!("Plugin {} by {}", index_entry.title(), index_entry.author());
+        output::note!("{}", index_entry.summary());
+
+        install_plugin(&index_entry, &self.verification, self.yes).await
+    }
+}
+
+pub(super) async fn install_plugin(index_entry: &hub_api::IndexEntry, verification: &VerifyArgs, yes: bool) -> anyhow::Result<()> {
+    let manifest_url = plugin_manifest_url(index_entry)?;
+    trust::check(&trust::Publisher { author: Some(index_entry.author()), repo_url: index_entry.repo_url() }, yes)?;
+
+    let tempdir = tempfile::tempdir()?;
+    let mut command = crate::spin::bin();
+    let manifest = if verification.no_verify {
+        command.args(["plugins", "install", "--url", manifest_url]);
+        None
+    } else {
+        let (manifest_path, manifest) = download_verified(index_entry, manifest_url, verification, tempdir.path()).await?;
+        command.args(["plugins", "install", "--file"]).arg(manifest_path);
+        Some(manifest)
+    };
+    if yes {
+        command.arg("--yes");
+    }
+    let status = command.status().await?;
+    if !status.success() {
+        return Err(anyhow!("spin plugins install failed - see output for details"));
+    }
+
+    // So `hub outdated` can tell when there's a newer version
+    let manifest_field = |name: &str| manifest.as_ref().and_then(|m| m[name].as_str()).map(|v| v.to_owned());
+    installed::record_plugin(installed::InstalledPlugin {
+        entry: index_entry.id().to_owned(),
+        name: manifest_field("name").unwrap_or_else(|| index_entry.id().to_owned()),
+        version: manifest_field("version"),
+    })
+}
+
+// The version in the plugin's manifest on the Hub
+pub(super) async fn latest_version(index_entry: &hub_api::IndexEntry) -> anyhow::Result<Option<String>> {
+    let manifest_url = plugin_manifest_url(index_entry)?;
+    let manifest_bytes = http::download(&http::client()?, manifest_url).await?;
+    let manifest: serde_json::Value = serde_json::from_slice(&manifest_bytes)
+        .with_context(|| format!("{manifest_url} is not a valid plugin manifest"))?;
+    Ok(manifest["version"].as_str().map(|v| v.to_owned()))
+}
+
+// Downloads and checks the package for this platform, and writes a manifest
+// that installs that exact file, so Spin doesn't download it again.
+async fn download_verified(index_entry: &hub_api::IndexEntry, manifest_url: &str, verification: &VerifyArgs, dir: &Path) -> anyhow::Result<(PathBuf, serde_json::Value)> {
+    let client = http::client()?;
+    let progress = Progress::start(format!("Downloading {manifest_url}"));
+    let manifest_bytes = http::download(&client, manifest_url).await?;
+    let manifest_path = dir.join("manifest.json");
+    std::fs::write(&manifest_path, &manifest_bytes)?;
+    progress.finish();
+
+    check_signature(index_entry, verification, &client, &manifest_path, dir).await?;
+
+    let mut manifest: serde_json::Value = serde_json::from_slice(&manifest_bytes)
+        .with_context(|| format!("{manifest_url} is not a valid plugin manifest"))?;
+
+    let (os, arch) = plugin_platform();
+    let package = manifest["packages"].as_array_mut()
+        .and_then(|packages| packages.iter_mut().find(|p| p["os"] == os && p["arch"] == arch))
+        .ok_or_else(|| anyhow!("The plugin has no package for {os}/{arch}"))?;
+    let (Some(url), Some(expected)) = (package["url"].as_str(), package["sha256"].as_str()) else {
+        return Err(anyhow!("The plugin manifest doesn't give a URL and checksum for {os}/{arch}"));
+    };
+    // Mirrored manifests give package locations relative to the manifest
+    let url = url::Url::parse(manifest_url).and_then(|base| base.join(url))
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| url.to_owned());
+
+    let progress = Progress::start(format!("Downloading {url}"));
+    let archive = http::download(&client, &url).await?;
+    progress.finish();
+    verify::check_sha256(&archive, expected, &url)?;
+
+    let archive_path = dir.join(url.rsplit('/').next().unwrap_or("plugin.tar.gz"));
+    std::fs::write(&archive_path, &archive)?;
+    package["url"] = url::Url::from_file_path(&archive_path)
+        .map_err(|_| anyhow!("Can't make a URL for {}", archive_path.display()))?
+        .to_string()
+        .into();
+
+    std::fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?)?;
+    Ok((manifest_path, manifest))
+}
+
+// For plugins the artifact is the manifest, which holds the package checksums,
+// so a signed manifest covers the package too
+pub(super) async fn check_signature(index_entry: &hub_api::IndexEntry, verification: &VerifyArgs, client: &reqwest::Client, artifact_path: &Path, dir: &Path) -> anyhow::Result<()> {
+    let trust_root = settings::get().trust_root.as_deref();
+    let (signature_url, trust_root) = match (index_entry.artifact_signature(), trust_root) {
+        (Some(signature_url), Some(trust_root)) => (signature_url, trust_root),
+        (None, _) if verification.require_signatures => return Err(Failure::Verification(format!("{} is not signed, and --require-signatures is set", index_entry.title())).into()),
+        (Some(_), None) if verification.require_signatures => return Err(anyhow!("--require-signatures is set, but no trust root is configured. Set one with `hub config set trust-root <public key file>`")),
+        (Some(_), None) => {
+            style::warning!("{} is signed, but the signature can't be checked because no trust root is configured.", index_entry.title());
+            return Ok(());
+        }
+        (None, _) => return Ok(()),
+    };
+
+    let signature_path = dir.join("artifact.sig");
+    std::fs::write(&signature_path, http::download(client, signature_url).await?)?;
+    verify::check_cosign_signature(artifact_path, &signature_path, trust_root).await
+        .with_context(|| format!("Can't verify the signature of {}", index_entry.title()))
+}
+
+// The names Spin plugin manifests use for this platform
+pub(super) fn plugin_platform() -> (&'static str, &'static str) {
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        arch => arch,
+    };
+    (std::env::consts::OS, arch)
+}
+
+fn plugin_manifest_url(index_entry: &hub_api::IndexEntry) -> anyhow::Result<&str> {
+    match index_entry.artifact_source() {
+        Some(url) if url.ends_with(".json") => Ok(url),
+        _ => Err(anyhow!(
+            "{} doesn't publish a plugin manifest on the Hub. See {} for installation instructions.",
+            index_entry.title(),
+            index_entry.repo_url()
+        )),
+    }
+}
@@ -0,0 +1,164 @@
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf, Prefix};
+
+use anyhow::{Context, Result};
+
+use crate::style;
+
+// The line endings to give the generated text files
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub(super) enum Newline {
+    Lf,
+    Crlf,
+    // Whatever this platform uses
+    Auto,
+}
+
+impl Newline {
+    fn is_crlf(self) -> bool {
+        match self {
+            Self::Lf => false,
+            Self::Crlf => true,
+            Self::Auto => cfg!(windows),
+        }
+    }
+}
+
+// Device names Windows reserves in every directory, with or without an extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// Windows limits paths to 260 characters unless they're in the extended-length
+// `\\?\` form, and generated applications with deep trees can go past that.
+// Other platforms get the path back as it is.
+pub(super) fn long_path(path: &Path) -> Result<PathBuf> {
+    if !cfg!(windows) {
+        return Ok(path.to_owned());
+    }
+    // Extended-length paths are used as they are, so `.` and `..` must go first
+    let absolute = std::env::current_dir()?.join(path);
+    let mut long = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::Prefix(prefix) => match prefix.kind() {
+                Prefix::Disk(_) => {
+                    let mut disk = OsString::from(r"\\?\");
+                    disk.push(prefix.as_os_str());
+                    long.push(disk);
+                }
+                Prefix::UNC(server, share) => {
+                    let mut unc = OsString::from(r"\\?\UNC\");
+                    unc.push(server);
+                    unc.push(r"\");
+                    unc.push(share);
+                    long.push(unc);
+                }
+                // Already extended-length, or a device
+                _ => return Ok(absolute),
+            },
+            Component::CurDir => {}
+            Component::ParentDir => {
+                long.pop();
+            }
+            other => long.push(other),
+        }
+    }
+    Ok(long)
+}
+
+// Also for application and component names, which templates use as directory names
+pub(super) fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+    RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem))
+}
+
+// Names that can't be created on Windows, or that Windows silently changes
+fn windows_problem(name: &str) -> Option<&'static str> {
+    if is_reserved_name(name) {
+        Some("is a reserved device name")
+    } else if name.contains(['<', '>', ':', '"', '|', '?', '*']) || name.chars().any(|c| c.is_control()) {
+        Some("has characters that aren't allowed in file names")
+    } else if name.ends_with(['.', ' ']) {
+        Some("ends with a dot or space, which are dropped from file names")
+    } else {
+        None
+    }
+}
+
+// Fails on Windows, where these would break the application. Elsewhere it's
+// only a warning, as the application works but not for anyone on Windows.
+pub(super) fn check_names(paths: &[PathBuf]) -> Result<()> {
+    let problems = paths.iter()
+        .flat_map(|path| path.components().filter_map(|c| match c {
+            Component::Normal(name) => {
+                let name = name.to_string_lossy();
+                windows_problem(&name).map(|problem| format!("  {} ({name} {problem})", path.display()))
+            }
+            _ => None,
+        }))
+        .collect::<Vec<_>>();
+    if problems.is_empty() {
+        return Ok(());
+    }
+    let list = problems.join("\n");
+    if cfg!(windows) {
+        Err(anyhow::anyhow!("These names can't be used on Windows:\n{list}"))
+    } else {
+        style::warning!("These names won't work on Windows:\n{list}");
+        Ok(())
+    }
+}
+
+// Converts the line endings of the text files in `dir`. Files that aren't
+// UTF-8, or have NUL bytes, are taken to be binary and left alone.
+pub(super) fn normalize_newlines(dir: &Path, files: &[PathBuf], newline: Newline) -> Result<()> {
+    for file in files {
+        let path = dir.join(file);
+        let bytes = std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let Ok(text) = std::str::from_utf8(&bytes) else {
+            continue;
+        };
+        if text.contains('\0') {
+            continue;
+        }
+        if let Some(converted) = convert_newlines(text, newline) {
+            std::fs::write(&path, converted).with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+// None if the text already has the right line endings
+fn convert_newlines(text: &str, newline: Newline) -> Option<String> {
+    let lf = text.replace("\r\n", "\n");
+    let converted = if newline.is_crlf() { lf.replace('\n', "\r\n") } else { lf };
+    (converted != text).then_some(converted)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn newlines_are_converted_both_ways() {
+        assert_eq!(Some("a\r\nb\r\n".to_owned()), convert_newlines("a\nb\r\n", Newline::Crlf));
+        assert_eq!(Some("a\nb\n".to_owned()), convert_newlines("a\r\nb\n", Newline::Lf));
+        assert_eq!(None, convert_newlines("a\nb\n", Newline::Lf));
+        assert_eq!(None, convert_newlines("a\r\nb", Newline::Crlf));
+    }
+
+    #[test]
+    fn windows_reserved_names_are_found() {
+        assert!(windows_problem("con").is_some());
+        assert!(windows_problem("Aux.txt").is_some());
+        assert!(windows_problem("COM1.tar.gz").is_some());
+        assert!(windows_problem("what?.md").is_some());
+        assert!(windows_problem("notes.").is_some());
+        assert_eq!(None, windows_problem("console.rs"));
+        assert_eq!(None, windows_problem("COM10"));
+        assert_eq!(None, windows_problem(".gitignore"));
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::{git, style};
+
+const MANIFEST: &str = "metadata/spin-template.toml";
+// Templates are at most a few directories into a repository
+const MAX_DEPTH: usize = 4;
+// Typed at a prompt to change the previous answer
+const BACK: &str = "<";
+
+// A value a template asks for, as declared in its `spin-template.toml`
+#[derive(Debug, PartialEq)]
+pub(super) struct Parameter {
+    pub id: String,
+    pub prompt: String,
+    pub default: Option<String>,
+    pub pattern: Option<String>,
+    pub allowed_values: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct Manifest {
+    id: String,
+    #[serde(default)]
+    parameters: toml::Table,
+    #[serde(default)]
+    add_component: Option<AddComponent>,
+}
+
+#[derive(serde::Deserialize)]
+struct AddComponent {
+    #[serde(default)]
+    skip_parameters: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct RawParameter {
+    #[serde(default)]
+    prompt: Option<String>,
+    #[serde(default)]
+    default: Option<String>,
+    #[serde(default)]
+    pattern: Option<String>,
+    #[serde(default)]
+    allowed_values: Vec<String>,
+}
+
+// The parameters of template `id` somewhere under `dir`, in the order the
+// manifest declares them, which is the order Spin asks in. None if the
+// template's manifest can't be found or read, so Spin does the asking.
+pub(super) fn template_parameters(dir: &Path, id: &str, adding_component: bool) -> Option<Vec<Parameter>> {
+    let (path, text) = find_manifest(dir, id, 0)?;
+    match parse_parameters(&text, adding_component) {
+        Ok(parameters) => Some(parameters),
+        Err(e) => {
+            tracing::debug!("Can't read the parameters in {}: {e:#}", path.display());
+            None
+        }
+    }
+}
+
+fn find_manifest(dir: &Path, id: &str, depth: usize) -> Option<(PathBuf, String)> {
+    let path = dir.join(MANIFEST);
+    if let Ok(text) = std::fs::read_to_string(&path) {
+        if toml::from_str::<Manifest>(&text).is_ok_and(|m| m.id == id) {
+            return Some((path, text));
+        }
+    }
+    if depth >= MAX_DEPTH {
+        return None;
+    }
+    std::fs::read_dir(dir).ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()) && entry.file_name() != ".git")
+        .find_map(|entry| find_manifest(&entry.path(), id, depth + 1))
+}
+
+fn parse_parameters(text: &str, adding_component: bool) -> Result<Vec<Parameter>> {
+    let manifest: Manifest = toml::from_str(text)?;
+    let skipped = match (&manifest.add_component, adding_component) {
+        (Some(add_component), true) => add_component.skip_parameters.clone(),
+        _ => vec![],
+    };
+    let mut parameters = manifest.parameters.into_iter()
+        .filter(|(id, _)| !skipped.contains(id))
+        .map(|(id, value)| {
+            let raw: RawParameter = value.try_into().with_context(|| format!("Parameter '{id}' is not valid"))?;
+            Ok(Parameter {
+                prompt: raw.prompt.unwrap_or_else(|| id.clone()),
+                id,
+                default: raw.default,
+                pattern: raw.pattern,
+                allowed_values: raw.allowed_values,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    // The TOML table is sorted by name, so the order comes from the text
+    parameters.sort_by_key(|p| declared_at(text, &p.id));
+    Ok(parameters)
+}
+
+fn declared_at(text: &str, id: &str) -> usize {
+    text.lines()
+        .position(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix('"').and_then(|l| l.strip_prefix(id)).and_then(|l| l.strip_prefix('"'))
+                .or_else(|| line.strip_prefix(id));
+            line.is_some_and(|rest| rest.trim_start().starts_with('='))
+        })
+        .unwrap_or(usize::MAX)
+}
+
+// The values templates commonly use for who wrote the application
+enum IdentityField {
+    Name,
+    Email,
+    // As in Cargo.toml: `Name <email>`
+    NameAndEmail,
+}
+
+fn identity_field(parameter: &str) -> Option<IdentityField> {
+    match parameter {
+        "author" | "author-name" | "owner" => Some(IdentityField::Name),
+        "email" | "author-email" => Some(IdentityField::Email),
+        "authors" => Some(IdentityField::NameAndEmail),
+        _ => None,
+    }
+}
+
+fn identity_value(field: IdentityField, name: Option<&str>, email: Option<&str>) -> Option<String> {
+    match (field, name, email) {
+        (IdentityField::Name, name, _) => name.map(|n| n.to_owned()),
+        (IdentityField::Email, _, email) => email.map(|e| e.to_owned()),
+        (IdentityField::NameAndEmail, Some(name), Some(email)) => Some(format!("{name} <{email}>")),
+        (IdentityField::NameAndEmail, name, _) => name.map(|n| n.to_owned()),
+    }
+}
+
+// The user's Git name and email for the parameters that look like they want
+// them, to use as their defaults
+pub(super) async fn git_identity_values(parameters: &[Parameter]) -> HashMap<String, String> {
+    if !parameters.iter().any(|p| identity_field(&p.id).is_some()) {
+        return HashMap::new();
+    }
+    let (name, email) = git::identity().await;
+    parameters.iter()
+        .filter_map(|p| Some((p.id.clone(), identity_value(identity_field(&p.id)?, name.as_deref(), email.as_deref())?)))
+        .collect()
+}
+
+// Asks for each value that wasn't given, showing what the template says
+// about it. Answering `<` goes back to the previous question.
+pub(super) fn prompt_values(parameters: &[Parameter], given: HashMap<String, String>) -> Result<HashMap<String, String>> {
+    let asked = parameters.iter().filter(|p| !given.contains_key(&p.id)).collect::<Vec<_>>();
+    let mut answers: Vec<Option<String>> = vec![None; asked.len()];
+    if !asked.is_empty() {
+        println!("The template needs {} values. Answer {BACK} to change the previous one.", asked.len());
+    }
+
+    let mut idx = 0;
+    while let Some(parameter) = asked.get(idx) {
+        println!();
+        println!("{}", style::attention(&parameter.prompt));
+        println!("  Parameter: {}", parameter.id);
+        if let Some(pattern) = &parameter.pattern {
+            println!("  Must match: {pattern}");
+        }
+        let previous = answers[idx].clone().or(parameter.default.clone());
+        match ask(parameter, previous, idx > 0)? {
+            Some(answer) => {
+                answers[idx] = Some(answer);
+                idx += 1;
+            }
+            None => idx -= 1,
+        }
+    }
+
+    let mut values = given;
+    values.extend(asked.iter().zip(answers).filter_map(|(p, a)| Some((p.id.clone(), a?))));
+    Ok(values)
+}
+
+// None to go back
+fn ask(parameter: &Parameter, default: Option<String>, can_go_back: bool) -> Result<Option<String>> {
+    if !parameter.allowed_values.is_empty() {
+        let mut items = parameter.allowed_values.clone();
+        if can_go_back {
+            items.push("(back to the previous question)".to_owned());
+        }
+        let selected = default.as_ref().and_then(|d| items.iter().position(|i| i == d)).unwrap_or(0);
+        let idx = dialoguer::Select::new().items(&items).default(selected).interact()?;
+        return Ok(parameter.allowed_values.get(idx).cloned());
+    }
+
+    let pattern = parameter.pattern.as_deref()
+        .map(|p| regex::Regex::new(&format!("^(?:{p})$")))
+        .transpose()
+        .with_context(|| format!("The pattern for parameter '{}' is not valid", parameter.id))?;
+    let mut input = dialoguer::Input::<String>::new().with_prompt(&parameter.id).allow_empty(true);
+    if let Some(default) = default {
+        input = input.default(default);
+    }
+    let answer = input
+        .validate_with(|answer: &String| -> Result<(), String> {
+            match &pattern {
+                _ if can_go_back && answer == BACK => Ok(()),
+                Some(pattern) if !pattern.is_match(answer) => Err(format!("must match {pattern}")),
+                _ => Ok(()),
+            }
+        })
+        .interact_text()?;
+    Ok(Some(answer).filter(|a| !(can_go_back && a == BACK)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parameters_keep_the_manifest_order() {
+        let manifest = r#"
+manifest_version = "1"
+id = "http-rust"
+
+[add_component]
+skip_parameters = ["project-description"]
+
+[parameters]
+project-description = { type = "string", prompt = "Description", default = "" }
+http-path = { type = "string", prompt = "HTTP path", default = "/...", pattern = "^/\\S*$" }
+"auth-mode" = { type = "string", prompt = "Auth", allowed_values = ["none", "basic"] }
+"#;
+        let ids = |params: Vec<Parameter>| params.into_iter().map(|p| p.id).collect::<Vec<_>>();
+        assert_eq!(vec!["project-description", "http-path", "auth-mode"], ids(parse_parameters(manifest, false).unwrap()));
+        assert_eq!(vec!["http-path", "auth-mode"], ids(parse_parameters(manifest, true).unwrap()));
+
+        let params = parse_parameters(manifest, false).unwrap();
+        assert_eq!(Some("^/\\S*$".to_owned()), params[1].pattern);
+        assert_eq!(vec!["none", "basic"], params[2].allowed_values);
+    }
+
+    #[test]
+    fn git_identity_fills_author_parameters() {
+        let name = Some("Ada Lovelace");
+        let email = Some("ada@example.com");
+        let value = |id| identity_value(identity_field(id)?, name, email);
+        assert_eq!(Some("Ada Lovelace".to_owned()), value("author"));
+        assert_eq!(Some("ada@example.com".to_owned()), value("author-email"));
+        assert_eq!(Some("Ada Lovelace <ada@example.com>".to_owned()), value("authors"));
+        assert_eq!(None, value("project-description"));
+        assert_eq!(Some("Ada Lovelace".to_owned()), identity_value(IdentityField::NameAndEmail, name, None));
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use spin_templates::{RunOptions, TemplateVariantInfo};
+
+use crate::{hub_api, interrupt, provenance::Provenance};
+use super::new::{run_template, TemplateArgs, TemplateRef};
+use super::summary;
+
+// A template rendered into a temporary directory, for comparing with a project
+pub(super) struct Rendered {
+    dir: tempfile::TempDir,
+    _cleanup: interrupt::CleanupGuard,
+    pub files: Vec<PathBuf>,
+}
+
+impl Rendered {
+    pub fn path(&self) -> PathBuf {
+        self.dir.path().join("app")
+    }
+}
+
+// The template a generated application came from. The Hub entry may now point
+// at a different repository, so entries are looked up again.
+pub(super) async fn provenance_template(provenance: &Provenance) -> anyhow::Result<TemplateRef> {
+    match &provenance.entry {
+        Some(id) => {
+            let entries = hub_api::index().await?;
+            Ok(TemplateRef::from_entry(hub_api::find_entry(&entries, id)?))
+        }
+        None => Ok(TemplateRef {
+            entry: None,
+            author: None,
+            repo: provenance.repo.clone(),
+            id: provenance.template_id.clone(),
+            digest: None,
+        }),
+    }
+}
+
+pub(super) async fn render(template: &TemplateRef, name: &str, values: HashMap<String, String>, git_ref: Option<String>) -> anyhow::Result<Rendered> {
+    let dir = tempfile::tempdir()?;
+    let cleanup = interrupt::remove_on_interrupt(dir.path());
+    let output_path = dir.path().join("app");
+
+    let options = RunOptions {
+        variant: TemplateVariantInfo::NewApplication,
+        name: name.to_owned(),
+        output_path: output_path.clone(),
+        values,
+        accept_defaults: true,
+    };
+    let args = TemplateArgs {
+        no_prompt: true,
+        git_ref,
+        ..Default::default()
+    };
+    run_template(template, options, &args).await?;
+
+    let files = summary::list_files(&output_path)?;
+    Ok(Rendered { dir, _cleanup: cleanup, files })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Status {
+    Added,
+    Modified,
+}
+
+#[derive(Clone)]
+pub(super) struct FileChange {
+    pub path: PathBuf,
+    pub status: Status,
+    // None for binary files
+    pub diff: Option<String>,
+}
+
+// Files the template would create or change in `project`. Files that are only
+// in the project are the user's own, so they aren't changes.
+pub(super) fn changes(rendered: &Rendered, project: &Path) -> anyhow::Result<Vec<FileChange>> {
+    let mut changes = vec![];
+    for file in &rendered.files {
+        let new = std::fs::read(rendered.path().join(file))?;
+        let project_file = project.join(file);
+        let (status, old) = if project_file.is_file() {
+            (Status::Modified, std::fs::read(&project_file)?)
+        } else {
+            (Status::Added, vec![])
+        };
+        if status == Status::Modified && old == new {
+            continue;
+        }
+
+        let name = file.display().to_string().replace('\\', "/");
+        let diff = match (std::str::from_utf8(&old), std::str::from_utf8(&new)) {
+            (Ok(old), Ok(new)) => {
+                let old_header = if status == Status::Added { "/dev/null".to_owned() } else { format!("a/{name}") };
+                Some(similar::TextDiff::from_lines(old, new)
+                    .unified_diff()
+                    .header(&old_header, &format!("b/{name}"))
+                    .to_string())
+            }
+            _ => None,
+        };
+        changes.push(FileChange { path: file.clone(), status, diff });
+    }
+    Ok(changes)
+}
+
+pub(super) fn print_changes(changes: &[FileChange]) {
+    for change in changes {
+        match &change.diff {
+            Some(diff) => print!("{diff}"),
+            None => println!("Binary file {} differs", change.path.display()),
+        }
+    }
+}
+
+pub(super) fn print_change_summary(changes: &[FileChange]) {
+    for change in changes {
+        let status = match change.status {
+            Status::Added => "added",
+            Status::Modified => "modified",
+        };
+        println!("    {status:<10}{}", change.path.display());
+    }
+}
+
+// Whether making a change would lose the user's own edits: the project's file
+// must still be as the template at `base` created it. Without a base there's
+// no telling, so every file the project already has counts as edited.
+pub(super) fn is_user_edited(change: &FileChange, base: Option<&Rendered>, project: &Path) -> anyhow::Result<bool> {
+    let Some(base) = base else {
+        return Ok(change.status == Status::Modified);
+    };
+    let base_file = base.path().join(&change.path);
+    Ok(match change.status {
+        // The user deleted it
+        Status::Added => base_file.is_file(),
+        Status::Modified => !base_file.is_file() || std::fs::read(&base_file)? != std::fs::read(project.join(&change.path))?,
+    })
+}
+
+pub(super) fn apply_changes(rendered: &Rendered, project: &Path, changes: &[FileChange]) -> anyhow::Result<()> {
+    for change in changes {
+        let dest = project.join(&change.path);
+        if let Some(dir) = dest.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::copy(rendered.path().join(&change.path), &dest)?;
+    }
+    Ok(())
+}
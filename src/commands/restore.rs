@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+
+use clap::{Parser};
+
+use crate::provenance::{self, Provenance};
+use super::new::{new_from_template, AppOptions, TemplateArgs, TemplateRef};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Create an application again from the template commit pinned in a lockfile")]
+pub struct RestoreCommand {
+    #[clap(name = "lockfile", default_value = provenance::LOCK_FILE_NAME, help = "The lockfile written by `hub new --lock`")]
+    lockfile: PathBuf,
+
+    #[clap(short = 'o', long = "output", value_name = "PATH", help = "Directory to create the application in [default: the application name]")]
+    output: Option<PathBuf>,
+
+    #[clap(long, help = "Generate into a non-empty directory")]
+    force: bool,
+}
+
+impl RestoreCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let lock = Provenance::read_lock(&self.lockfile)?;
+
+        let template = TemplateRef {
+            entry: lock.entry.clone(),
+            author: None,
+            repo: lock.repo.clone(),
+            id: lock.template_id.clone(),
+            digest: None,
+        };
+        let app = AppOptions {
+            name: Some(lock.name.clone()),
+            output: self.output.clone(),
+            lock: true,
+            ..Default::default()
+        };
+        // Only the recorded values are used, so that every restore is the same
+        let args = TemplateArgs {
+            no_prompt: true,
+            values: lock.values.into_iter().collect(),
+            git_ref: lock.commit,
+            force: self.force,
+            ..Default::default()
+        };
+
+        new_from_template(&template, &app, &args).await
+    }
+}
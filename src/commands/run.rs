@@ -1,15 +1,15 @@
 use std::path::PathBuf;
 
 use clap::{Parser};
-use itertools::Itertools;
 
-use crate::{hub_api, git};
+use crate::{hub_api::{self, Category}, git, interrupt};
+use super::selection::{resolve_entry, SelectionArgs};
 
 #[derive(Parser, Debug)]
 #[clap(about = "Create an application from a template on the Hub")]
 pub struct RunCommand {
-    #[clap(short = 't')]
-    terms: Vec<String>,
+    #[clap(flatten)]
+    selection: SelectionArgs,
 
     #[clap(long = "deploy")]
     deploy: bool,
@@ -17,7 +17,7 @@ pub struct RunCommand {
 
 impl RunCommand {
     pub async fn run(&self) -> anyhow::Result<()> {
-        let Some(index_entry) = self.resolve_selection().await? else {
+        let Some(index_entry) = resolve_entry(Category::Sample, &self.selection, false).await? else {
             return Ok(());
         };
 
@@ -35,10 +35,13 @@ impl RunCommand {
 
         let (repo, manifest_path) = get_repo_and_manifest_path(&index_entry)?;
 
-        git::clone_decoupled(&repo).await?;
-
         let clone_dir = git::clone_dir(&repo)?;
 
+        // Don't remove a directory that was already there if the clone fails to start
+        let cleanup = (!PathBuf::from(&clone_dir).exists()).then(|| interrupt::remove_on_interrupt(&clone_dir));
+        git::clone_decoupled(&repo).await?;
+        drop(cleanup);
+
         let manifest_path = PathBuf::from(&clone_dir).join(manifest_path);
 
         // TODO: DAMMIT THERE ARE SO MANY PIPENVS AND NPMS AND STUFF
@@ -59,47 +62,6 @@ impl RunCommand {
 
         Ok(())
     }
-
-    async fn resolve_selection(&self) -> Result<Option<hub_api::IndexEntry>, dialoguer::Error> {
-        let entries = hub_api::index().await.unwrap();
-        let matches = entries.iter().filter(|e| self.is_match(e)).sorted_by_key(|e| e.title()).collect_vec();
-
-        match matches.len() {
-            0 => {
-                println!("No templates matches your search terms");
-                return Ok(None);
-            }
-            1 => {
-                let index_entry = matches[0].clone();
-                return Ok(Some(index_entry))
-            },
-            _ => {
-                dialoguer::Select::new()
-                    .with_prompt("Several templates match your search. Use arrow keys and Enter to select, or Esc to cancel:")
-                    .items(&matches.iter().map(|e| e.title()).collect_vec())
-                    .interact_opt()?
-                    .map(|idx| Ok(matches[idx].clone()))
-                    .transpose()
-            }
-        }
-    }
-
-    fn is_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
-        self.is_terms_match(index_entry) &&
-            self.is_category_match(index_entry)
-    }
-
-    fn is_terms_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
-        let tags = index_entry.tags();
-        let title = index_entry.title_words();
-        self.terms.iter()
-            .map(|t| t.to_lowercase())
-            .all(|t| tags.contains(&t) || title.contains(&t))
-    }
-
-    fn is_category_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
-        index_entry.category() == hub_api::Category::Sample
-    }
 }
 
 fn get_repo_and_manifest_path(_: &hub_api::IndexEntry) -> anyhow::Result<(String, String)> {
@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use clap::{Parser};
+
+use crate::{archive, exit::Failure, git, http, hub_api::{self, Category}, interrupt, output, progress::Progress, style, trust, verify};
+use super::plugin::{check_signature, VerifyArgs};
+use super::{selection::{resolve_entry, SelectionArgs}, summary};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Create a copy of a sample application from the Hub")]
+pub struct SampleCommand {
+    #[clap(flatten)]
+    selection: SelectionArgs,
+
+    #[clap(name = "dir", help = "Directory to create the sample in [default: the sample repository name]")]
+    dir: Option<PathBuf>,
+
+    #[clap(long = "tag", help = "Branch or tag of the sample repository to use")]
+    tag: Option<String>,
+
+    #[clap(flatten)]
+    verification: VerifyArgs,
+}
+
+impl SampleCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let Some(index_entry) = resolve_entry(Category::Sample, &self.selection, false).await? else {
+            return Ok(());
+        };
+
+        output::note!("Sample {} by {}", index_entry.title(), index_entry.author());
+        output::note!("{}", index_entry.summary());
+
+        let repo = index_entry.repo_url();
+        trust::check(&trust::Publisher { author: Some(index_entry.author()), repo_url: repo }, false)?;
+        let dir = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => PathBuf::from(git::clone_dir(repo)?),
+        };
+        if dir.exists() {
+            return Err(anyhow!("{} already exists", dir.display()));
+        }
+
+        let cleanup = interrupt::remove_on_interrupt(&dir);
+        // A published archive can be checked like plugins and components are, but not a branch of its own choosing
+        match index_entry.artifact_source().filter(|s| archive::is_archive(s)) {
+            Some(source) if self.tag.is_none() => fetch_archive(&index_entry, source, &self.verification, &dir).await?,
+            _ => clone(&index_entry, self.tag.as_deref(), &self.verification, &dir).await?,
+        }
+        drop(cleanup);
+
+        output::print_outcome(&format!("Created {}", dir.display()), &dir.display().to_string());
+        summary::print_next_steps(&dir);
+        Ok(())
+    }
+}
+
+async fn fetch_archive(index_entry: &hub_api::IndexEntry, source: &str, verification: &VerifyArgs, dir: &Path) -> anyhow::Result<()> {
+    let progress = Progress::start(format!("Downloading {source}"));
+    let bytes = archive::read(source).await?;
+    progress.finish();
+
+    let parent = dir.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent)?;
+    let staging = tempfile::Builder::new().prefix(".spin-hub-").tempdir_in(parent)?;
+    let _cleanup = interrupt::remove_on_interrupt(staging.path());
+    if !verification.no_verify {
+        match index_entry.artifact_digest() {
+            Some(expected) => verify::check_sha256(&bytes, expected, source)?,
+            None => style::warning!("{} doesn't publish a checksum, so the download can't be checked.", index_entry.title()),
+        }
+        let archive_path = staging.path().join("sample-archive");
+        std::fs::write(&archive_path, &bytes)?;
+        check_signature(index_entry, verification, &http::client()?, &archive_path, staging.path()).await?;
+    }
+
+    let root = archive::extract(&bytes, source, &staging.path().join("contents"))?;
+    std::fs::rename(&root, dir).with_context(|| format!("Failed to move the sample to {}", dir.display()))
+}
+
+// A commit the entry pins the sample to is checked out and checked. Anything
+// else can't be verified, so with --require-signatures it's refused.
+async fn clone(index_entry: &hub_api::IndexEntry, tag: Option<&str>, verification: &VerifyArgs, dir: &Path) -> anyhow::Result<()> {
+    let repo = index_entry.repo_url();
+    let pinned = index_entry.git_ref().filter(|r| tag.is_none() && git::is_commit_sha(r));
+    if pinned.is_none() && !verification.no_verify {
+        if verification.require_signatures {
+            return Err(Failure::Verification(format!("{} isn't published as a signed archive or pinned to a commit, and --require-signatures is set", index_entry.title())).into());
+        }
+        style::warning!("{} isn't pinned to a commit, so what's downloaded can't be checked.", index_entry.title());
+    }
+
+    match pinned {
+        Some(commit) => {
+            git::clone_at_rev(repo, commit, dir, None).await?;
+            let head = git::head_commit(dir).await?;
+            if !verification.no_verify && !head.starts_with(&commit.to_lowercase()) {
+                return Err(Failure::Verification(format!("{repo} gave commit {head} instead of {commit}")).into());
+            }
+        }
+        None => git::clone_shallow(repo, tag.or(index_entry.git_ref()), dir).await?,
+    }
+
+    // The user is starting their own application, not contributing to the sample
+    let git_dir = dir.join(".git");
+    std::fs::remove_dir_all(&git_dir)
+        .with_context(|| format!("Failed to remove {}", git_dir.display()))
+}
@@ -1,7 +1,8 @@
 use clap::{Parser};
 use itertools::Itertools;
 
-use crate::hub_api;
+use crate::{exit::Failure, hub_api, output::{self, OutputFormat}, ranking, settings, stars, style};
+use super::{fields::FieldArgs, selection::print_suggestions};
 
 #[derive(Parser, Debug)]
 #[clap(about = "Search for content on the Hub")]
@@ -11,51 +12,200 @@ pub struct SearchCommand {
     #[clap(long, alias = "lang")]
     language: Option<String>,
 
-    #[clap(long, alias = "cat")]
+    #[clap(long, alias = "cat", help = "Only show entries in this category (template, sample, plugin, component or library)")]
     category: Option<String>,
+
+    #[clap(long, help = "Match entries with any of the terms, instead of all of them")]
+    any: bool,
+
+    #[clap(short = 'x', long = "exclude", value_name = "TERM", help = "Leave out entries with this word (can be repeated)")]
+    exclude: Vec<String>,
+
+    #[clap(long, help = "Only show templates for this trigger type, e.g. http, redis or cron")]
+    trigger: Option<String>,
+
+    #[clap(long = "min-spin-version", value_name = "VERSION", help = "Only show entries that work with this version of Spin")]
+    min_spin_version: Option<String>,
+
+    #[clap(short = 'o', long = "output", alias = "format", value_enum, env = "SPIN_HUB_OUTPUT")]
+    output: Option<OutputFormat>,
+
+    #[clap(long, value_enum, help = "How to order results [default: relevance if there are search terms, otherwise title, or as listed in the index for jsonl so results stream]")]
+    sort: Option<SortOrder>,
+
+    #[clap(long, help = "Reverse the sort order")]
+    reverse: bool,
+
+    #[clap(long, help = "Show at most this many results")]
+    limit: Option<usize>,
+
+    #[clap(long, help = "Only show entries you have starred")]
+    starred: bool,
+
+    #[clap(long = "include-deprecated", help = "Also show entries that are deprecated")]
+    include_deprecated: bool,
+
+    #[clap(flatten)]
+    fields: FieldArgs,
+}
+
+pub(super) const RESULT_HEADER: &[&str] = &["Name", "Category", "Language", "Trigger", "Author", "Tags", "Description"];
+
+// How an entry appears in search results, with the fields that matched the
+// search terms if there were any
+pub(super) fn result_row(entry: &hub_api::IndexEntry, relevance: Option<&ranking::Relevance>) -> Vec<String> {
+    let mut row = vec![
+        entry.listed_title(),
+        entry.category().to_string(),
+        entry.language().to_string(),
+        entry.trigger().unwrap_or_default(),
+        entry.author().to_owned(),
+        entry.tags().join(", "),
+        entry.short_summary(),
+    ];
+    if let Some(relevance) = relevance {
+        row.push(relevance.matched_fields());
+    }
+    row
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+enum SortOrder {
+    Title,
+    Author,
+    Updated,
+    Relevance,
 }
 
 impl SearchCommand {
     pub async fn run(&self) -> anyhow::Result<()> {
+        let settings = settings::get();
+        let terms = settings.terms_or_default(&self.terms);
+
+        let sort = self.sort.unwrap_or(if terms.is_empty() { SortOrder::Title } else { SortOrder::Relevance });
+
         let entries = hub_api::index().await?;
-        let matches = entries.iter().filter(|e| self.is_match(e)).sorted_by_key(|e| e.title()).collect_vec();
-        self.print(&matches);
+        let starred = if self.starred { Some(stars::starred()?) } else { None };
+        let format = settings.output_format(self.output);
+
+        // With no order to wait for, matches are written as they are found
+        if format == OutputFormat::Jsonl && self.sort.is_none() && !self.reverse {
+            let mut found = false;
+            let matches = self.matching(&entries, terms, starred.as_deref())
+                .map(|(e, _)| e)
+                .take(self.limit.unwrap_or(usize::MAX))
+                .inspect(|_| found = true);
+            output::print_json_lines(matches)?;
+            return if found { Ok(()) } else { Err(Failure::NoMatches.into()) };
+        }
+
+        let filter_span = tracing::info_span!("filter entries").entered();
+        let mut matches = self.matching(&entries, terms, starred.as_deref())
+            .sorted_by(|(e1, r1), (e2, r2)| {
+                let order = match sort {
+                    SortOrder::Title => std::cmp::Ordering::Equal,
+                    SortOrder::Author => e1.author().to_lowercase().cmp(&e2.author().to_lowercase()),
+                    // Newest first, with entries that don't say when they were updated at the end
+                    SortOrder::Updated => e2.last_updated().cmp(&e1.last_updated()),
+                    SortOrder::Relevance => r2.score.cmp(&r1.score),
+                };
+                order.then_with(|| e1.title().cmp(e2.title()))
+            })
+            .collect_vec();
+
+        if self.reverse {
+            matches.reverse();
+        }
+        if let Some(limit) = self.limit {
+            matches.truncate(limit);
+        }
+        drop(filter_span);
+
+        match format {
+            format @ (OutputFormat::Table | OutputFormat::Plain) => {
+                self.print(&matches, terms, format);
+                if matches.is_empty() {
+                    let vocabulary = entries.iter().flat_map(|e| e.tags().into_iter().chain(e.title_words())).collect_vec();
+                    print_suggestions(terms, &vocabulary);
+                }
+            }
+            OutputFormat::Json => output::print_json(&matches.iter().map(|(e, _)| e).collect_vec())?,
+            OutputFormat::Jsonl => output::print_json_lines(matches.iter().map(|(e, _)| e))?,
+            // The same columns whatever the terms
+            OutputFormat::Porcelain => self.print(&matches, &[], OutputFormat::Porcelain),
+        }
+        if matches.is_empty() {
+            return Err(Failure::NoMatches.into());
+        }
         Ok(())
     }
 
-    fn print(&self, entries: &[&hub_api::IndexEntry]) {
+    fn matching<'a>(&'a self, entries: &'a [hub_api::IndexEntry], terms: &'a [String], starred: Option<&'a [String]>) -> impl Iterator<Item = (&'a hub_api::IndexEntry, ranking::Relevance)> + 'a {
+        entries.iter()
+            .filter(move |e| self.is_match(e))
+            .filter(move |e| starred.map_or(true, |s| s.iter().any(|id| id == e.id())))
+            .map(move |e| (e, ranking::score(e, terms)))
+            .filter(move |(_, relevance)| terms.is_empty() || if self.any { relevance.is_match() } else { relevance.terms == terms.len() })
+    }
+
+    fn print(&self, entries: &[(&hub_api::IndexEntry, ranking::Relevance)], terms: &[String], format: OutputFormat) {
         if entries.is_empty() {
-            println!("No matches");
+            output::note!("No matches");
             return;
         }
 
-        let mut table = comfy_table::Table::new();
-        table.load_preset(comfy_table::presets::ASCII_BORDERS_ONLY_CONDENSED);
+        let mut table = output::table(format);
 
-        let header = vec!["Name", "Description", "Author"];
+        if self.fields.is_chosen() {
+            table.set_header(self.fields.header());
+            for (entry, _) in entries {
+                let row = self.fields.fields.iter().zip(self.fields.row(entry))
+                    .map(|(field, value)| if field.is_searched() { style::highlight(&value, terms) } else { value })
+                    .collect();
+                table.add_row(output::single_line_row(row));
+            }
+            println!("{table}");
+            return;
+        }
+
+        let show_matched = !terms.is_empty();
+        let mut header = RESULT_HEADER.to_vec();
+        if show_matched {
+            header.push("Matched");
+        }
         table.set_header(header);
 
-        for entry in entries {
-            let summary = entry.short_summary();
-            let row = vec![entry.title(), summary.as_str(), entry.author()];
-            table.add_row(row);
+        for (entry, relevance) in entries {
+            let mut row = result_row(entry, show_matched.then_some(relevance));
+            // Name, tags and description, the fields that terms are matched against
+            for cell in [0, 5, 6] {
+                row[cell] = style::highlight(&row[cell], terms);
+            }
+            table.add_row(output::single_line_row(row));
         }
 
         println!("{table}");
     }
 
     fn is_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
-        self.is_terms_match(index_entry) &&
+        !self.is_excluded(index_entry) &&
+            (self.include_deprecated || !index_entry.is_deprecated()) &&
             self.is_lang_match(index_entry) &&
-            self.is_category_match(index_entry)
+            self.is_category_match(index_entry) &&
+            self.is_trigger_match(index_entry) &&
+            self.is_spin_version_match(index_entry)
     }
 
-    fn is_terms_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
-        let tags = index_entry.tags();
-        let title = index_entry.title_words();  // TODO: the trouble is now e.g. this picks up 'trigger' for all the templates
-        self.terms.iter()
-            .map(|t| t.to_lowercase())
-            .all(|t| tags.contains(&t) || title.contains(&t))
+    fn is_excluded(&self, index_entry: &hub_api::IndexEntry) -> bool {
+        self.exclude.iter().any(|term| ranking::has_word(index_entry, term))
+    }
+
+    // Entries that don't say what they need are assumed to work
+    fn is_spin_version_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
+        match &self.min_spin_version {
+            None => true,
+            Some(version) => index_entry.is_compatible_with(version) != Some(false),
+        }
     }
 
     fn is_lang_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
@@ -65,6 +215,13 @@ impl SearchCommand {
         }
     }
 
+    fn is_trigger_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
+        match &self.trigger {
+            None => true,
+            Some(trigger) => index_entry.trigger().is_some_and(|t| t.eq_ignore_ascii_case(trigger)),
+        }
+    }
+
     fn is_category_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
         match &self.category {
             None => true,
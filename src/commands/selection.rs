@@ -0,0 +1,195 @@
+use anyhow::anyhow;
+use itertools::Itertools;
+
+use crate::{exit::Failure, fuzzy, hub_api, ranking, settings};
+
+#[derive(clap::Args, Clone, Debug, Default)]
+pub(super) struct SelectionArgs {
+    #[clap(short = 't', help = "Search term to filter entries by (can be repeated)")]
+    pub terms: Vec<String>,
+
+    #[clap(long, alias = "lang", help = "Only consider entries in this language")]
+    pub language: Option<String>,
+
+    #[clap(long, help = "Only consider templates for this trigger type, e.g. http, redis or cron")]
+    pub trigger: Option<String>,
+
+    #[clap(long, help = "Match terms approximately, e.g. 'postgre' matches 'postgresql'")]
+    pub fuzzy: bool,
+
+    #[clap(long, help = "Match entries with any of the terms, instead of all of them")]
+    pub any: bool,
+
+    #[clap(short = 'x', long = "exclude", value_name = "TERM", help = "Leave out entries matching this term (can be repeated)")]
+    pub exclude: Vec<String>,
+
+    #[clap(long, help = "Also match terms against summaries and descriptions, listing the best matches first")]
+    pub deep: bool,
+
+    #[clap(long, conflicts_with = "select", help = "If several entries match, use the first one listed")]
+    pub first: bool,
+
+    #[clap(long, value_name = "N", help = "If several entries match, use the Nth one listed (starting from 1)")]
+    pub select: Option<usize>,
+
+    #[clap(long, conflicts_with_all = ["terms", "first", "select"], help = "Use the Hub entry with this ID, without searching")]
+    pub id: Option<String>,
+}
+
+impl SelectionArgs {
+    pub fn with_terms(terms: &[String]) -> Self {
+        Self {
+            terms: terms.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    // Whether anything was given to choose an entry by
+    pub fn is_given(&self) -> bool {
+        !self.terms.is_empty() ||
+            self.language.is_some() ||
+            self.trigger.is_some() ||
+            !self.exclude.is_empty() ||
+            self.first ||
+            self.select.is_some() ||
+            self.id.is_some()
+    }
+
+    pub fn is_match(&self, terms: &[String], index_entry: &hub_api::IndexEntry) -> bool {
+        self.is_terms_match(terms, index_entry) &&
+            self.is_lang_match(index_entry) &&
+            self.is_trigger_match(index_entry)
+    }
+
+    fn is_terms_match(&self, terms: &[String], index_entry: &hub_api::IndexEntry) -> bool {
+        let mut words = index_entry.tags().into_iter().chain(index_entry.title_words()).collect_vec();
+        if self.deep {
+            words.extend(ranking::words(index_entry.summary()));
+            words.extend(ranking::words(index_entry.description().unwrap_or_default()));
+        }
+        let mut term_matches = terms.iter().map(|t| self.is_term_match(t, &words));
+        let included = if self.any && !terms.is_empty() {
+            term_matches.any(|m| m)
+        } else {
+            term_matches.all(|m| m)
+        };
+        included && !self.exclude.iter().any(|t| self.is_term_match(t, &words))
+    }
+
+    fn is_term_match(&self, term: &str, words: &[String]) -> bool {
+        let term = term.to_lowercase();
+        if self.fuzzy {
+            words.iter().any(|w| fuzzy::is_fuzzy_match(&term, w))
+        } else {
+            words.contains(&term)
+        }
+    }
+
+    fn is_lang_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
+        match &self.language {
+            None => true,
+            Some(lang) => index_entry.language().is_match(lang),
+        }
+    }
+
+    fn is_trigger_match(&self, index_entry: &hub_api::IndexEntry) -> bool {
+        match &self.trigger {
+            None => true,
+            Some(trigger) => index_entry.trigger().is_some_and(|t| t.eq_ignore_ascii_case(trigger)),
+        }
+    }
+}
+
+pub(super) async fn resolve_entry(category: hub_api::Category, selection: &SelectionArgs, no_prompt: bool) -> anyhow::Result<Option<hub_api::IndexEntry>> {
+    resolve(Some(category), selection, no_prompt).await
+}
+
+// Like `resolve_entry`, but for an entry of any category
+pub(super) async fn resolve_any_entry(selection: &SelectionArgs, no_prompt: bool) -> anyhow::Result<Option<hub_api::IndexEntry>> {
+    resolve(None, selection, no_prompt).await
+}
+
+// None if the user cancelled choosing between several matches
+async fn resolve(category: Option<hub_api::Category>, selection: &SelectionArgs, no_prompt: bool) -> anyhow::Result<Option<hub_api::IndexEntry>> {
+    let terms = settings::get().terms_or_default(&selection.terms);
+    let entries = hub_api::index().await?;
+    let is_in_category = |e: &hub_api::IndexEntry| category.as_ref().map_or(true, |c| &e.category() == c);
+
+    if let Some(id) = &selection.id {
+        let entry = hub_api::find_entry(&entries, id)?;
+        if let Some(category) = category.as_ref().filter(|_| !is_in_category(entry)) {
+            return Err(anyhow!("{} is a {}, not a {}", entry.title(), entry.category(), category.to_string().to_lowercase()));
+        }
+        return Ok(Some(entry.clone()));
+    }
+
+    let filter_span = tracing::info_span!("filter entries").entered();
+    let mut matches = entries.iter()
+        .filter(|e| is_in_category(e) && selection.is_match(terms, e))
+        .sorted_by_key(|e| e.title())
+        .collect_vec();
+    if selection.deep {
+        // Stable, so equally relevant entries stay in title order
+        matches.sort_by_cached_key(|e| std::cmp::Reverse(ranking::score(e, terms).score));
+    }
+    drop(filter_span);
+    let noun = category.as_ref().map(plural).unwrap_or_else(|| "entries".to_owned());
+    tracing::debug!(
+        ?category, ?terms, any = selection.any, fuzzy = selection.fuzzy, deep = selection.deep, exclude = ?selection.exclude,
+        matches = ?matches.iter().map(|e| (e.title(), ranking::score(e, terms).score)).collect_vec(),
+        "Matched entries"
+    );
+
+    let position = selection.select.or(selection.first.then_some(1));
+    if let (Some(position), false) = (position, matches.is_empty()) {
+        return match position.checked_sub(1).and_then(|idx| matches.get(idx)) {
+            Some(entry) => Ok(Some((*entry).clone())),
+            None => Err(anyhow!("Can't select {noun} number {position}: only {} match your search terms", matches.len())),
+        };
+    }
+
+    match matches.len() {
+        0 => {
+            println!("No {noun} match your search terms");
+            let vocabulary = entries.iter()
+                .filter(|e| is_in_category(e))
+                .flat_map(|e| e.tags().into_iter().chain(e.title_words()))
+                .collect_vec();
+            print_suggestions(terms, &vocabulary);
+            Err(Failure::NoMatches.into())
+        }
+        1 => Ok(Some(matches[0].clone())),
+        n if no_prompt => {
+            Err(Failure::Ambiguous(format!(
+                "{n} {noun} match your search terms ({}). Refine the terms to match a single one.",
+                matches.iter().map(|e| e.title()).join(", ")
+            )).into())
+        }
+        _ => {
+            Ok(dialoguer::Select::new()
+                .with_prompt(format!("Several {noun} match your search. Use arrow keys and Enter to select, or Esc to cancel"))
+                .items(&matches.iter().map(|entry| format!("{} ({}) - {}", entry.listed_title(), entry.language(), entry.summary())).collect_vec())
+                .interact_opt()?
+                .map(|idx| matches[idx].clone()))
+        }
+    }
+}
+
+pub(super) fn print_suggestions(terms: &[String], vocabulary: &[String]) {
+    let suggestions = terms.iter()
+        .map(|t| t.to_lowercase())
+        .filter(|t| !vocabulary.contains(t))
+        .flat_map(|t| fuzzy::suggestions(&t, vocabulary))
+        .unique()
+        .collect_vec();
+    if !suggestions.is_empty() {
+        println!("Did you mean: {}?", suggestions.join(", "));
+    }
+}
+
+fn plural(category: &hub_api::Category) -> String {
+    match category {
+        hub_api::Category::Library => "libraries".to_owned(),
+        _ => format!("{}s", category.to_string().to_lowercase()),
+    }
+}
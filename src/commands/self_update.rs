@@ -0,0 +1,185 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+
+use crate::{exit::Failure, github::GitHub, http, output, progress::Progress, settings, style, verify};
+use super::outdated::is_newer;
+use super::plugin::plugin_platform;
+
+pub(super) const RELEASE_REPO: &str = "VamshiReddy02/spin-hub-cli";
+pub(super) const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Subcommand, Debug)]
+pub enum SelfCommand {
+    Update(UpdateCommand),
+}
+
+impl SelfCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Update(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Update the Hub CLI to its latest release")]
+pub struct UpdateCommand {
+    #[clap(long, help = "Only say whether there is a newer release, exiting with code 10 if there is")]
+    check: bool,
+
+    #[clap(long, hide = true, env = "SPIN_HUB_RELEASE_REPO", default_value = RELEASE_REPO)]
+    repo: String,
+}
+
+pub(super) struct Release {
+    pub version: String,
+    assets: Vec<Asset>,
+}
+
+struct Asset {
+    name: String,
+    url: String,
+}
+
+impl UpdateCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let release = latest_release(&self.repo).await?;
+        if !is_newer(&release.version, VERSION) {
+            println!("hub {VERSION} is the latest release");
+            return Ok(());
+        }
+        if self.check {
+            println!("hub {} is available (this is {VERSION})", release.version);
+            return Err(Failure::Outdated(format!("A newer release of the Hub CLI, {}, is available", release.version)).into());
+        }
+
+        let exe = std::env::current_exe().context("Can't find the running executable")?;
+        // Spin would still list the old version, and replace ours on its next upgrade
+        if exe.components().any(|c| c.as_os_str() == "plugins") && exe.to_string_lossy().contains("spin") {
+            return Err(anyhow!("The Hub CLI was installed as a Spin plugin. Update it with `spin plugins upgrade hub` instead."));
+        }
+
+        let (os, arch) = plugin_platform();
+        let asset = release.asset(|name| name.contains(&format!("-{os}-{arch}")) && name.ends_with(".tar.gz"))
+            .ok_or_else(|| anyhow!("Release {} has no package for {os}/{arch}", release.version))?;
+
+        let client = http::client()?;
+        let progress = Progress::start(format!("Downloading {}", asset.name));
+        let package = http::download(&client, &asset.url).await?;
+        progress.finish();
+        let expected = release.checksum(&client, &asset.name).await?
+            .ok_or_else(|| Failure::Verification(format!("Release {} doesn't publish a checksum for {}, so it can't be checked", release.version, asset.name)))?;
+        verify::check_sha256(&package, &expected, &asset.name)?;
+
+        // Next to the executable, so the final rename doesn't cross filesystems
+        let dir = exe.parent().ok_or_else(|| anyhow!("Can't find the directory of {}", exe.display()))?;
+        let scratch = tempfile::Builder::new().prefix(".hub-update-").tempdir_in(dir)
+            .with_context(|| format!("Can't write to {}. You may need to run the update with more permissions.", dir.display()))?;
+        let package_path = scratch.path().join(&asset.name);
+        std::fs::write(&package_path, &package)?;
+        release.check_signature(&client, &asset.name, &package_path, scratch.path()).await?;
+
+        let new_exe = extract_executable(&package, scratch.path())?;
+        replace_executable(&new_exe, &exe)?;
+        output::print_outcome(&format!("Updated hub from {VERSION} to {}", release.version), &release.version);
+        Ok(())
+    }
+}
+
+pub(super) async fn latest_release(repo: &str) -> anyhow::Result<Release> {
+    let release = GitHub::public()?.get(&format!("repos/{repo}/releases/latest")).await
+        .with_context(|| format!("Failed to find the latest release of {repo}"))?;
+    let tag = release["tag_name"].as_str().ok_or_else(|| anyhow!("The latest release of {repo} has no tag"))?;
+    let assets = release["assets"].as_array().into_iter().flatten()
+        .filter_map(|a| Some(Asset {
+            name: a["name"].as_str()?.to_owned(),
+            url: a["browser_download_url"].as_str()?.to_owned(),
+        }))
+        .collect();
+    Ok(Release { version: tag.trim_start_matches('v').to_owned(), assets })
+}
+
+impl Release {
+    fn asset(&self, is_wanted: impl Fn(&str) -> bool) -> Option<&Asset> {
+        self.assets.iter().find(|a| is_wanted(&a.name))
+    }
+
+    // Releases publish either a `.sha256` file per package or one list of them all
+    async fn checksum(&self, client: &reqwest::Client, name: &str) -> anyhow::Result<Option<String>> {
+        if let Some(asset) = self.asset(|n| n == format!("{name}.sha256")) {
+            let text = String::from_utf8(http::download(client, &asset.url).await?)?;
+            return Ok(text.split_whitespace().next().map(|s| s.to_owned()));
+        }
+        if let Some(asset) = self.asset(|n| n == "checksums.txt") {
+            let text = String::from_utf8(http::download(client, &asset.url).await?)?;
+            return Ok(find_checksum(&text, name));
+        }
+        Ok(None)
+    }
+
+    async fn check_signature(&self, client: &reqwest::Client, name: &str, package: &Path, dir: &Path) -> anyhow::Result<()> {
+        let Some(signature) = self.asset(|n| n == format!("{name}.sig")) else {
+            return Ok(());
+        };
+        let Some(trust_root) = settings::get().trust_root.as_deref() else {
+            style::warning!("{name} is signed, but the signature can't be checked because no trust root is configured.");
+            return Ok(());
+        };
+        let signature_path = dir.join(&signature.name);
+        std::fs::write(&signature_path, http::download(client, &signature.url).await?)?;
+        verify::check_cosign_signature(package, &signature_path, trust_root).await
+            .with_context(|| format!("Can't verify the signature of {name}"))
+    }
+}
+
+// Lines are `<sha256>  <file name>`, as sha256sum writes them
+fn find_checksum(checksums: &str, name: &str) -> Option<String> {
+    checksums.lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, file)| file.trim().trim_start_matches('*') == name)
+        .map(|(checksum, _)| checksum.to_owned())
+}
+
+fn extract_executable(package: &[u8], dir: &Path) -> anyhow::Result<PathBuf> {
+    let dest = dir.join("package");
+    tar::Archive::new(flate2::read::GzDecoder::new(package))
+        .unpack(&dest)
+        .context("Failed to extract the release package")?;
+    let name = format!("hub{}", std::env::consts::EXE_SUFFIX);
+    let path = dest.join(&name);
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(anyhow!("The release package has no {name} executable"))
+    }
+}
+
+// A rename is atomic, so there's never a half-written executable. Windows
+// won't replace a running executable, but will rename it out of the way.
+fn replace_executable(new: &Path, exe: &Path) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(new, std::fs::Permissions::from_mode(0o755))?;
+    }
+    if cfg!(windows) {
+        let old = exe.with_extension("old.exe");
+        _ = std::fs::remove_file(&old);
+        std::fs::rename(exe, &old).with_context(|| format!("Failed to move {} aside", exe.display()))?;
+    }
+    std::fs::rename(new, exe).with_context(|| format!("Failed to replace {}", exe.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checksums_are_found_by_file_name() {
+        let checksums = "abc123  hub-v0.2.0-linux-amd64.tar.gz\ndef456 *hub-v0.2.0-macos-aarch64.tar.gz\n";
+        assert_eq!(Some("def456".to_owned()), find_checksum(checksums, "hub-v0.2.0-macos-aarch64.tar.gz"));
+        assert_eq!(None, find_checksum(checksums, "hub-v0.2.0-windows-amd64.tar.gz"));
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use clap::{Parser};
+use itertools::Itertools;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::{hub_api::IndexEntry, hub_entry::HubEntry, style, verify};
+
+const INDEX_PATH: &str = "/api/hub/get_list";
+const MAX_REQUEST_HEAD: usize = 64 * 1024;
+
+#[derive(Parser, Debug)]
+#[clap(about = "Serve a Hub index made from local files, to test the Hub CLI and entries against")]
+pub struct ServeCommand {
+    #[clap(long, value_name = "DIR", default_value = ".", help = "Directory of entry files (*.md) and index files (*.json) to serve")]
+    dir: PathBuf,
+
+    #[clap(long, default_value = "127.0.0.1", help = "Address to listen on")]
+    host: String,
+
+    #[clap(long, default_value_t = 3080, help = "Port to listen on")]
+    port: u16,
+}
+
+impl ServeCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        // Fail now rather than on the first request if the files are bad
+        let entries = load_entries(&self.dir)?;
+        let listener = tokio::net::TcpListener::bind((self.host.as_str(), self.port)).await
+            .with_context(|| format!("Can't listen on {}:{}", self.host, self.port))?;
+        let address = listener.local_addr()?;
+        println!("Serving {} entries from {} at http://{address}/", entries.len(), self.dir.display());
+        println!("Use it with e.g. `hub --hub-url http://{address}/ --refresh search`. Files are read again for each request. Press Ctrl+C to stop.");
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let dir = self.dir.clone();
+            tokio::spawn(async move {
+                if let Err(e) = handle(stream, &dir).await {
+                    style::warning!("failed to handle a request: {e:#}");
+                }
+            });
+        }
+    }
+}
+
+// The entries the Hub would list: entry files as `hub entry` works with, and
+// index files as `hub index export` writes
+fn load_entries(dir: &Path) -> anyhow::Result<Vec<IndexEntry>> {
+    let mut entries = vec![];
+    let files = std::fs::read_dir(dir).with_context(|| format!("Can't read {}", dir.display()))?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .sorted();
+    for path in files {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("md") => {
+                let id = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+                entries.push(HubEntry::load(&path)?.to_index_entry(&id)?);
+            }
+            Some("json") => {
+                let text = std::fs::read(&path).with_context(|| format!("Can't read {}", path.display()))?;
+                let index: Vec<IndexEntry> = serde_json::from_slice(&text)
+                    .with_context(|| format!("{} is not a Hub index", path.display()))?;
+                entries.extend(index);
+            }
+            _ => (),
+        }
+    }
+    Ok(entries)
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+}
+
+fn parse_request(head: &str) -> Option<Request> {
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_owned();
+    let target = request_line.next()?;
+    let path = target.split('?').next().unwrap_or(target).to_owned();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_owned()))
+        .collect();
+    Some(Request { method, path, headers })
+}
+
+// Only enough HTTP/1.1 for the Hub CLI: one request per connection
+async fn handle(mut stream: tokio::net::TcpStream, dir: &Path) -> anyhow::Result<()> {
+    let mut head = vec![];
+    let mut buffer = [0; 4096];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        head.extend_from_slice(&buffer[..read]);
+        if head.len() > MAX_REQUEST_HEAD {
+            return Err(anyhow!("request head is too large"));
+        }
+    }
+    let request = parse_request(&String::from_utf8_lossy(&head)).ok_or_else(|| anyhow!("malformed request"))?;
+
+    let (status, headers, body) = respond(&request, dir);
+    println!("{} {} {status}", request.method, request.path);
+    let reason = match status {
+        200 => "OK",
+        304 => "Not Modified",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let mut response = format!("HTTP/1.1 {status} {reason}\r\nContent-Length: {}\r\nConnection: close\r\n", body.len());
+    for (name, value) in headers {
+        response.push_str(&format!("{name}: {value}\r\n"));
+    }
+    response.push_str("\r\n");
+    stream.write_all(response.as_bytes()).await?;
+    if request.method != "HEAD" {
+        stream.write_all(&body).await?;
+    }
+    stream.shutdown().await?;
+    Ok(())
+}
+
+fn respond(request: &Request, dir: &Path) -> (u16, Vec<(&'static str, String)>, Vec<u8>) {
+    let text = |status, message: String| (status, vec![("Content-Type", "text/plain".to_owned())], message.into_bytes());
+    if request.method != "GET" && request.method != "HEAD" {
+        return text(405, "Only GET is supported\n".to_owned());
+    }
+    if request.path != INDEX_PATH {
+        return text(404, format!("Not found. The index is at {INDEX_PATH}\n"));
+    }
+
+    let body = match load_entries(dir).and_then(|entries| Ok(serde_json::to_vec(&entries)?)) {
+        Ok(body) => body,
+        Err(e) => {
+            style::warning!("can't serve the index: {e:#}");
+            return text(500, format!("{e:#}\n"));
+        }
+    };
+    // So the Hub CLI's cache revalidation can be tested too
+    let etag = format!("\"{}\"", verify::sha256(&body));
+    if request.headers.get("if-none-match") == Some(&etag) {
+        return (304, vec![("ETag", etag)], vec![]);
+    }
+    (200, vec![("Content-Type", "application/json".to_owned()), ("ETag", etag)], body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn requests_are_parsed() {
+        let request = parse_request("GET /api/hub/get_list?x=1 HTTP/1.1\r\nHost: localhost\r\nIf-None-Match: \"abc\"\r\n\r\n").unwrap();
+        assert_eq!("GET", request.method);
+        assert_eq!(INDEX_PATH, request.path);
+        assert_eq!(Some(&"\"abc\"".to_owned()), request.headers.get("if-none-match"));
+        assert!(parse_request("").is_none());
+    }
+}
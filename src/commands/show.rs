@@ -0,0 +1,97 @@
+use clap::{Parser};
+
+use crate::{hub_api, markdown, output, readme, settings, style};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Show full details of a Hub entry")]
+pub struct ShowCommand {
+    #[clap(name = "id", help = "The ID or title of the Hub entry to show")]
+    id: String,
+
+    #[clap(long, help = "Also show the README from the entry's repository")]
+    readme: bool,
+
+    #[clap(long, help = "Also list the published versions of the entry, which `hub new --entry-version` can use")]
+    versions: bool,
+}
+
+impl ShowCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let entries = hub_api::index().await?;
+        let entry = hub_api::find_entry(&entries, &self.id)?;
+        print_entry(entry);
+        if self.versions {
+            println!();
+            print_versions(entry);
+        }
+        if self.readme {
+            println!();
+            readme::print(entry.repo_url(), None).await?;
+        }
+        Ok(())
+    }
+}
+
+pub(super) fn print_entry(entry: &hub_api::IndexEntry) {
+    println!("{}", entry.title());
+    println!();
+    print_field("ID", entry.id());
+    print_field("Category", &entry.category().to_string());
+    print_field("Language", &entry.language().to_string());
+    print_field("Author", entry.author());
+    print_field("Tags", &entry.tags().join(", "));
+    print_field("Repository", entry.repo_url());
+    if entry.category() == hub_api::Category::Template {
+        print_field("Template ID", entry.template_id());
+        print_field("Trigger", &entry.trigger().unwrap_or_default());
+    }
+    if let Some(hub_url) = entry.hub_url(settings::get().hub_url()) {
+        print_field("Hub page", hub_url.as_str());
+    }
+    if let Some(artifacts) = entry.artifact_source() {
+        print_field("Artifacts", artifacts);
+    }
+    print_field("Needs plugins", &entry.required_plugins().join(", "));
+    if let Some(spin_version) = entry.spin_version() {
+        print_field("Spin version", spin_version);
+    }
+    if let Some(last_updated) = entry.last_updated() {
+        print_field("Last updated", last_updated);
+    }
+    if let Some(deprecation) = entry.deprecation() {
+        print_field("Deprecated", &style::attention(deprecation.message().unwrap_or("yes")));
+        print_field("Use instead", deprecation.replacement().unwrap_or_default());
+        print_field("End of life", deprecation.end_of_life().unwrap_or_default());
+    }
+    println!();
+    println!("{}", entry.summary());
+    if let Some(description) = entry.description() {
+        println!();
+        print!("{}", markdown::render(description));
+    }
+}
+
+fn print_versions(entry: &hub_api::IndexEntry) {
+    let versions = entry.versions();
+    if versions.is_empty() {
+        output::note!("{} has no earlier versions on the Hub, only the current one", entry.title());
+        return;
+    }
+    let mut table = output::table(settings::get().output);
+    table.set_header(["Version", "Released", "Spin version", "Source"]);
+    for version in versions {
+        table.add_row(output::single_line_row(vec![
+            version.version().to_owned(),
+            version.released().unwrap_or_default().to_owned(),
+            version.spin_version().or(entry.spin_version()).unwrap_or_default().to_owned(),
+            version.artifact_source().map(|s| s.to_owned()).unwrap_or_else(|| version.git_ref()),
+        ]));
+    }
+    println!("{table}");
+}
+
+fn print_field(name: &str, value: &str) {
+    if !value.is_empty() {
+        println!("{:<14}{value}", format!("{name}:"));
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use clap::{Parser, Subcommand};
+use itertools::Itertools;
+use spin_templates::{RunOptions, TemplateVariantInfo};
+
+use crate::{history, hub_api::{self, Category}, output};
+use super::{deps, hooks, names};
+use super::new::{check_compatibility, new_from_template, record_history, run_template, warn_if_deprecated, AppOptions, TemplateArgs, TemplateRef};
+
+const DEFAULT_BLUEPRINT_FILE: &str = "spinhub.yaml";
+
+#[derive(Subcommand, Debug)]
+pub enum StackCommand {
+    New(NewCommand),
+    Apply(ApplyCommand),
+    Init(InitCommand),
+}
+
+impl StackCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::New(cmd) => cmd.run().await,
+            Self::Apply(cmd) => cmd.run().await,
+            Self::Init(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Create an application from one template and add components to it from others")]
+pub struct NewCommand {
+    #[clap(name = "name", help = "Name of the application to create")]
+    name: Option<String>,
+
+    #[clap(long = "with", value_name = "TEMPLATE[=COMPONENT]", required = true, value_parser = parse_part, help = "A Hub template to use, by ID, optionally with the name of the component to add from it. The first creates the application, and the rest add components to it (can be repeated)")]
+    parts: Vec<Part>,
+
+    #[clap(short = 'o', long = "output", value_name = "PATH", help = "Directory to create the application in [default: the application name]")]
+    output: Option<PathBuf>,
+
+    #[clap(flatten)]
+    hooks: HookArgs,
+
+    #[clap(flatten)]
+    template_args: TemplateArgs,
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Create the application described by a blueprint file")]
+pub struct ApplyCommand {
+    #[clap(name = "file", default_value = DEFAULT_BLUEPRINT_FILE, help = "The blueprint file")]
+    file: PathBuf,
+
+    #[clap(long, help = "Name of the application to create [default: the name in the blueprint]")]
+    name: Option<String>,
+
+    #[clap(short = 'o', long = "output", value_name = "PATH", help = "Directory to create the application in [default: the application name]")]
+    output: Option<PathBuf>,
+
+    #[clap(flatten)]
+    hooks: HookArgs,
+
+    #[clap(flatten)]
+    template_args: TemplateArgs,
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Write a starter blueprint file to edit")]
+pub struct InitCommand {
+    #[clap(name = "file", default_value = DEFAULT_BLUEPRINT_FILE, help = "Where to write the blueprint")]
+    file: PathBuf,
+
+    #[clap(long, help = "Replace the file if it already exists")]
+    force: bool,
+}
+
+#[derive(clap::Args, Clone, Debug, Default)]
+struct HookArgs {
+    #[clap(long, help = "Run `spin build` in the new application")]
+    build: bool,
+
+    #[clap(long, help = "Make the new application a Git repository, with a first commit and a .gitignore for its languages")]
+    git: bool,
+
+    #[clap(long, value_name = "EDITOR", help = "Open the new application in this editor, e.g. 'code'")]
+    open: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Part {
+    template: String,
+    #[serde(default)]
+    component: Option<String>,
+    #[serde(default)]
+    git_ref: Option<String>,
+    #[serde(default)]
+    values: BTreeMap<String, String>,
+}
+
+// A file describing an application made from several templates
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Blueprint {
+    #[serde(default)]
+    name: Option<String>,
+    // Values for every template, which a template's own values override
+    #[serde(default)]
+    values: BTreeMap<String, String>,
+    templates: Vec<Part>,
+}
+
+const STARTER_BLUEPRINT: &str = r#"# A blueprint for `hub stack apply`. The first template creates the
+# application, and each of the others adds a component to it.
+
+# The application name, unless `hub stack apply --name` gives another
+name: my-app
+
+# Values for every template. `--value` on the command line overrides them.
+values:
+  authors: Your Name <you@example.com>
+
+templates:
+  - template: http-rust
+    values:
+      project-description: The application's API
+  - template: static-fileserver
+    component: web
+    values:
+      http-path: /static/...
+"#;
+
+fn parse_part(value: &str) -> Result<Part, String> {
+    let (template, component) = match value.split_once('=') {
+        Some((template, component)) => (template, Some(component.trim().to_owned())),
+        None => (value, None),
+    };
+    let template = template.trim();
+    if template.is_empty() || component.as_ref().is_some_and(|c| c.is_empty()) {
+        return Err(format!("'{value}' should be a template ID, or TEMPLATE=COMPONENT"));
+    }
+    Ok(Part { template: template.to_owned(), component, git_ref: None, values: BTreeMap::new() })
+}
+
+impl NewCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let name = names::resolve_name(self.name.as_deref(), "application", self.template_args.no_prompt)?;
+        create_stack(&name, self.output.as_deref(), &self.parts, &self.template_args, &self.hooks).await
+    }
+}
+
+impl ApplyCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let blueprint = load_blueprint(&self.file)?;
+        let name = names::resolve_name(self.name.as_deref().or(blueprint.name.as_deref()), "application", self.template_args.no_prompt)?;
+
+        // Values on the command line take precedence over the blueprint's
+        let parts = blueprint.templates.iter()
+            .map(|part| {
+                let mut values = blueprint.values.clone();
+                values.extend(part.values.clone());
+                Part { values, ..part.clone() }
+            })
+            .collect_vec();
+        create_stack(&name, self.output.as_deref(), &parts, &self.template_args, &self.hooks).await
+    }
+}
+
+impl InitCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        if self.file.exists() && !self.force {
+            return Err(anyhow!("{} already exists. Use --force to replace it.", self.file.display()));
+        }
+        std::fs::write(&self.file, STARTER_BLUEPRINT)
+            .with_context(|| format!("Failed to write {}", self.file.display()))?;
+        println!("Wrote {}. Edit it to choose templates, then run `hub stack apply {}`", self.file.display(), self.file.display());
+        Ok(())
+    }
+}
+
+fn load_blueprint(path: &Path) -> anyhow::Result<Blueprint> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read blueprint {}", path.display()))?;
+    let blueprint: Blueprint = serde_yaml::from_str(&text)
+        .with_context(|| format!("Blueprint {} is not valid", path.display()))?;
+    if blueprint.templates.is_empty() {
+        return Err(anyhow!("Blueprint {} doesn't list any templates", path.display()));
+    }
+    Ok(blueprint)
+}
+
+async fn create_stack(name: &str, output: Option<&Path>, parts: &[Part], args: &TemplateArgs, hook_args: &HookArgs) -> anyhow::Result<()> {
+    let (entries, ()) = tokio::join!(hub_api::index(), crate::spin::preload_version());
+    let entries = entries?;
+
+    // Find all the templates before creating anything, so a mistyped ID
+    // doesn't leave a half-made application
+    let mut templates = vec![];
+    for part in parts {
+        let entry = hub_api::find_entry(&entries, &part.template)?;
+        if entry.category() != Category::Template {
+            return Err(anyhow!("{} is a {}, not a template", entry.title(), entry.category()));
+        }
+        warn_if_deprecated(entry);
+        check_compatibility(entry, args)?;
+        templates.push((entry, part));
+    }
+
+    let output_path = output.map(|p| p.to_owned()).unwrap_or_else(|| PathBuf::from(name));
+    let ((first, first_part), rest) = templates.split_first().ok_or_else(|| anyhow!("No templates to use"))?;
+
+    output::note!("Creating {name} from {}", first.title());
+    let app = AppOptions {
+        name: Some(name.to_owned()),
+        output: Some(output_path.clone()),
+        ..Default::default()
+    };
+    new_from_template(&TemplateRef::from_entry(first), &app, &part_args(first_part, args)).await?;
+
+    // Spin's add-component templates merge their component into spin.toml
+    let manifest_path = output_path.join("spin.toml");
+    let mut component_names = vec![name.to_owned()];
+    for (entry, part) in rest {
+        let component = part.component.clone()
+            .unwrap_or_else(|| unique_name(entry.id(), &component_names));
+        output::note!();
+        output::note!("Adding {component} from {}", entry.title());
+
+        let template = TemplateRef::from_entry(entry);
+        let args = part_args(part, args);
+        let options = RunOptions {
+            variant: TemplateVariantInfo::AddComponent { manifest_path: manifest_path.clone() },
+            name: component.clone(),
+            output_path: PathBuf::from(&component),
+            values: args.values()?,
+            accept_defaults: args.no_prompt || args.accept_defaults,
+        };
+        let values = run_template(&template, options, &args).await
+            .map_err(|e| anyhow!("Failed to add {component} from {} to {}: {e:#}", entry.title(), output_path.display()))?;
+        deps::check_required_plugins(&template, &args).await;
+        record_history(history::Kind::Add, &template, args.git_ref.clone(), &component, values.into_iter().collect());
+        component_names.push(component);
+    }
+
+    // With --quiet, creating the first part has already printed the path
+    output::note!();
+    output::note!("Created {name} in {} with components: {}", output_path.display(), component_names.iter().join(", "));
+
+    let hooks = AppOptions {
+        build: hook_args.build,
+        git: hook_args.git,
+        open: hook_args.open.clone(),
+        ..Default::default()
+    };
+    hooks::run_post_create(&output_path, &TemplateRef::from_entry(first), &hooks).await
+}
+
+// The part's own values and Git ref, unless the command line gives others
+fn part_args(part: &Part, args: &TemplateArgs) -> TemplateArgs {
+    let mut part_args = args.clone();
+    part_args.values = part.values.clone().into_iter().chain(args.values.iter().cloned()).collect();
+    part_args.git_ref = args.git_ref.clone().or_else(|| part.git_ref.clone());
+    part_args
+}
+
+// Adding the same template twice needs a different name each time
+fn unique_name(base: &str, taken: &[String]) -> String {
+    (1..)
+        .map(|n| if n == 1 { base.to_owned() } else { format!("{base}-{n}") })
+        .find(|name| !taken.contains(name))
+        .expect("some name is free")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn repeated_templates_get_numbered_component_names() {
+        let taken = vec!["myapp".to_owned(), "kv-worker".to_owned()];
+        assert_eq!("static-fileserver", unique_name("static-fileserver", &taken));
+        assert_eq!("kv-worker-2", unique_name("kv-worker", &taken));
+    }
+
+    #[test]
+    fn starter_blueprint_is_valid() {
+        let blueprint: Blueprint = serde_yaml::from_str(STARTER_BLUEPRINT).unwrap();
+        assert_eq!(Some("my-app".to_owned()), blueprint.name);
+        assert_eq!(2, blueprint.templates.len());
+        assert_eq!(Some("web".to_owned()), blueprint.templates[1].component);
+    }
+}
@@ -0,0 +1,89 @@
+use clap::{Parser};
+use itertools::Itertools;
+
+use crate::{hub_api, output::{self, OutputFormat}, settings, stars, style};
+use super::fields::{Field, FieldArgs};
+
+#[derive(Parser, Debug)]
+#[clap(about = "Star a Hub entry, so you can find it again with `stars` or `search --starred`")]
+pub struct StarCommand {
+    #[clap(name = "id", help = "The ID or title of the Hub entry to star")]
+    id: String,
+}
+
+impl StarCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let entries = hub_api::index().await?;
+        let entry = hub_api::find_entry(&entries, &self.id)?;
+        if stars::star(entry.id())? {
+            println!("Starred {}", entry.title());
+        } else {
+            println!("{} is already starred", entry.title());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Remove the star from a Hub entry")]
+pub struct UnstarCommand {
+    #[clap(name = "id", help = "The ID or title of the Hub entry to unstar")]
+    id: String,
+}
+
+impl UnstarCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        // Entries that have left the Hub can still be unstarred by ID
+        let entries = hub_api::cached_index().unwrap_or_default();
+        let id = hub_api::find_entry(&entries, &self.id).map(|e| e.id()).unwrap_or(&self.id);
+        if stars::unstar(id)? {
+            println!("Unstarred {id}");
+        } else {
+            println!("{id} is not starred");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "List the Hub entries you have starred")]
+pub struct StarsCommand {
+    #[clap(short = 'o', long = "output", alias = "format", value_enum, env = "SPIN_HUB_OUTPUT")]
+    output: Option<OutputFormat>,
+
+    #[clap(flatten)]
+    fields: FieldArgs,
+}
+
+impl StarsCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let starred = stars::starred()?;
+        if starred.is_empty() {
+            output::note!("You haven't starred anything. Use `hub star <id>` to star a Hub entry.");
+            return Ok(());
+        }
+
+        let entries = hub_api::index().await?;
+        let (found, missing): (Vec<_>, Vec<_>) = starred.iter()
+            .map(|id| entries.iter().find(|e| e.id() == id).ok_or(id))
+            .partition_result();
+
+        match settings::get().output_format(self.output) {
+            OutputFormat::Json => output::print_json(&found)?,
+            OutputFormat::Jsonl => output::print_json_lines(&found)?,
+            format => {
+                let fields = self.fields.or_default(&[Field::Id, Field::Title, Field::Category, Field::Language, Field::Summary]);
+                let mut table = output::table(format);
+                table.set_header(fields.header());
+                for entry in found.iter().sorted_by_key(|e| e.title()) {
+                    table.add_row(output::single_line_row(fields.row(entry)));
+                }
+                println!("{table}");
+            }
+        }
+        for id in missing {
+            style::warning!("{id} is no longer on the Hub. Use `hub unstar {id}` to remove it.");
+        }
+        Ok(())
+    }
+}
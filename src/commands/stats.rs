@@ -0,0 +1,154 @@
+use std::time::SystemTime;
+
+use clap::{Parser};
+
+use crate::{hub_api, output::{self, OutputFormat}, settings};
+use super::facets::{count_values, FacetCount};
+
+// How many authors and tags to show in a table; JSON has all of them
+const TOP: usize = 10;
+
+#[derive(Parser, Debug)]
+#[clap(about = "Summarise what is on the Hub")]
+pub struct StatsCommand {
+    #[clap(short = 'o', long = "output", alias = "format", value_enum, env = "SPIN_HUB_OUTPUT")]
+    output: Option<OutputFormat>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Stats {
+    entries: usize,
+    categories: Vec<FacetCount>,
+    languages: Vec<FacetCount>,
+    authors: Vec<FacetCount>,
+    tags: Vec<FacetCount>,
+    freshness: Freshness,
+}
+
+// The day counts overlap: an entry updated last week is in all three
+#[derive(Default, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Freshness {
+    last_30_days: usize,
+    last_90_days: usize,
+    last_365_days: usize,
+    older: usize,
+    unknown: usize,
+}
+
+impl StatsCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let entries = hub_api::index().await?;
+        let stats = Stats {
+            entries: entries.len(),
+            categories: count_values(entries.iter(), |e| vec![e.category().to_string()]),
+            languages: count_values(entries.iter(), |e| vec![e.language().to_string()]),
+            authors: count_values(entries.iter(), |e| vec![e.author().to_owned()]),
+            tags: count_values(entries.iter(), |e| e.tags()),
+            freshness: freshness(&entries, today()),
+        };
+
+        match settings::get().output_format(self.output) {
+            OutputFormat::Json => output::print_json(&stats),
+            OutputFormat::Jsonl => output::print_json_lines([&stats]),
+            format => {
+                print_stats(&stats, format);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn print_stats(stats: &Stats, format: OutputFormat) {
+    println!("{} entries on the Hub", stats.entries);
+    print_counts("Category", &stats.categories, None, format);
+    print_counts("Language", &stats.languages, None, format);
+    print_counts("Author", &stats.authors, Some(TOP), format);
+    print_counts("Tag", &stats.tags, Some(TOP), format);
+
+    let freshness = &stats.freshness;
+    println!();
+    println!("Updated in the last 30 days:  {}", freshness.last_30_days);
+    println!("Updated in the last 90 days:  {}", freshness.last_90_days);
+    println!("Updated in the last 365 days: {}", freshness.last_365_days);
+    println!("Not updated for over a year:  {}", freshness.older);
+    if freshness.unknown > 0 {
+        println!("Not saying when updated:      {}", freshness.unknown);
+    }
+}
+
+fn print_counts(name: &str, counts: &[FacetCount], top: Option<usize>, format: OutputFormat) {
+    println!();
+    let shown = top.unwrap_or(counts.len()).min(counts.len());
+    let mut table = output::table(format);
+    table.set_header([name, "Entries"]);
+    for count in &counts[..shown] {
+        table.add_row(output::single_line_row(vec![count.value.clone(), count.count.to_string()]));
+    }
+    println!("{table}");
+    if shown < counts.len() {
+        println!("...and {} more", counts.len() - shown);
+    }
+}
+
+fn freshness(entries: &[hub_api::IndexEntry], today: i64) -> Freshness {
+    let mut freshness = Freshness::default();
+    for entry in entries {
+        let Some(updated) = entry.last_updated().and_then(days_since_epoch) else {
+            freshness.unknown += 1;
+            continue;
+        };
+        let age = today - updated;
+        if age <= 30 {
+            freshness.last_30_days += 1;
+        }
+        if age <= 90 {
+            freshness.last_90_days += 1;
+        }
+        if age <= 365 {
+            freshness.last_365_days += 1;
+        } else {
+            freshness.older += 1;
+        }
+    }
+    freshness
+}
+
+fn today() -> i64 {
+    let secs = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+    (secs / 86400) as i64
+}
+
+// Hub dates start with YYYY-MM-DD, possibly followed by a time
+fn days_since_epoch(date: &str) -> Option<i64> {
+    let mut parts = date.get(..10)?.split('-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // Howard Hinnant's days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146097 + day_of_era - 719468)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dates_are_converted_to_days() {
+        assert_eq!(Some(0), days_since_epoch("1970-01-01"));
+        assert_eq!(Some(19671), days_since_epoch("2023-11-10T12:00:00Z"));
+        assert_eq!(Some(11016), days_since_epoch("2000-02-29"));
+        assert_eq!(None, days_since_epoch("last week"));
+        assert_eq!(None, days_since_epoch("2023-13-01"));
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use itertools::Itertools;
+
+use crate::output;
+
+// Files under `dir`, relative to it, in path order
+pub(super) fn list_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    collect_files(dir, Path::new(""), &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files(dir: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn print_tree(root: &str, files: &[PathBuf]) {
+    println!("{root}");
+    for line in tree_lines(files) {
+        println!("{line}");
+    }
+}
+
+fn tree_lines(files: &[PathBuf]) -> Vec<String> {
+    let mut lines = vec![];
+    add_tree_lines(files, 0, "", &mut lines);
+    lines
+}
+
+// `files` all share their first `depth` components
+fn add_tree_lines(files: &[PathBuf], depth: usize, prefix: &str, lines: &mut Vec<String>) {
+    let groups = files.iter()
+        .filter_map(|f| f.components().nth(depth).map(|c| (c.as_os_str().to_string_lossy().into_owned(), f)))
+        .group_by(|(name, _)| name.clone());
+    let groups = groups.into_iter()
+        .map(|(name, group)| (name, group.map(|(_, f)| f.clone()).collect_vec()))
+        .collect_vec();
+
+    for (idx, (name, children)) in groups.iter().enumerate() {
+        let last = idx == groups.len() - 1;
+        lines.push(format!("{prefix}{}{name}", if last { "└── " } else { "├── " }));
+        let is_dir = children.iter().any(|f| f.components().count() > depth + 1);
+        if is_dir {
+            let child_prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            add_tree_lines(children, depth + 1, &child_prefix, lines);
+        }
+    }
+}
+
+pub(super) fn print_next_steps(dir: &Path) {
+    let manifest = std::fs::read_to_string(dir.join("spin.toml")).ok();
+    let needs_build = manifest.as_deref().map(has_build_command).unwrap_or(true);
+
+    output::note!();
+    output::note!("Next steps:");
+    if dir != Path::new(".") {
+        output::note!("    cd {}", dir.display());
+    }
+    if needs_build {
+        output::note!("    spin build");
+    }
+    output::note!("    spin up");
+}
+
+// Handles both the component array of v1 manifests and the component table of v2
+fn has_build_command(manifest: &str) -> bool {
+    let Ok(manifest) = manifest.parse::<toml::Table>() else {
+        return true;
+    };
+    let components = match manifest.get("component") {
+        Some(toml::Value::Array(components)) => components.iter().collect_vec(),
+        Some(toml::Value::Table(components)) => components.values().collect_vec(),
+        _ => vec![],
+    };
+    components.iter().any(|c| c.get("build").and_then(|b| b.get("command")).is_some())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tree_groups_files_by_directory() {
+        let files = ["Cargo.toml", "spin.toml", "src/lib.rs", "src/util/mod.rs"].map(PathBuf::from);
+        let expected = vec![
+            "├── Cargo.toml",
+            "├── spin.toml",
+            "└── src",
+            "    ├── lib.rs",
+            "    └── util",
+            "        └── mod.rs",
+        ];
+        assert_eq!(expected, tree_lines(&files));
+    }
+
+    #[test]
+    fn build_command_is_found_in_v1_and_v2_manifests() {
+        assert!(has_build_command("[component.app.build]\ncommand = \"cargo build\""));
+        assert!(has_build_command("[[component]]\nid = \"app\"\n[component.build]\ncommand = \"npm run build\""));
+        assert!(!has_build_command("[component.app]\nsource = \"app.wasm\""));
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+
+use crate::{history::now, output::{self, OutputFormat}, settings, telemetry};
+use super::history::format_age;
+
+#[derive(Subcommand, Debug)]
+pub enum TelemetryCommand {
+    Enable(EnableCommand),
+    Disable(DisableCommand),
+    Status(StatusCommand),
+    Show(ShowCommand),
+}
+
+impl TelemetryCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Enable(cmd) => cmd.run().await,
+            Self::Disable(cmd) => cmd.run().await,
+            Self::Status(cmd) => cmd.run().await,
+            Self::Show(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Send anonymous reports of which commands are used and how they end")]
+pub struct EnableCommand {}
+
+impl EnableCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let mut state = telemetry::load()?;
+        state.enabled = true;
+        telemetry::save(&state)?;
+        println!("Telemetry is enabled. Thank you!");
+        println!("Only the command names, how they ended, the Hub CLI version and your OS are recorded, never arguments or paths.");
+        println!("Reports are sent to {} in batches. Use `hub telemetry show` to see what will be sent.", telemetry::endpoint()?);
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Stop sending telemetry, and delete anything not yet sent")]
+pub struct DisableCommand {}
+
+impl DisableCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let mut state = telemetry::load()?;
+        let discarded = state.events.len();
+        state.enabled = false;
+        state.events.clear();
+        telemetry::save(&state)?;
+        println!("Telemetry is disabled. Deleted {discarded} unsent events.");
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Show whether telemetry is enabled, and what is waiting to be sent")]
+pub struct StatusCommand {}
+
+impl StatusCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let state = telemetry::load()?;
+        let status = match (state.enabled, telemetry::is_suppressed()) {
+            (false, _) => "disabled",
+            (true, true) => "enabled, but turned off by SPIN_HUB_NO_TELEMETRY or DO_NOT_TRACK",
+            (true, false) => "enabled",
+        };
+        println!("Telemetry is {status}");
+        println!("Unsent events: {}", state.events.len());
+        if let Some(last_sent) = state.last_sent {
+            println!("Last sent: {}", format_age(Duration::from_secs(now().saturating_sub(last_sent))));
+        }
+        println!("Reports go to: {}", telemetry::endpoint()?);
+        println!("Kept in: {}", telemetry::path()?.display());
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Show the events waiting to be sent, exactly as they will be")]
+pub struct ShowCommand {
+    #[clap(short = 'o', long = "output", alias = "format", value_enum, env = "SPIN_HUB_OUTPUT")]
+    output: Option<OutputFormat>,
+}
+
+impl ShowCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let events = telemetry::load()?.events;
+
+        let format = settings::get().output_format(self.output);
+        if format == OutputFormat::Json {
+            return output::print_json(&events);
+        }
+        if format == OutputFormat::Jsonl {
+            return output::print_json_lines(&events);
+        }
+        if events.is_empty() {
+            output::note!("No telemetry is waiting to be sent");
+            return Ok(());
+        }
+
+        let mut table = output::table(format);
+        table.set_header(["Day", "Command", "Outcome", "Version", "OS", "Arch", "Sent to"]);
+        for event in &events {
+            table.add_row(output::single_line_row(vec![
+                format_day(event.time),
+                event.command.clone(),
+                event.outcome.clone(),
+                event.version.clone(),
+                event.os.clone(),
+                event.arch.clone(),
+                event.endpoint.clone().unwrap_or_default(),
+            ]));
+        }
+        println!("{table}");
+        Ok(())
+    }
+}
+
+// Events only record the day
+fn format_day(time: u64) -> String {
+    let day = 24 * 60 * 60;
+    match (now() / day).saturating_sub(time / day) {
+        0 => "today".to_owned(),
+        1 => "yesterday".to_owned(),
+        days => format!("{days} days ago"),
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::anyhow;
+use clap::{Parser, Subcommand};
+use itertools::Itertools;
+
+use crate::{installed, output, style};
+
+#[derive(Subcommand, Debug)]
+pub enum TemplatesCommand {
+    Installed(InstalledCommand),
+    Uninstall(UninstallCommand),
+}
+
+impl TemplatesCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Installed(cmd) => cmd.run().await,
+            Self::Uninstall(cmd) => cmd.run().await,
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "List templates installed from the Hub with --keep")]
+pub struct InstalledCommand {}
+
+impl InstalledCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let manager = spin_templates::TemplateManager::try_default()?;
+        let templates = installed::templates()?.into_iter()
+            .sorted_by(|t1, t2| t1.id.cmp(&t2.id))
+            .collect_vec();
+
+        if templates.is_empty() {
+            output::note!("No templates have been installed from the Hub. Use --keep with new or add to install one.");
+            return Ok(());
+        }
+
+        let mut table = output::table(crate::settings::get().output);
+        table.set_header(["ID", "Repository", "Ref", "Status"]);
+        for template in templates {
+            // The user may have removed it with `spin templates uninstall`
+            let status = if manager.get(&template.id)?.is_some() { "installed".to_owned() } else { style::attention("missing") };
+            table.add_row(output::single_line_row(vec![
+                template.id,
+                template.repo,
+                template.git_ref.unwrap_or_else(|| "(default branch)".to_owned()),
+                status,
+            ]));
+        }
+        println!("{table}");
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Uninstall a template that was installed from the Hub")]
+pub struct UninstallCommand {
+    #[clap(name = "id", help = "The ID of the template to uninstall")]
+    id: String,
+}
+
+impl UninstallCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let id = &self.id;
+        if !installed::templates()?.iter().any(|t| &t.id == id) {
+            return Err(anyhow!("{id} was not installed by the Hub CLI. Use `spin templates uninstall {id}` to remove other templates."));
+        }
+
+        let manager = spin_templates::TemplateManager::try_default()?;
+        if manager.get(id)?.is_some() {
+            manager.uninstall(id).await?;
+        }
+        installed::forget_template(id)?;
+        println!("Uninstalled {id}");
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use anyhow::anyhow;
+use clap::{Parser, Subcommand};
+
+use crate::trust;
+
+#[derive(Subcommand, Debug)]
+pub enum TrustCommand {
+    Add(AddCommand),
+    Block(BlockCommand),
+    Remove(RemoveCommand),
+    List(ListCommand),
+}
+
+impl TrustCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Add(cmd) => cmd.run(),
+            Self::Block(cmd) => cmd.run(),
+            Self::Remove(cmd) => cmd.run(),
+            Self::List(cmd) => cmd.run(),
+        }
+    }
+}
+
+const PATTERN_HELP: &str = "An author, repository host (github.com) or organisation (github.com/fermyon)";
+
+#[derive(Parser, Debug)]
+#[clap(about = "Trust templates and plugins from an author or organisation")]
+pub struct AddCommand {
+    #[clap(help = PATTERN_HELP)]
+    pattern: String,
+}
+
+impl AddCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        trust::update(|trusted, blocked| {
+            remove_pattern(blocked, &self.pattern);
+            add_pattern(trusted, &self.pattern);
+        })?;
+        println!("Trusted {}", self.pattern);
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Refuse templates and plugins from an author or organisation")]
+pub struct BlockCommand {
+    #[clap(help = PATTERN_HELP)]
+    pattern: String,
+}
+
+impl BlockCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        trust::update(|trusted, blocked| {
+            remove_pattern(trusted, &self.pattern);
+            add_pattern(blocked, &self.pattern);
+        })?;
+        println!("Blocked {}", self.pattern);
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "Remove an author or organisation from the trusted and blocked lists")]
+pub struct RemoveCommand {
+    pattern: String,
+}
+
+impl RemoveCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        let (trusted, blocked) = trust::lists()?;
+        let listed = |patterns: &[String]| patterns.iter().any(|p| p.eq_ignore_ascii_case(&self.pattern));
+        if !listed(&trusted) && !listed(&blocked) {
+            return Err(anyhow!("{} is not in the trusted or blocked lists", self.pattern));
+        }
+        trust::update(|trusted, blocked| {
+            remove_pattern(trusted, &self.pattern);
+            remove_pattern(blocked, &self.pattern);
+        })?;
+        println!("Removed {}", self.pattern);
+        Ok(())
+    }
+}
+
+#[derive(Parser, Debug)]
+#[clap(about = "List trusted and blocked authors and organisations")]
+pub struct ListCommand {}
+
+impl ListCommand {
+    fn run(&self) -> anyhow::Result<()> {
+        let (trusted, blocked) = trust::lists()?;
+        if trusted.is_empty() {
+            println!("Trusted: (everyone not blocked)");
+        } else {
+            println!("Trusted: {}", trusted.join(", "));
+        }
+        if !blocked.is_empty() {
+            println!("Blocked: {}", blocked.join(", "));
+        }
+        Ok(())
+    }
+}
+
+fn add_pattern(patterns: &mut Vec<String>, pattern: &str) {
+    if !patterns.iter().any(|p| p.eq_ignore_ascii_case(pattern)) {
+        patterns.push(pattern.to_owned());
+    }
+}
+
+fn remove_pattern(patterns: &mut Vec<String>, pattern: &str) {
+    patterns.retain(|p| !p.eq_ignore_ascii_case(pattern));
+}
@@ -0,0 +1,105 @@
+use std::path::PathBuf;
+
+use clap::{Parser};
+
+use crate::{provenance::Provenance, style};
+use super::new::{pinned_commit, TemplateRef};
+use super::render;
+
+#[derive(Parser, Debug)]
+#[clap(about = "Update an application with changes to the template it was created from")]
+pub struct UpgradeCommand {
+    #[clap(name = "dir", default_value = ".", help = "The application directory")]
+    dir: PathBuf,
+
+    #[clap(long = "git-ref", alias = "branch", value_name = "REF", help = "Branch, tag or commit of the template repository to upgrade to [default: the default branch]")]
+    git_ref: Option<String>,
+
+    #[clap(long, help = "Write the changes to the application, instead of only showing them")]
+    apply: bool,
+
+    #[clap(long, requires = "apply", help = "With --apply, also replace files you've changed since the application was created or last upgraded")]
+    force: bool,
+}
+
+impl UpgradeCommand {
+    pub async fn run(&self) -> anyhow::Result<()> {
+        let mut provenance = Provenance::read(&self.dir)?;
+
+        let template = render::provenance_template(&provenance).await?;
+
+        // Rendered at an exact commit, so the next upgrade can tell the user's edits from the template's
+        let commit = pinned_commit(&template, self.git_ref.as_deref()).await.ok();
+        let values: std::collections::HashMap<_, _> = provenance.values.clone().into_iter().collect();
+        let rendered = render::render(&template, &provenance.name, values.clone(), commit.clone().or(self.git_ref.clone())).await?;
+        let changes = render::changes(&rendered, &self.dir)?;
+
+        if changes.is_empty() {
+            println!("{} is up to date with the template", self.dir.display());
+            return Ok(());
+        }
+
+        // What the application was created from, to compare the user's files with
+        let base = match &provenance.commit {
+            Some(commit) => {
+                let original = TemplateRef {
+                    entry: provenance.entry.clone(),
+                    author: None,
+                    repo: provenance.repo.clone(),
+                    id: provenance.template_id.clone(),
+                    digest: None,
+                };
+                match render::render(&original, &provenance.name, values, Some(commit.clone())).await {
+                    Ok(base) => Some(base),
+                    Err(e) => {
+                        style::warning!("Can't create the application as it was at {commit}, so files you may have changed are left alone: {e:#}");
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let mut edited = vec![];
+        for change in &changes {
+            if render::is_user_edited(change, base.as_ref(), &self.dir)? {
+                edited.push(change.path.clone());
+            }
+        }
+
+        render::print_changes(&changes);
+        println!();
+        render::print_change_summary(&changes);
+
+        if !self.apply {
+            println!("Run with --apply to make these changes.");
+            return Ok(());
+        }
+
+        let applied = changes.iter()
+            .filter(|c| self.force || !edited.contains(&c.path))
+            .cloned()
+            .collect::<Vec<_>>();
+        render::apply_changes(&rendered, &self.dir, &applied)?;
+        provenance.repo = template.repo;
+        provenance.template_id = template.id;
+        provenance.git_ref = self.git_ref.clone();
+        provenance.commit = commit;
+        provenance.write(&self.dir)?;
+        println!("Upgraded {}", self.dir.display());
+
+        if !self.force && !edited.is_empty() {
+            println!();
+            if base.is_none() {
+                println!("These files were left alone, as there's no record of how the template created them, so any changes you made to them would be lost:");
+            } else {
+                println!("These files were left alone, as you've changed them since the template created them:");
+            }
+            for path in &edited {
+                println!("    {}", path.display());
+            }
+            println!("Make the changes shown above to them yourself, or run again with --force to replace them.");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+
+pub fn parse_value(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_owned(), value.to_owned())),
+        _ => Err(format!("'{value}' should be of the form KEY=VALUE")),
+    }
+}
+
+pub fn load_values_file(path: &Path) -> Result<HashMap<String, String>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read values file {}", path.display()))?;
+
+    let is_json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let values: HashMap<String, serde_json::Value> = if is_json {
+        serde_json::from_str(&text)
+            .with_context(|| format!("Values file {} is not a valid JSON object", path.display()))?
+    } else {
+        toml::from_str(&text)
+            .with_context(|| format!("Values file {} is not a valid TOML table", path.display()))?
+    };
+
+    values.into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => s,
+                serde_json::Value::Bool(b) => b.to_string(),
+                serde_json::Value::Number(n) => n.to_string(),
+                _ => return Err(anyhow!("Value '{key}' in {} must be a string, number or boolean", path.display())),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+// Variables such as PREFIX_PROJECT_DESCRIPTION set the value project-description,
+// as template values are kebab-case and environment variables can't be
+pub fn values_from_env(prefix: &str, vars: impl Iterator<Item = (String, String)>) -> Result<HashMap<String, String>> {
+    if prefix.is_empty() {
+        return Err(anyhow!("--values-from-env needs a prefix, such as HUB_VALUE_"));
+    }
+    Ok(vars
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(prefix)?;
+            (!key.is_empty()).then(|| (key.to_lowercase().replace('_', "-"), value))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn values_are_parsed_from_key_value_pairs() {
+        assert_eq!(("color".to_owned(), "blue".to_owned()), parse_value("color=blue").unwrap());
+        assert_eq!(("query".to_owned(), "a=b".to_owned()), parse_value("query=a=b").unwrap());
+        assert_eq!(("empty".to_owned(), "".to_owned()), parse_value("empty=").unwrap());
+        assert!(parse_value("novalue").is_err());
+        assert!(parse_value("=value").is_err());
+    }
+
+    #[test]
+    fn values_files_can_be_toml_or_json() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let toml_path = dir.path().join("values.toml");
+        std::fs::write(&toml_path, "project-description = \"My app\"\nport = 3000\n").unwrap();
+        let values = load_values_file(&toml_path).unwrap();
+        assert_eq!("My app", values["project-description"]);
+        assert_eq!("3000", values["port"]);
+
+        let json_path = dir.path().join("values.json");
+        std::fs::write(&json_path, r#"{ "enabled": true }"#).unwrap();
+        let values = load_values_file(&json_path).unwrap();
+        assert_eq!("true", values["enabled"]);
+    }
+
+    #[test]
+    fn env_vars_map_onto_kebab_case_values() {
+        let vars = [
+            ("CI_VALUE_PROJECT_DESCRIPTION", "My app"),
+            ("CI_VALUE_", "ignored"),
+            ("CI_OTHER", "ignored"),
+            ("HOME", "/home/me"),
+        ].into_iter().map(|(k, v)| (k.to_owned(), v.to_owned()));
+        let values = values_from_env("CI_VALUE_", vars).unwrap();
+        assert_eq!(1, values.len());
+        assert_eq!("My app", values["project-description"]);
+        assert!(values_from_env("", std::iter::empty()).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use clap::ValueEnum;
+
+use crate::output::{ColorMode, OutputFormat};
+
+pub const KEYS: &[&str] = &["hub-url", "mirrors", "output", "default-terms", "color", "cache-ttl", "retries", "retry-delay", "trust-root", "trusted", "blocked", "git-credentials", "git-ssh-hosts", "github-token", "pager", "update-check", "update-check-hours"];
+
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hub_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<OutputFormat>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub default_terms: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_delay: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trust_root: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocked: Vec<String>,
+    // HOST=TOKEN pairs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub git_credentials: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub git_ssh_hosts: Vec<String>,
+    // Used for GitHub API calls, instead of one stored with `hub login --github`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+    // `cat` turns paging off
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pager: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_check: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_check_hours: Option<u64>,
+    // Named sets of settings that override the ones above, chosen with --profile
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Config>,
+}
+
+impl Config {
+    // Settings in the profile override the top-level ones
+    pub fn with_profile(mut self, name: &str) -> anyhow::Result<Self> {
+        let profile = self.profiles.remove(name).ok_or_else(|| {
+            anyhow!("No profile '{name}' in the config file. Create it with `hub --profile {name} config set hub-url <URL>`")
+        })?;
+        Ok(Self {
+            hub_url: profile.hub_url.or(self.hub_url),
+            mirrors: or_list(profile.mirrors, self.mirrors),
+            output: profile.output.or(self.output),
+            default_terms: or_list(profile.default_terms, self.default_terms),
+            color: profile.color.or(self.color),
+            cache_ttl: profile.cache_ttl.or(self.cache_ttl),
+            retries: profile.retries.or(self.retries),
+            retry_delay: profile.retry_delay.or(self.retry_delay),
+            trust_root: profile.trust_root.or(self.trust_root),
+            trusted: or_list(profile.trusted, self.trusted),
+            blocked: or_list(profile.blocked, self.blocked),
+            git_credentials: or_list(profile.git_credentials, self.git_credentials),
+            git_ssh_hosts: or_list(profile.git_ssh_hosts, self.git_ssh_hosts),
+            github_token: profile.github_token.or(self.github_token),
+            pager: profile.pager.or(self.pager),
+            update_check: profile.update_check.or(self.update_check),
+            update_check_hours: profile.update_check_hours.or(self.update_check_hours),
+            profiles: BTreeMap::new(),
+        })
+    }
+
+    // The settings that `config get` and `config set` work on
+    pub fn section_mut(&mut self, profile: Option<&str>) -> &mut Self {
+        match profile {
+            Some(name) => self.profiles.entry(name.to_owned()).or_default(),
+            None => self,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        let value = match key {
+            "hub-url" => self.hub_url.clone(),
+            "mirrors" => non_empty(&self.mirrors),
+            "output" => self.output.map(|o| value_name(&o)),
+            "default-terms" => non_empty(&self.default_terms),
+            "color" => self.color.map(|c| value_name(&c)),
+            "cache-ttl" => self.cache_ttl.map(|t| t.to_string()),
+            "retries" => self.retries.map(|r| r.to_string()),
+            "retry-delay" => self.retry_delay.map(|d| d.to_string()),
+            "trust-root" => self.trust_root.as_ref().map(|p| p.display().to_string()),
+            "trusted" => non_empty(&self.trusted),
+            "blocked" => non_empty(&self.blocked),
+            "git-credentials" => non_empty(&self.git_credentials),
+            "git-ssh-hosts" => non_empty(&self.git_ssh_hosts),
+            "github-token" => self.github_token.clone(),
+            "pager" => self.pager.clone(),
+            "update-check" => self.update_check.map(|c| c.to_string()),
+            "update-check-hours" => self.update_check_hours.map(|h| h.to_string()),
+            _ => return Err(unknown_key(key)),
+        };
+        Ok(value)
+    }
+
+    // An empty value clears the setting
+    pub fn set(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        let value = Some(value.trim()).filter(|v| !v.is_empty());
+        match key {
+            "hub-url" => {
+                if let Some(url) = value {
+                    url::Url::parse(url).with_context(|| format!("Invalid Hub URL '{url}'"))?;
+                }
+                self.hub_url = value.map(|v| v.to_owned());
+            }
+            "mirrors" => self.mirrors = split_list(value),
+            "output" => self.output = value.map(|v| OutputFormat::from_str(v, true)).transpose().map_err(|e| anyhow!(e))?,
+            "default-terms" => self.default_terms = split_list(value),
+            "color" => self.color = value.map(|v| ColorMode::from_str(v, true)).transpose().map_err(|e| anyhow!(e))?,
+            "cache-ttl" => self.cache_ttl = value.map(|v| v.parse()).transpose().context("cache-ttl must be a number of seconds")?,
+            "retries" => self.retries = value.map(|v| v.parse()).transpose().context("retries must be a whole number of attempts")?,
+            "retry-delay" => self.retry_delay = value.map(|v| v.parse()).transpose().context("retry-delay must be a number of milliseconds")?,
+            "trust-root" => self.trust_root = value.map(PathBuf::from),
+            "trusted" => self.trusted = split_list(value),
+            "blocked" => self.blocked = split_list(value),
+            "git-credentials" => {
+                let credentials = split_list(value);
+                if let Some(invalid) = credentials.iter().find(|c| !c.contains('=')) {
+                    return Err(anyhow!("git-credentials must be HOST=TOKEN pairs, not '{invalid}'"));
+                }
+                self.git_credentials = credentials;
+            }
+            "git-ssh-hosts" => self.git_ssh_hosts = split_list(value),
+            "github-token" => self.github_token = value.map(|v| v.to_owned()),
+            "pager" => self.pager = value.map(|v| v.to_owned()),
+            "update-check" => self.update_check = value.map(|v| v.parse()).transpose().context("update-check must be true or false")?,
+            "update-check-hours" => self.update_check_hours = value.map(|v| v.parse()).transpose().context("update-check-hours must be a whole number of hours")?,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    pub fn save(&self) -> anyhow::Result<PathBuf> {
+        let path = config_path().ok_or_else(|| anyhow!("Can't determine the config directory"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("Failed to write config file {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("spin-hub").join("config.toml"))
+}
+
+pub fn load() -> anyhow::Result<Config> {
+    let Some(path) = config_path() else {
+        return Ok(Config::default());
+    };
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    toml::from_str(&text)
+        .with_context(|| format!("Config file {} is not valid", path.display()))
+}
+
+// The settings as `profile` sees them, with the top-level ones it doesn't override
+pub fn load_for_profile(profile: Option<&str>) -> anyhow::Result<Config> {
+    let config = load()?;
+    match profile {
+        Some(name) => config.with_profile(name),
+        None => Ok(config),
+    }
+}
+
+fn unknown_key(key: &str) -> anyhow::Error {
+    anyhow!("Unknown config key '{key}'. Valid keys are: {}", KEYS.join(", "))
+}
+
+fn value_name(value: &impl ValueEnum) -> String {
+    value.to_possible_value().map(|v| v.get_name().to_owned()).unwrap_or_default()
+}
+
+fn non_empty(values: &[String]) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(","))
+    }
+}
+
+fn or_list(preferred: Vec<String>, fallback: Vec<String>) -> Vec<String> {
+    if preferred.is_empty() {
+        fallback
+    } else {
+        preferred
+    }
+}
+
+fn split_list(value: Option<&str>) -> Vec<String> {
+    value.unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn set_values_round_trip_through_get() {
+        let mut config = Config::default();
+        config.set("mirrors", "https://a.example.com, https://b.example.com").unwrap();
+        config.set("output", "JSON").unwrap();
+        config.set("cache-ttl", "60").unwrap();
+
+        assert_eq!(Some("https://a.example.com,https://b.example.com".to_owned()), config.get("mirrors").unwrap());
+        assert_eq!(Some("json".to_owned()), config.get("output").unwrap());
+        assert_eq!(Some("60".to_owned()), config.get("cache-ttl").unwrap());
+
+        config.set("cache-ttl", "").unwrap();
+        assert_eq!(None, config.get("cache-ttl").unwrap());
+
+        assert!(config.set("output", "xml").is_err());
+        assert!(config.get("nonsense").is_err());
+    }
+
+    #[test]
+    fn profile_settings_override_top_level_ones() {
+        let mut config = Config::default();
+        config.set("hub-url", "https://hub.example.com").unwrap();
+        config.set("cache-ttl", "60").unwrap();
+        config.section_mut(Some("work")).set("hub-url", "https://hub.work.example.com").unwrap();
+
+        let work = config.clone().with_profile("work").unwrap();
+        assert_eq!(Some("https://hub.work.example.com".to_owned()), work.get("hub-url").unwrap());
+        assert_eq!(Some("60".to_owned()), work.get("cache-ttl").unwrap());
+        assert!(config.with_profile("home").is_err());
+    }
+}
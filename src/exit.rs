@@ -0,0 +1,221 @@
+use crate::{hub_api::HubError, settings, style};
+
+// The exit codes that scripts can rely on. Usage errors from argument
+// parsing also exit with 2.
+pub const FAILURE: u8 = 1;
+pub const NO_MATCHES: u8 = 2;
+pub const AMBIGUOUS: u8 = 3;
+pub const NETWORK: u8 = 4;
+pub const VERIFICATION: u8 = 5;
+pub const BAD_INDEX: u8 = 6;
+pub const NOT_FOUND: u8 = 7;
+pub const RATE_LIMITED: u8 = 8;
+pub const UNAUTHORIZED: u8 = 9;
+pub const OUTDATED: u8 = 10;
+
+pub const CODES_HELP: &str = "\
+Exit codes:
+    0    Success
+    1    Any other failure
+    2    Nothing matched the search terms (or the arguments were invalid)
+    3    Several entries matched, and the command couldn't ask which to use
+    4    The Hub couldn't be reached
+    5    A checksum, signature or trust check failed
+    6    The Hub index couldn't be read
+    7    The Hub index or entry wasn't found
+    8    The Hub or GitHub is rate limiting requests
+    9    The Hub needs you to log in
+    10   A newer Hub CLI release is available (self update --check)
+    130  Interrupted with Ctrl-C";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    #[default]
+    Text,
+    // One JSON object on stderr, for tools that run the Hub CLI
+    Json,
+}
+
+#[derive(serde::Serialize)]
+struct ErrorReport {
+    code: u8,
+    kind: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+    // What led to the error, innermost last
+    causes: Vec<String>,
+}
+
+// Failures that aren't from the Hub but that scripts may want to tell apart
+#[derive(Debug, thiserror::Error)]
+pub enum Failure {
+    // The command has already said so, with any suggestions
+    #[error("Nothing matched the search terms")]
+    NoMatches,
+    #[error("{0}")]
+    Ambiguous(String),
+    #[error("{0}")]
+    Verification(String),
+    #[error("{0}")]
+    Outdated(String),
+    // A service other than the Hub, such as the GitHub API
+    #[error("{0}")]
+    RateLimited(String),
+    // An external subcommand, or the copy of the Hub CLI run for the pager,
+    // failed and has said why itself
+    #[error("{program} exited with code {code}")]
+    External { program: String, code: u8 },
+}
+
+// The first cause with a code of its own decides, so context added on the
+// way up doesn't change the code
+pub fn code(error: &anyhow::Error) -> u8 {
+    error.chain()
+        .find_map(|cause| {
+            if let Some(e) = cause.downcast_ref::<HubError>() {
+                Some(hub_code(e))
+            } else {
+                cause.downcast_ref::<Failure>().map(failure_code)
+            }
+        })
+        .unwrap_or(FAILURE)
+}
+
+pub fn report(error: &anyhow::Error, format: ErrorFormat) {
+    match format {
+        ErrorFormat::Text if is_reported(error) => (),
+        ErrorFormat::Text => eprintln!("{}", render(error)),
+        ErrorFormat::Json => match serde_json::to_string(&error_report(error)) {
+            Ok(json) => eprintln!("{json}"),
+            Err(_) => eprintln!("{}", render(error)),
+        },
+    }
+}
+
+// The error, then what caused it as a tree, then what to do about it:
+//
+//   Error: Failed to fetch the Hub index from https://hub.example.com/
+//     ╰─▶ Couldn't reach the Hub at https://hub.example.com/api/hub/get_list.
+//   help: Check your network connection and proxy settings, ...
+fn render(error: &anyhow::Error) -> String {
+    let mut out = format!("{} {error}", style::error_label());
+    let causes = error.chain().skip(1).map(|cause| cause.to_string()).collect::<Vec<_>>();
+    for (idx, cause) in causes.iter().enumerate() {
+        let branch = if idx == causes.len() - 1 { "╰─▶" } else { "├─▶" };
+        // Later lines of a cause line up under its first
+        out.push_str(&format!("\n  {branch} {}", cause.replace('\n', "\n      ")));
+    }
+    if let Some(hint) = hint(error) {
+        out.push_str(&format!("\n{} {hint}", style::help_label()));
+    }
+    out
+}
+
+fn error_report(error: &anyhow::Error) -> ErrorReport {
+    let code = code(error);
+    ErrorReport {
+        code,
+        kind: kind(code),
+        message: error.to_string(),
+        hint: hint(error),
+        causes: error.chain().skip(1).map(|cause| cause.to_string()).collect(),
+    }
+}
+
+// Whether the user has already been told, so printing the error would repeat it
+fn is_reported(error: &anyhow::Error) -> bool {
+    matches!(error.downcast_ref::<Failure>(), Some(Failure::NoMatches | Failure::External { .. }))
+}
+
+pub fn kind(code: u8) -> &'static str {
+    match code {
+        NO_MATCHES => "no-matches",
+        AMBIGUOUS => "ambiguous",
+        NETWORK => "network",
+        VERIFICATION => "verification",
+        BAD_INDEX => "bad-index",
+        NOT_FOUND => "not-found",
+        RATE_LIMITED => "rate-limited",
+        UNAUTHORIZED => "unauthorized",
+        OUTDATED => "outdated",
+        _ => "failure",
+    }
+}
+
+// What the user can do about it
+fn hint(error: &anyhow::Error) -> Option<String> {
+    // These say what to do in the message already
+    if error.chain().any(|cause| matches!(cause.downcast_ref::<Failure>(), Some(Failure::RateLimited(_)))) {
+        return None;
+    }
+    let hint = match code(error) {
+        NO_MATCHES => "Try fewer or different search terms, or --fuzzy to match them approximately",
+        AMBIGUOUS => "Refine the search terms, or choose one with --first, --select N or --id",
+        NETWORK => return Some(network_hint()),
+        VERIFICATION => "Check where the download comes from, and review the trust policy with `hub trust list`",
+        BAD_INDEX => "Run again with --refresh to fetch a fresh copy of the index",
+        NOT_FOUND => "Check the Hub URL with `hub config get hub-url`, or use `hub search` to find entry IDs",
+        RATE_LIMITED => "Wait a few minutes and try again, or use `hub login` if the Hub gives logged-in users more requests",
+        UNAUTHORIZED => "Use `hub login` to store your credentials",
+        OUTDATED => "Run `hub self update` to install it",
+        _ => return None,
+    };
+    Some(hint.to_owned())
+}
+
+fn network_hint() -> String {
+    let settings = settings::get();
+    let proxy = match &settings.proxy {
+        Some(proxy) => format!("check that the proxy {proxy} is reachable"),
+        None => "check your proxy settings (--proxy or HTTPS_PROXY)".to_owned(),
+    };
+    let mirrors = if settings.hub_urls.len() > 1 { "" } else { ", or use --hub-mirror to try another Hub" };
+    format!("Check your network connection and {proxy}{mirrors}")
+}
+
+fn hub_code(error: &HubError) -> u8 {
+    match error {
+        HubError::Network { .. } | HubError::Response { .. } | HubError::NotModified { .. } | HubError::NoHubUrl | HubError::HttpClient(_) => NETWORK,
+        HubError::Decode(_) => BAD_INDEX,
+        HubError::IndexNotFound { .. } | HubError::IndexFile { .. } | HubError::EntryNotFound(_) | HubError::VersionNotFound { .. } => NOT_FOUND,
+        HubError::RateLimited { .. } => RATE_LIMITED,
+        HubError::Unauthorized { .. } => UNAUTHORIZED,
+    }
+}
+
+fn failure_code(failure: &Failure) -> u8 {
+    match failure {
+        Failure::NoMatches => NO_MATCHES,
+        Failure::Ambiguous(_) => AMBIGUOUS,
+        Failure::Verification(_) => VERIFICATION,
+        Failure::Outdated(_) => OUTDATED,
+        Failure::RateLimited(_) => RATE_LIMITED,
+        Failure::External { code, .. } => *code,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn codes_come_from_the_first_cause_that_has_one() {
+        let error = anyhow::Error::from(Failure::Verification("Checksum mismatch".to_owned())).context("Failed to install the plugin");
+        assert_eq!(VERIFICATION, code(&error));
+        assert_eq!(NOT_FOUND, code(&HubError::EntryNotFound("nope".to_owned()).into()));
+        assert_eq!(FAILURE, code(&anyhow::anyhow!("Something else")));
+        assert!(is_reported(&Failure::NoMatches.into()));
+    }
+
+    #[test]
+    fn reports_have_the_cause_chain() {
+        let error = anyhow::Error::from(HubError::NoHubUrl).context("Failed to fetch the Hub index");
+        let report = error_report(&error);
+        assert_eq!(NETWORK, report.code);
+        assert_eq!("network", report.kind);
+        assert_eq!("Failed to fetch the Hub index", report.message);
+        assert_eq!(vec!["No Hub URL configured".to_owned()], report.causes);
+        assert!(report.hint.is_some());
+    }
+}
@@ -0,0 +1,71 @@
+use itertools::Itertools;
+
+const MAX_SUGGESTIONS: usize = 3;
+
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect_vec();
+    let mut previous = (0..=b.len()).collect_vec();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+// Longer words get more leeway, so that short terms like "go" don't fuzzily
+// match everything.
+fn max_distance(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=6 => 1,
+        _ => 2,
+    }
+}
+
+pub fn is_fuzzy_match(term: &str, word: &str) -> bool {
+    word.starts_with(term) || edit_distance(term, word) <= max_distance(term)
+}
+
+pub fn suggestions<'a>(term: &str, vocabulary: impl IntoIterator<Item = &'a String>) -> Vec<&'a str> {
+    let max = max_distance(term).max(1) + 1;
+    vocabulary.into_iter()
+        .unique()
+        .map(|word| (edit_distance(term, word), word.as_str()))
+        .filter(|(distance, _)| *distance > 0 && *distance <= max)
+        .sorted()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, word)| word)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        assert_eq!(0, edit_distance("redis", "redis"));
+        assert_eq!(1, edit_distance("reddis", "redis"));
+        assert_eq!(3, edit_distance("kitten", "sitting"));
+        assert_eq!(5, edit_distance("", "hello"));
+    }
+
+    #[test]
+    fn fuzzy_matches_prefixes_and_typos() {
+        assert!(is_fuzzy_match("postgre", "postgresql"));
+        assert!(is_fuzzy_match("typscript", "typescript"));
+        assert!(!is_fuzzy_match("go", "js"));
+    }
+
+    #[test]
+    fn suggestions_are_closest_first() {
+        let vocabulary = ["redis", "rust", "reds", "python"].map(|s| s.to_owned());
+        assert_eq!(vec!["redis", "reds"], suggestions("rediss", &vocabulary));
+    }
+}
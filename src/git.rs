@@ -1,7 +1,49 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
 use anyhow::anyhow;
+use base64::Engine;
+
+use crate::{cache, interrupt, progress::Progress, settings};
+
+// Settings for the git processes the Hub CLI runs, passed with git's
+// GIT_CONFIG_COUNT environment variables. They're set on each git command
+// rather than on the Hub CLI's own environment, so tokens don't reach builds,
+// hooks, editors or extensions. git's own credential helpers and SSH agent keep
+// working as well. spin-templates only gets local copies of templates, so it
+// never needs them.
+static CONFIG: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+pub fn configure(credentials: &[(String, String)], ssh_hosts: &[String]) {
+    let mut entries = vec![];
+    for (host, token) in credentials {
+        let basic = base64::engine::general_purpose::STANDARD.encode(format!("x-access-token:{token}"));
+        entries.push((format!("http.https://{host}/.extraheader"), format!("Authorization: Basic {basic}")));
+    }
+    for host in ssh_hosts {
+        entries.push((format!("url.git@{host}:.insteadOf"), format!("https://{host}/")));
+    }
+    _ = CONFIG.set(entries);
+}
+
+pub fn git_command() -> tokio::process::Command {
+    let mut command = tokio::process::Command::new("git");
+    let entries = CONFIG.get().map(|e| e.as_slice()).unwrap_or_default();
+    if entries.is_empty() {
+        return command;
+    }
+    let existing: usize = std::env::var("GIT_CONFIG_COUNT").ok().and_then(|c| c.parse().ok()).unwrap_or(0);
+    for (idx, (key, value)) in entries.iter().enumerate() {
+        command.env(format!("GIT_CONFIG_KEY_{}", existing + idx), key);
+        command.env(format!("GIT_CONFIG_VALUE_{}", existing + idx), value);
+    }
+    command.env("GIT_CONFIG_COUNT", (existing + entries.len()).to_string());
+    command
+}
 
+#[tracing::instrument(name = "git clone", skip_all)]
 pub async fn clone_decoupled(repo: &str) -> anyhow::Result<()> {
-    let status = tokio::process::Command::new("git")
+    let status = git_command()
         .args(["clone", "-o", "upstream"])
         .arg(repo)
         .status()
@@ -14,6 +56,321 @@ pub async fn clone_decoupled(repo: &str) -> anyhow::Result<()> {
     }
 }
 
+// Shallow clones are much quicker for big repositories, but not every server
+// supports them (e.g. git's "dumb" HTTP protocol), so fall back to a full clone.
+#[tracing::instrument(name = "git clone", skip_all)]
+pub async fn clone_shallow(repo: &str, git_ref: Option<&str>, dest: &Path) -> anyhow::Result<()> {
+    clone_branch(repo, git_ref, dest, None).await
+}
+
+// For template sources, where git's progress is shown in the spinner
+#[tracing::instrument(name = "git clone", skip_all)]
+pub async fn clone_shallow_with_progress(repo: &str, git_ref: Option<&str>, dest: &Path, progress: &Progress) -> anyhow::Result<()> {
+    clone_branch(repo, git_ref, dest, Some(progress)).await
+}
+
+async fn clone_branch(repo: &str, git_ref: Option<&str>, dest: &Path, progress: Option<&Progress>) -> anyhow::Result<()> {
+    let clone = |shallow: bool| {
+        let mut command = git_command();
+        command.arg("clone");
+        if progress.is_some() {
+            command.arg("--progress");
+        }
+        if shallow {
+            command.args(["--depth", "1"]);
+        }
+        command.arg("--single-branch");
+        if let Some(git_ref) = git_ref {
+            command.args(["--branch", git_ref]);
+        }
+        command.arg(repo).arg(dest);
+        command
+    };
+
+    tracing::debug!(repo, ?git_ref, dest = %dest.display(), "Shallow clone");
+    if run(&mut clone(true), progress).await?.0 {
+        return Ok(());
+    }
+
+    tracing::info!(repo, "Shallow clone failed, trying a full clone");
+    remove_partial_clone(dest);
+    match run(&mut clone(false), progress).await? {
+        (true, _) => Ok(()),
+        (false, None) => Err(anyhow!("git clone failed - see output for details")),
+        (false, Some(output)) => Err(anyhow!("git clone failed: {}", last_lines(&output))),
+    }
+}
+
+// Runs git, showing its progress, such as "Receiving objects: 42%", in the
+// spinner if there is one. Returns whether it succeeded, and what it printed
+// if that was captured.
+async fn run(command: &mut tokio::process::Command, progress: Option<&Progress>) -> anyhow::Result<(bool, Option<String>)> {
+    use tokio::io::AsyncReadExt;
+
+    let Some(progress) = progress else {
+        return Ok((command.status().await?.success(), None));
+    };
+    let mut child = command.stderr(std::process::Stdio::piped()).spawn()?;
+    let mut stderr = child.stderr.take().ok_or_else(|| anyhow!("git has no error output"))?;
+    let mut output = vec![];
+    let mut buf = [0; 1024];
+    loop {
+        let read = stderr.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        output.extend_from_slice(&buf[..read]);
+        if let Some(line) = progress_line(&String::from_utf8_lossy(&output)) {
+            progress.set_detail(line);
+        }
+    }
+    let status = child.wait().await?;
+    progress.set_detail("");
+    Ok((status.success(), Some(String::from_utf8_lossy(&output).into_owned())))
+}
+
+// git redraws its progress with carriage returns, so the latest is the last
+// complete line, whichever way it ends
+fn progress_line(output: &str) -> Option<&str> {
+    let complete = &output[..output.rfind(['\r', '\n'])?];
+    complete.rsplit(['\r', '\n']).map(|l| l.trim()).find(|l| !l.is_empty())
+}
+
+fn last_lines(output: &str) -> String {
+    let lines = output.split(['\r', '\n']).map(|l| l.trim()).filter(|l| !l.is_empty()).collect::<Vec<_>>();
+    lines[lines.len().saturating_sub(3)..].join("\n")
+}
+
+#[tracing::instrument(name = "git clone", skip_all)]
+pub async fn clone_at_rev(repo: &str, rev: &str, dest: &Path, progress: Option<&Progress>) -> anyhow::Result<()> {
+    // Servers only let you fetch a commit by its full SHA
+    if rev.len() == 40 && fetch_rev_shallow(repo, rev, dest, progress).await.unwrap_or(false) {
+        return Ok(());
+    }
+    remove_partial_clone(dest);
+
+    tracing::debug!(repo, rev, dest = %dest.display(), "Clone at revision");
+    let mut clone = git_command();
+    clone.args(["clone", if progress.is_some() { "--progress" } else { "--quiet" }, "--no-checkout"]).arg(repo).arg(dest);
+    match run(&mut clone, progress).await? {
+        (true, _) => {}
+        (false, None) => return Err(anyhow!("git clone failed - see output for details")),
+        (false, Some(output)) => return Err(anyhow!("git clone failed: {}", last_lines(&output))),
+    }
+
+    let status = git_command()
+        .arg("-C")
+        .arg(dest)
+        .args(["checkout", "--quiet", rev])
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow!("git checkout of {rev} failed - see output for details"));
+    }
+    Ok(())
+}
+
+// Template repositories are kept in the cache and brought up to date each time
+// they're used, rather than cloned again for every app. Returns where the
+// checkout is, which is `scratch` if there's no cache directory.
+pub async fn cached_clone(repo: &str, git_ref: Option<&str>, scratch: &Path, progress: &Progress) -> anyhow::Result<PathBuf> {
+    let clone = |dest: PathBuf| async move {
+        match git_ref {
+            Some(rev) if is_commit_sha(rev) => clone_at_rev(repo, rev, &dest, Some(progress)).await,
+            git_ref => clone_shallow_with_progress(repo, git_ref, &dest, progress).await,
+        }?;
+        anyhow::Ok(dest)
+    };
+
+    let Some(dir) = cache::repo_dir(repo, git_ref) else {
+        return clone(scratch.to_owned()).await;
+    };
+
+    if dir.exists() && !settings::get().refresh {
+        // A commit never changes, so there's nothing to update
+        if git_ref.is_some_and(is_commit_sha) {
+            tracing::info!(repo, dir = %dir.display(), "Using cached clone");
+            return Ok(dir);
+        }
+        match update_clone(&dir, git_ref, progress).await {
+            Ok(()) => {
+                tracing::info!(repo, dir = %dir.display(), "Updated cached clone");
+                return Ok(dir);
+            }
+            Err(e) => tracing::info!(repo, error = %e, "Cached clone couldn't be updated, cloning again"),
+        }
+    }
+
+    // Cloned alongside and moved into place, so a failed or interrupted clone
+    // never looks like a usable one
+    let parent = dir.parent().ok_or_else(|| anyhow!("Invalid cache directory {}", dir.display()))?;
+    std::fs::create_dir_all(parent)?;
+    let partial = tempfile::tempdir_in(parent)?;
+    let _cleanup = interrupt::remove_on_interrupt(partial.path());
+    let cloned = clone(partial.path().join("repo")).await?;
+    remove_partial_clone(&dir);
+    std::fs::rename(&cloned, &dir)?;
+    Ok(dir)
+}
+
+async fn update_clone(dir: &Path, git_ref: Option<&str>, progress: &Progress) -> anyhow::Result<()> {
+    let git_ref = git_ref.unwrap_or("HEAD");
+    let git = |args: &[&str]| {
+        let mut command = git_command();
+        command.arg("-C").arg(dir).args(args);
+        command
+    };
+    if !run(&mut git(&["fetch", "--progress", "--depth", "1", "origin", git_ref]), Some(progress)).await?.0 {
+        return Err(anyhow!("git fetch of {git_ref} failed"));
+    }
+    // Discards anything a previous run left behind, as well as moving to what was fetched
+    if !git(&["reset", "--quiet", "--hard", "FETCH_HEAD"]).status().await?.success()
+        || !git(&["clean", "--quiet", "-ffdx"]).status().await?.success()
+    {
+        return Err(anyhow!("git reset of {} failed", dir.display()));
+    }
+    Ok(())
+}
+
+// Many servers, including GitHub, allow fetching a single commit by SHA
+async fn fetch_rev_shallow(repo: &str, rev: &str, dest: &Path, progress: Option<&Progress>) -> anyhow::Result<bool> {
+    tracing::debug!(repo, rev, dest = %dest.display(), "Shallow fetch of revision");
+    let git = |args: &[&str]| {
+        let mut command = git_command();
+        command.arg("-C").arg(dest).args(args);
+        command.stdout(std::process::Stdio::null()).stderr(std::process::Stdio::null());
+        command
+    };
+    std::fs::create_dir_all(dest)?;
+    Ok(git(&["init", "--quiet"]).status().await?.success()
+        && match progress {
+            Some(progress) => run(&mut git(&["fetch", "--progress", "--depth", "1", repo, rev]), Some(progress)).await?.0,
+            None => git(&["fetch", "--quiet", "--depth", "1", repo, rev]).status().await?.success(),
+        }
+        && git(&["checkout", "--quiet", "FETCH_HEAD"]).status().await?.success())
+}
+
+fn remove_partial_clone(dest: &Path) {
+    if dest.exists() {
+        _ = std::fs::remove_dir_all(dest);
+    }
+}
+
+pub async fn head_commit(dir: &Path) -> anyhow::Result<String> {
+    let output = git_command()
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow!("git rev-parse failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+// The user's name and email from their git config, where they've set them
+pub async fn identity() -> (Option<String>, Option<String>) {
+    (config_value("user.name").await, config_value("user.email").await)
+}
+
+async fn config_value(key: &str) -> Option<String> {
+    let output = git_command().args(["config", "--get", key]).output().await.ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+// Makes `dir` a repository whose first commit has everything in it
+pub async fn init_with_commit(dir: &Path, message: &str) -> anyhow::Result<()> {
+    let steps: [&[&str]; 3] = [&["init", "--quiet"], &["add", "--all"], &["commit", "--quiet", "-m", message]];
+    for args in steps {
+        let output = git_command()
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .await?;
+        if !output.status.success() {
+            return Err(anyhow!("git {} failed: {}", args[0], String::from_utf8_lossy(&output.stderr).trim()));
+        }
+    }
+    Ok(())
+}
+
+// Whether the repository has a branch with this name
+pub async fn has_branch(repo: &str, branch: &str) -> bool {
+    git_command()
+        .args(["ls-remote", "--exit-code", "--heads", repo, branch])
+        .stdout(std::process::Stdio::null())
+        .status()
+        .await
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+// A single file holding all the branches and tags of the repository, which
+// git can clone from like a remote
+#[tracing::instrument(name = "git clone", skip_all)]
+pub async fn bundle(repo: &str, dest: &Path) -> anyhow::Result<()> {
+    // The bundle is written from inside the clone
+    let dest = std::env::current_dir()?.join(dest);
+    let tempdir = tempfile::tempdir()?;
+    let mirror = tempdir.path().join("repo.git");
+    let status = git_command()
+        .args(["clone", "--quiet", "--mirror", repo])
+        .arg(&mirror)
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow!("git clone of {repo} failed - see output for details"));
+    }
+
+    let status = git_command()
+        .arg("-C")
+        .arg(&mirror)
+        .args(["bundle", "create", "--quiet"])
+        .arg(&dest)
+        .arg("--all")
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(anyhow!("git bundle of {repo} failed - see output for details"));
+    }
+    Ok(())
+}
+
+// The commit that a branch or tag (or the default branch) currently points to
+pub async fn resolve_ref(repo: &str, git_ref: Option<&str>) -> anyhow::Result<String> {
+    if let Some(rev) = git_ref.filter(|r| is_commit_sha(r)) {
+        return Ok(rev.to_owned());
+    }
+    let git_ref = git_ref.unwrap_or("HEAD");
+    let output = git_command()
+        .args(["ls-remote", repo, git_ref])
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow!("git ls-remote failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    parse_ls_remote(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| anyhow!("No branch or tag {git_ref} in {repo}"))
+}
+
+// Annotated tags are listed twice, and the `^{}` line is the commit they point to
+fn parse_ls_remote(output: &str) -> Option<String> {
+    let refs: Vec<(&str, &str)> = output.lines().filter_map(|line| line.split_once('\t')).collect();
+    refs.iter()
+        .find(|(_, name)| name.ends_with("^{}"))
+        .or_else(|| refs.first())
+        .map(|(sha, _)| sha.to_string())
+}
+
+// `git clone --branch` accepts branches and tags but not commits, so commits
+// need a full clone and checkout.
+pub fn is_commit_sha(git_ref: &str) -> bool {
+    (7..=40).contains(&git_ref.len()) && git_ref.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 pub fn clone_dir(repo: &str) -> anyhow::Result<String> {
     let url = url::Url::parse(repo)?;
     let path_segments = url.path_segments().ok_or(anyhow!("can't determine output directory"))?;
@@ -21,3 +378,23 @@ pub fn clone_dir(repo: &str) -> anyhow::Result<String> {
     let dir = last_segment.strip_suffix(".git").unwrap_or(last_segment);
     Ok(dir.to_owned())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn the_latest_complete_progress_line_is_shown() {
+        let output = "Cloning into 'x'...\nReceiving objects:  10% (1/10)\rReceiving objects:  20% (2/10)\rReceiving obj";
+        assert_eq!(Some("Receiving objects:  20% (2/10)"), progress_line(output));
+        assert_eq!(None, progress_line("Cloning"));
+    }
+
+    #[test]
+    fn ls_remote_prefers_the_commit_an_annotated_tag_points_to() {
+        let output = "1111111111111111111111111111111111111111\trefs/tags/v1.0\n2222222222222222222222222222222222222222\trefs/tags/v1.0^{}\n";
+        assert_eq!(Some("2222222222222222222222222222222222222222".to_owned()), parse_ls_remote(output));
+        assert_eq!(Some("3333333".to_owned()), parse_ls_remote("3333333\trefs/heads/main\n"));
+        assert_eq!(None, parse_ls_remote(""));
+    }
+}
@@ -0,0 +1,147 @@
+use anyhow::{anyhow, Context};
+use serde_json::Value;
+
+use crate::{auth::{self, Credentials}, exit::Failure, history::now, http, settings};
+
+const API_URL: &str = "https://api.github.com/";
+
+// Tokens are kept in the keyring under the API URL, by `hub login --github`
+pub fn api_url() -> url::Url {
+    url::Url::parse(API_URL).expect("the GitHub API URL is valid")
+}
+
+// From GITHUB_TOKEN or the config file, then the keyring
+pub fn token() -> Option<String> {
+    if let Some(token) = &settings::get().github_token {
+        return Some(token.clone());
+    }
+    match auth::load_stored(&api_url())? {
+        Credentials::Bearer { token } => Some(token),
+        Credentials::Basic { .. } => None,
+    }
+}
+
+pub struct GitHub {
+    client: reqwest::Client,
+    token: Option<String>,
+}
+
+impl GitHub {
+    pub fn new(token: String) -> anyhow::Result<Self> {
+        Ok(Self { client: http::client()?, token: Some(token) })
+    }
+
+    // For public data such as releases. A stored token is still used if there
+    // is one, for its higher rate limit.
+    pub fn public() -> anyhow::Result<Self> {
+        Ok(Self { client: http::client()?, token: token() })
+    }
+
+    pub async fn get(&self, path: &str) -> anyhow::Result<Value> {
+        self.send(reqwest::Method::GET, path, None).await
+    }
+
+    pub async fn post(&self, path: &str, body: Value) -> anyhow::Result<Value> {
+        self.send(reqwest::Method::POST, path, Some(body)).await
+    }
+
+    pub async fn put(&self, path: &str, body: Value) -> anyhow::Result<Value> {
+        self.send(reqwest::Method::PUT, path, Some(body)).await
+    }
+
+    // None if there's nothing at `path`
+    pub async fn get_optional(&self, path: &str) -> anyhow::Result<Option<Value>> {
+        match self.request(reqwest::Method::GET, path, None).await? {
+            (reqwest::StatusCode::NOT_FOUND, _, _) => Ok(None),
+            (status, headers, body) => check(status, &headers, body, path).map(Some),
+        }
+    }
+
+    async fn send(&self, method: reqwest::Method, path: &str, body: Option<Value>) -> anyhow::Result<Value> {
+        let (status, headers, body) = self.request(method, path, body).await?;
+        check(status, &headers, body, path)
+    }
+
+    async fn request(&self, method: reqwest::Method, path: &str, body: Option<Value>) -> anyhow::Result<(reqwest::StatusCode, reqwest::header::HeaderMap, Value)> {
+        let url = api_url().join(path.trim_start_matches('/'))?;
+        tracing::debug!(%method, %url, "GitHub API");
+        let mut request = self.client.request(method, url.clone())
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json");
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(body) = body {
+            request = request.header(reqwest::header::CONTENT_TYPE, "application/json").body(body.to_string());
+        }
+        let response = request.send().await.with_context(|| format!("Failed to call {url}"))?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response.bytes().await?;
+        let body = if bytes.is_empty() { Value::Null } else { serde_json::from_slice(&bytes).unwrap_or(Value::Null) };
+        Ok((status, headers, body))
+    }
+}
+
+fn check(status: reqwest::StatusCode, headers: &reqwest::header::HeaderMap, body: Value, path: &str) -> anyhow::Result<Value> {
+    if status.is_success() {
+        return Ok(body);
+    }
+    let message = body["message"].as_str().unwrap_or("no details");
+    // GitHub says 403 rather than 429 when the limit runs out
+    if matches!(status, reqwest::StatusCode::FORBIDDEN | reqwest::StatusCode::TOO_MANY_REQUESTS) {
+        if let Some(limit) = RateLimit::from_headers(headers).filter(|l| l.remaining == 0) {
+            return Err(Failure::RateLimited(limit.describe(now())).into());
+        }
+    }
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED => Err(anyhow!("GitHub didn't accept the token ({message}). Log in again with `hub login --github`")),
+        _ => Err(anyhow!("GitHub API call {path} failed: {status} ({message})")),
+    }
+}
+
+struct RateLimit {
+    limit: u64,
+    remaining: u64,
+    // Seconds since the Unix epoch
+    reset: u64,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        let number = |name: &str| headers.get(name)?.to_str().ok()?.parse().ok();
+        Some(Self {
+            limit: number("x-ratelimit-limit")?,
+            remaining: number("x-ratelimit-remaining")?,
+            reset: number("x-ratelimit-reset")?,
+        })
+    }
+
+    fn describe(&self, now: u64) -> String {
+        let minutes = self.reset.saturating_sub(now).div_ceil(60);
+        let advice = if token().is_some() {
+            "Wait for it to reset, or use a token with a higher limit."
+        } else {
+            "Set GITHUB_TOKEN, or store a token with `hub login --github`, for a higher limit."
+        };
+        format!("GitHub API rate limit exceeded: all {} requests have been used, and the limit resets in {minutes} min. {advice}", self.limit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rate_limits_are_read_from_headers() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-ratelimit-limit", "60".parse().unwrap());
+        headers.insert("x-ratelimit-remaining", "0".parse().unwrap());
+        headers.insert("x-ratelimit-reset", "1000".parse().unwrap());
+
+        let limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!((60, 0, 1000), (limit.limit, limit.remaining, limit.reset));
+
+        headers.remove("x-ratelimit-reset");
+        assert!(RateLimit::from_headers(&headers).is_none());
+    }
+}
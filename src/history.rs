@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Context};
+
+use crate::{installed, style};
+
+// Older invocations are dropped so the file doesn't grow forever
+const MAX_ENTRIES: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    New,
+    Add,
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::New => f.write_str("new"),
+            Self::Add => f.write_str("add"),
+        }
+    }
+}
+
+// Only values given on the command line or in a values file are recorded.
+// Spin doesn't say what was answered at its prompts.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Invocation {
+    pub kind: Kind,
+    // Seconds since the Unix epoch
+    pub time: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub repo: String,
+    pub template_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, String>,
+}
+
+impl Invocation {
+    pub fn age(&self) -> Duration {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(self.time);
+        SystemTime::now().duration_since(time).unwrap_or_default()
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    installed::data_dir().map(|dir| dir.join("history.json"))
+}
+
+// Most recent first
+pub fn invocations() -> anyhow::Result<Vec<Invocation>> {
+    let Some(path) = history_path() else {
+        return Ok(vec![]);
+    };
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&text)
+        .with_context(|| format!("{} is not valid", path.display()))
+}
+
+// Numbered from 1 for the most recent, as `hub history` shows them
+pub fn get(number: usize) -> anyhow::Result<Invocation> {
+    let invocations = invocations()?;
+    number.checked_sub(1)
+        .and_then(|idx| invocations.get(idx))
+        .cloned()
+        .ok_or_else(|| anyhow!("There's no history entry {number}. Use `hub history` to see what's there."))
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+// History is a convenience, so failing to record it is only a warning
+pub fn record(invocation: Invocation) {
+    if let Err(e) = try_record(invocation) {
+        style::warning!("couldn't record this in the history: {e:#}");
+    }
+}
+
+fn try_record(invocation: Invocation) -> anyhow::Result<()> {
+    let mut invocations = invocations()?;
+    invocations.insert(0, invocation);
+    invocations.truncate(MAX_ENTRIES);
+
+    let path = history_path().ok_or_else(|| anyhow!("Can't determine the data directory"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&invocations)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
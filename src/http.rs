@@ -0,0 +1,40 @@
+use anyhow::Context;
+
+use crate::settings;
+
+// reqwest honours HTTP_PROXY, HTTPS_PROXY and NO_PROXY itself unless an
+// explicit proxy is configured, so there's only something to do for --proxy.
+pub fn client() -> anyhow::Result<reqwest::Client> {
+    let settings = settings::get();
+    let mut builder = reqwest::Client::builder()
+        .user_agent(concat!("spin-hub/", env!("CARGO_PKG_VERSION")));
+
+    if let Some(proxy) = &settings.proxy {
+        let proxy = reqwest::Proxy::all(proxy).with_context(|| format!("Invalid proxy URL '{proxy}'"))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(cacert) = &settings.cacert {
+        let pem = std::fs::read(cacert)
+            .with_context(|| format!("Failed to read CA certificate {}", cacert.display()))?;
+        let certificate = reqwest::Certificate::from_pem(&pem)
+            .with_context(|| format!("{} is not a valid PEM certificate", cacert.display()))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+
+    Ok(builder.build()?)
+}
+
+// Also reads file URLs, for Hub mirrors
+pub async fn download(client: &reqwest::Client, url: &str) -> anyhow::Result<Vec<u8>> {
+    if let Some(path) = url::Url::parse(url).ok().filter(|u| u.scheme() == "file").and_then(|u| u.to_file_path().ok()) {
+        return std::fs::read(&path).with_context(|| format!("Failed to read {}", path.display()));
+    }
+    tracing::debug!(%url, "GET");
+    let response = client.get(url).send().await
+        .with_context(|| format!("Failed to download {url}"))?
+        .error_for_status()
+        .with_context(|| format!("Failed to download {url}"))?;
+    tracing::debug!(%url, status = %response.status(), "Response");
+    Ok(response.bytes().await?.to_vec())
+}
@@ -1,180 +1,95 @@
-use itertools::Itertools;
+use anyhow::Context;
+use tracing::Instrument;
 
-const DEV_SITE_BASE: &'static str = "https://developer.fermyon.com";
+use crate::{auth, cache, http, progress::Progress, settings, style};
 
-fn index_url() -> url::Url {
-    url::Url::parse(DEV_SITE_BASE)
-        .expect("Base URL was malformed")
-        .join("api/hub/get_list")
-        .expect("Index URL was malformed")
-}
+pub use spin_hub_api::{find_entry, Category, HubError, IndexEntry};
+use spin_hub_api::HubClient;
 
-pub async fn index() -> Result<Vec<IndexEntry>, Error> {
-    let response = reqwest::get(index_url()).await?;
-    if !response.status().is_success() {
-        return Err(Error::Response(response.status()));
-    }
-    let body = response.bytes().await?;
-    Ok(serde_json::from_slice(&body)?)
+// Errors keep the HubError, so the exit code still reflects what went wrong
+pub async fn index() -> anyhow::Result<Vec<IndexEntry>> {
+    let settings = settings::get();
+    load_index().await.with_context(|| match &settings.hub_file {
+        Some(path) => format!("Failed to read the Hub index file {}", path.display()),
+        None => format!("Failed to fetch the Hub index from {}", settings.hub_url()),
+    })
 }
 
-#[derive(thiserror::Error, Debug)]
-pub enum Error {
-    #[error(transparent)]
-    Request(#[from] reqwest::Error),
-    #[error("Unsuccessful response from API: {0}")]
-    Response(reqwest::StatusCode),
-    #[error(transparent)]
-    Json(#[from] serde_json::Error),
-}
-
-#[derive(Clone, Debug, serde::Deserialize)]
-pub struct IndexEntry {
-    title: String,
-    summary: String,
-    category: String,
-    language: String,
-    author: String,
-    tags: Vec<String>,
-    repo_url: String,
-    template_id: String,
-    #[allow(dead_code)]
-    path: String,
-}
-
-const SHORT_SUMMARY_LEN: usize = 60;
-
-impl IndexEntry {
-    pub fn title(&self) -> &str {
-        &self.title
-    }
-
-    pub fn summary(&self) -> &str {
-        &self.summary
-    }
-
-    pub fn short_summary(&self) -> String {
-        if self.summary.len() < SHORT_SUMMARY_LEN {
-            self.summary.clone()
-        } else {
-            let suffix = "...";
-            let max_len = SHORT_SUMMARY_LEN - suffix.len();
-            let truncated = truncate_to_word_boundary(&self.summary, max_len, 5);
-            format!("{truncated}{suffix}")
+async fn load_index() -> Result<Vec<IndexEntry>, HubError> {
+    let settings = settings::get();
+    if let Some(path) = &settings.hub_file {
+        tracing::info!(path = %path.display(), "Reading Hub index file");
+        return read_index_file(path);
+    }
+
+    let client = client()?;
+    let index = client.index_with(|hub_url, validators| {
+        // Only shown if the cached index can't just be used
+        let progress = Progress::start(format!("Fetching Hub index from {hub_url}"));
+        let client = &client;
+        async move {
+            let fetched = settings.retry.run(HubError::is_transient, || client.fetch_index_from(&hub_url, validators.as_ref())).await;
+            progress.finish();
+            fetched
         }
-    }
-
-    pub fn author(&self) -> &str {
-        &self.author
-    }
-
-    pub fn language(&self) -> Language {
-        match self.language.to_lowercase().as_str() {
-            "rust" => Language::Rust,
-            "js/ts" | "javascript" | "typescript" => Language::JavaScript,
-            "python" => Language::Python,
-            "go" | "tinygo" => Language::Go,
-            _ => Language::Other(self.language.clone()),
-        }
-    }
-
-    pub fn category(&self) -> Category {
-        Category::parse(&self.category)
-    }
-
-    pub fn tags(&self) -> Vec<String> {
-        self.tags.iter().map(|t| t.to_lowercase()).collect_vec()
-    }
-
-    pub fn title_words(&self) -> Vec<String> {
-        self.title.split_whitespace().map(|t| t.to_lowercase()).collect_vec()
-    }
-    pub fn repo_url(&self) -> &str {
-        &self.repo_url
-    }
-    
-    pub fn template_id(&self) -> &str {
-        &self.template_id
-    }
-}
-
-#[derive(Debug, PartialEq)]
-pub enum Language {
-    #[allow(dead_code)]
-    Neutral,
-    Rust,
-    JavaScript,
-    Python,
-    Go,
-    Other(String),
+    });
+    index.instrument(tracing::info_span!("fetch index")).await
 }
 
-impl Language {
-    pub fn is_match(&self, lang: &str) -> bool {
-        let lang = lang.to_lowercase();
-        match self {
-            Self::Neutral => true, // TODO: or... false?
-            Self::Rust => lang == "rust" || lang == "rs",
-            Self::JavaScript => lang == "javascript" || lang == "js" || lang == "typescript" || lang == "ts",
-            Self::Python => lang == "python" || lang == "python3" || lang == "py",
-            Self::Go => lang == "go" || lang == "tinygo" || lang == "golang",
-            Self::Other(name) => lang == name.as_str(),
-        }
+// Never goes to the network, for callers that must be fast such as shell completion
+pub fn cached_index() -> Option<Vec<IndexEntry>> {
+    if let Some(path) = &settings::get().hub_file {
+        return read_index_file(path).ok();
     }
+    let cached = cache::index_cache()?.read(settings::get().hub_url())?;
+    serde_json::from_slice(&cached.body).ok()
 }
 
-#[derive(Debug, PartialEq)]
-pub enum Category {
-    // Component,
-    Library,
-    Plugin,
-    Template,
-    Sample,
-    Other(String),
-}
-
-impl Category {
-    pub fn parse(value: &str) -> Self {
-        match value.to_lowercase().as_str() {
-            "library" => Category::Library,
-            "plugin" => Category::Plugin,
-            "sample" => Category::Sample,
-            "template" => Category::Template,
-            _ => Category::Other(value.to_string()),
-        }
+// The file the index is read from, for other programs to read it too
+pub fn index_path() -> Option<std::path::PathBuf> {
+    match &settings::get().hub_file {
+        Some(path) => Some(path.clone()),
+        None => Some(cache::index_cache()?.index_path(settings::get().hub_url())),
     }
 }
 
-fn truncate_to_word_boundary(source: &str, max_len: usize, min_len: usize) -> &str {
-    if source.len() <= max_len {
-        return source;
+// Sources in the file may be relative to it, as they are in a `hub mirror` directory
+fn read_index_file(path: &std::path::Path) -> Result<Vec<IndexEntry>, HubError> {
+    let body = std::fs::read(path).map_err(|source| HubError::IndexFile { path: path.to_owned(), source })?;
+    let mut entries: Vec<IndexEntry> = serde_json::from_slice(&body)?;
+    let base = path.parent().unwrap_or(std::path::Path::new("."));
+    for entry in &mut entries {
+        entry.resolve_relative_sources(base);
     }
+    Ok(entries)
+}
 
-    let mut index = max_len - 1;
-    loop {
-        let ch = source.chars().nth(index).unwrap();
-        if ch.is_whitespace() {
-            return source[..index].trim();
-        }
-        index = index - 1;
-        if index < min_len {
-            break;
+// The library client, set up with the Hub CLI's settings, cache and stored
+// credentials. Retries and progress are added for each request.
+fn client() -> Result<HubClient, HubError> {
+    let settings = settings::get();
+    let http = http::client().map_err(|e| HubError::HttpClient(format!("{e:#}")))?;
+    let (hub_url, mirrors) = settings.hub_urls.split_first().ok_or(HubError::NoHubUrl)?;
+    let mut client = HubClient::new(hub_url.clone())
+        .with_http_client(http)
+        .with_mirrors(mirrors.iter().cloned())
+        // --refresh is for when the cache is suspect, so don't let the Hub vouch for it
+        .with_refresh(settings.refresh)
+        .on_stale_index(|e, age| {
+            style::warning!("{e}\nUsing the cached index from {} minutes ago.", age.as_secs() / 60);
+        });
+    if let Some(index_cache) = cache::index_cache() {
+        client = client.with_cache(index_cache, settings.cache_ttl);
+    }
+    for hub_url in &settings.hub_urls {
+        if let Some(credentials) = auth::load(hub_url) {
+            client = client.with_credentials(hub_url.clone(), credentials);
         }
     }
-
-    &source[..max_len]
+    Ok(client)
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    #[test]
-    fn truncation() {
-        assert_eq!("Hello world", truncate_to_word_boundary("Hello world bibblybobbly", 15, 2));
-        assert_eq!("Hello world", truncate_to_word_boundary("Hello world", 15, 2));
-        assert_eq!("Hello", truncate_to_word_boundary("Hello world", 7, 2));
-        assert_eq!("Hello", truncate_to_word_boundary("Hello world", 5, 2));
-        assert_eq!("Hell", truncate_to_word_boundary("Hello world", 4, 2));
-    }
+pub async fn check_reachable(hub_url: &url::Url) -> Result<(), HubError> {
+    client()?.fetch_index_from(hub_url, None).await?;
+    Ok(())
 }
@@ -0,0 +1,286 @@
+use anyhow::{anyhow, Context};
+
+// The file a Hub entry is submitted as: TOML metadata, then `---`, then a
+// markdown description, as the Hub website's content files are written
+pub const DEFAULT_FILE: &str = "hub-entry.md";
+
+pub const CATEGORIES: &[&str] = &["Template", "Sample", "Plugin", "Component", "Library"];
+pub const LANGUAGES: &[&str] = &["Rust", "JS/TS", "Python", "Go", "Neutral"];
+
+const MAX_TITLE_LEN: usize = 60;
+const MAX_SUMMARY_LEN: usize = 200;
+
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Metadata {
+    pub title: String,
+    #[serde(default = "default_render_template")]
+    pub template: String,
+    pub date: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub extra: Extra,
+}
+
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Extra {
+    pub author: String,
+    #[serde(rename = "type", default = "default_document_type")]
+    pub document_type: String,
+    pub category: String,
+    pub language: String,
+    pub created_at: String,
+    pub last_updated: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spin_version: Option<String>,
+    pub summary: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_source: Option<String>,
+}
+
+fn default_render_template() -> String {
+    "render_hub_content_body".to_owned()
+}
+
+fn default_document_type() -> String {
+    "hub_document".to_owned()
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HubEntry {
+    pub metadata: Metadata,
+    pub description: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Problem {
+    pub field: &'static str,
+    pub message: String,
+    // Warnings don't stop an entry being accepted
+    pub warning: bool,
+}
+
+impl HubEntry {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let (metadata, description) = match text.split_once("\n---") {
+            Some((metadata, description)) => (metadata, description.trim_start_matches('-').trim()),
+            None => (text, ""),
+        };
+        let metadata = toml::from_str(metadata).context("The entry's metadata is not valid")?;
+        Ok(Self { metadata, description: description.to_owned() })
+    }
+
+    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}. Create it with `hub entry init`", path.display()))?;
+        Self::parse(&text).with_context(|| format!("{} is not a valid Hub entry", path.display()))
+    }
+
+    pub fn render(&self) -> anyhow::Result<String> {
+        let metadata = toml::to_string(&self.metadata).map_err(|e| anyhow!("Can't write the entry's metadata: {e}"))?;
+        Ok(format!("{metadata}\n---\n\n{}\n", self.description.trim()))
+    }
+
+    // The entry as it would be in the Hub index, where its ID comes from the
+    // name of its file
+    pub fn to_index_entry(&self, id: &str) -> anyhow::Result<spin_hub_api::IndexEntry> {
+        let metadata = &self.metadata;
+        let extra = &metadata.extra;
+        let json = serde_json::json!({
+            "title": metadata.title,
+            "summary": extra.summary,
+            "description": Some(self.description.as_str()).filter(|d| !d.trim().is_empty()),
+            "category": extra.category,
+            "language": extra.language,
+            "author": extra.author,
+            "tags": metadata.tags,
+            "repo_url": extra.url,
+            "template_id": extra.template_id.clone().unwrap_or_default(),
+            "path": format!("/hub/preview/{id}"),
+            "artifact_source": extra.artifact_source,
+            "last_updated": extra.last_updated,
+            "spin_version": extra.spin_version,
+        });
+        serde_json::from_value(json).context("The entry can't be made into an index entry")
+    }
+
+    pub fn problems(&self) -> Vec<Problem> {
+        let metadata = &self.metadata;
+        let extra = &metadata.extra;
+        let mut problems = vec![];
+        let mut check = |field: &'static str, result: Result<(), String>| {
+            if let Err(message) = result {
+                problems.push(Problem { field, message, warning: false });
+            }
+        };
+        check("title", check_title(&metadata.title));
+        check("summary", check_summary(&extra.summary));
+        check("tags", check_tags(&metadata.tags));
+        check("category", check_one_of(&extra.category, CATEGORIES));
+        check("language", check_one_of(&extra.language, LANGUAGES));
+        check("author", check_not_empty(&extra.author));
+        check("url", check_url(&extra.url));
+        if let Some(spin_version) = &extra.spin_version {
+            check("spin_version", check_spin_version(spin_version));
+        }
+        if let Some(artifact_source) = &extra.artifact_source {
+            check("artifact_source", check_url(artifact_source));
+        }
+        if extra.category == "Template" {
+            check("template_id", check_not_empty(extra.template_id.as_deref().unwrap_or_default()));
+        }
+
+        let mut warn = |field: &'static str, message: &str| {
+            problems.push(Problem { field, message: message.to_owned(), warning: true });
+        };
+        if extra.spin_version.is_none() {
+            warn("spin_version", "Say which Spin versions the entry works with, so users of other versions are warned");
+        }
+        if matches!(extra.category.as_str(), "Plugin" | "Component") && extra.artifact_source.is_none() {
+            warn("artifact_source", "Without an artifact source, the Hub CLI can't install this entry");
+        }
+        if self.description.trim().is_empty() {
+            warn("description", "Add a description after the `---` line, to tell users how to use the entry");
+        }
+        problems
+    }
+}
+
+// Midnight UTC today, as the Hub's dates are written
+pub fn today() -> String {
+    let days = (std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs() / 86400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{year:04}-{month:02}-{day:02}T00:00:00Z")
+}
+
+// Howard Hinnant's civil_from_days
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+pub fn check_not_empty(value: &str) -> Result<(), String> {
+    if value.trim().is_empty() {
+        Err("must not be empty".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+pub fn check_title(title: &str) -> Result<(), String> {
+    check_not_empty(title)?;
+    if title.chars().count() > MAX_TITLE_LEN {
+        return Err(format!("must be at most {MAX_TITLE_LEN} characters"));
+    }
+    Ok(())
+}
+
+pub fn check_summary(summary: &str) -> Result<(), String> {
+    check_not_empty(summary)?;
+    if summary.chars().count() > MAX_SUMMARY_LEN {
+        return Err(format!("must be at most {MAX_SUMMARY_LEN} characters. Put the details in the description"));
+    }
+    Ok(())
+}
+
+// Tags are matched as single lowercase words
+pub fn check_tags(tags: &[String]) -> Result<(), String> {
+    if tags.is_empty() {
+        return Err("give at least one tag, so the entry can be found".to_owned());
+    }
+    match tags.iter().find(|t| t.is_empty() || t.chars().any(|c| c.is_whitespace() || c.is_uppercase())) {
+        Some(tag) => Err(format!("'{tag}' should be a single lowercase word")),
+        None => Ok(()),
+    }
+}
+
+pub fn check_one_of(value: &str, allowed: &[&str]) -> Result<(), String> {
+    if allowed.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("'{value}' must be one of {}", allowed.join(", ")))
+    }
+}
+
+pub fn check_url(url: &str) -> Result<(), String> {
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "https" | "http" | "oci") => Ok(()),
+        Ok(_) => Err(format!("'{url}' must be an https URL")),
+        Err(e) => Err(format!("'{url}' is not a valid URL ({e})")),
+    }
+}
+
+pub fn check_spin_version(requirement: &str) -> Result<(), String> {
+    match spin_hub_api::compat::parse_requirement(requirement) {
+        Some(_) => Ok(()),
+        None => Err(format!("'{requirement}' is not a version requirement such as '>=v2.0'")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ENTRY: &str = r#"title = "Redis worker"
+date = "2024-01-02T00:00:00Z"
+tags = ["redis", "rust"]
+
+[extra]
+author = "fermyon"
+category = "Template"
+language = "Rust"
+created_at = "2024-01-02T00:00:00Z"
+last_updated = "2024-01-02T00:00:00Z"
+summary = "A Redis-triggered component"
+url = "https://github.com/fermyon/redis-worker"
+template_id = "redis-rust"
+
+---
+
+Run `spin build`.
+"#;
+
+    #[test]
+    fn entries_round_trip() {
+        let entry = HubEntry::parse(ENTRY).unwrap();
+        assert_eq!("Redis worker", entry.metadata.title);
+        assert_eq!("hub_document", entry.metadata.extra.document_type);
+        assert_eq!("Run `spin build`.", entry.description);
+
+        let index_entry = entry.to_index_entry("redis-worker").unwrap();
+        assert_eq!("redis-worker", index_entry.id());
+        assert_eq!("redis-rust", index_entry.template_id());
+
+        let reparsed = HubEntry::parse(&entry.render().unwrap()).unwrap();
+        assert_eq!(entry.metadata.extra.template_id, reparsed.metadata.extra.template_id);
+        assert_eq!(entry.description, reparsed.description);
+    }
+
+    #[test]
+    fn days_are_converted_to_dates() {
+        assert_eq!((1970, 1, 1), civil_from_days(0));
+        assert_eq!((2024, 2, 29), civil_from_days(19782));
+    }
+
+    #[test]
+    fn problems_are_reported_by_field() {
+        let mut entry = HubEntry::parse(ENTRY).unwrap();
+        assert!(entry.problems().iter().all(|p| p.warning));
+
+        entry.metadata.tags = vec!["Redis Rust".to_owned()];
+        entry.metadata.extra.template_id = None;
+        let errors = entry.problems().into_iter().filter(|p| !p.warning).map(|p| p.field).collect::<Vec<_>>();
+        assert_eq!(vec!["tags", "template_id"], errors);
+    }
+}
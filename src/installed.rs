@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+
+// Spin doesn't record where templates and plugins came from, so the Hub CLI
+// keeps its own lists of the ones it has installed
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct InstalledTemplate {
+    pub id: String,
+    pub repo: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    // The Hub entry, if the template came from the Hub
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct InstalledPlugin {
+    pub entry: String,
+    pub name: String,
+    // Unknown if the plugin was installed with --no-verify, which doesn't read the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("spin-hub"))
+}
+
+fn templates_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("installed-templates.json"))
+}
+
+fn plugins_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("installed-plugins.json"))
+}
+
+pub fn templates() -> anyhow::Result<Vec<InstalledTemplate>> {
+    read_list(templates_path())
+}
+
+pub fn plugins() -> anyhow::Result<Vec<InstalledPlugin>> {
+    read_list(plugins_path())
+}
+
+pub fn record_template(template: InstalledTemplate) -> anyhow::Result<()> {
+    let mut templates = templates()?;
+    templates.retain(|t| t.id != template.id);
+    templates.push(template);
+    save_templates(&templates)
+}
+
+pub fn forget_template(id: &str) -> anyhow::Result<()> {
+    let mut templates = templates()?;
+    templates.retain(|t| t.id != id);
+    save_templates(&templates)
+}
+
+pub fn record_plugin(plugin: InstalledPlugin) -> anyhow::Result<()> {
+    let mut plugins = plugins()?;
+    plugins.retain(|p| p.entry != plugin.entry);
+    plugins.push(plugin);
+    write_list(plugins_path(), &plugins)
+}
+
+fn save_templates(templates: &[InstalledTemplate]) -> anyhow::Result<()> {
+    write_list(templates_path(), templates)
+}
+
+fn read_list<T: serde::de::DeserializeOwned>(path: Option<PathBuf>) -> anyhow::Result<Vec<T>> {
+    let Some(path) = path else {
+        return Ok(vec![]);
+    };
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&text)
+        .with_context(|| format!("{} is not valid", path.display()))
+}
+
+fn write_list<T: serde::Serialize>(path: Option<PathBuf>, items: &[T]) -> anyhow::Result<()> {
+    let path = path.ok_or_else(|| anyhow!("Can't determine the data directory"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(items)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+pub const EXIT_CODE: i32 = 130;
+
+// Paths that are only partly created, to remove if the user presses Ctrl-C
+static PENDING: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+// Ctrl-C waits while something that can't be undone is being done
+static DEFERRED: AtomicUsize = AtomicUsize::new(0);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+// The handler runs on its own task because the main thread may be blocked in
+// a prompt. Child processes such as git get the signal too, so they stop by
+// themselves.
+pub fn install_handler() {
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        if DEFERRED.load(Ordering::SeqCst) == 0 {
+            exit();
+        }
+    });
+}
+
+// Also for when Ctrl-C arrives as a key press, as it does in the terminal UI
+pub fn exit() -> ! {
+    for path in pending().iter() {
+        remove(path);
+    }
+    _ = crossterm::execute!(std::io::stderr(), crossterm::cursor::Show);
+    eprintln!();
+    eprintln!("Interrupted");
+    std::process::exit(EXIT_CODE);
+}
+
+// Removes `path` if interrupted while the guard is alive
+#[must_use]
+pub struct CleanupGuard(PathBuf);
+
+pub fn remove_on_interrupt(path: impl Into<PathBuf>) -> CleanupGuard {
+    let path = path.into();
+    pending().push(path.clone());
+    CleanupGuard(path)
+}
+
+impl Drop for CleanupGuard {
+    fn drop(&mut self) {
+        let mut pending = pending();
+        if let Some(idx) = pending.iter().position(|p| p == &self.0) {
+            pending.remove(idx);
+        }
+    }
+}
+
+// Holds off Ctrl-C until the guard is dropped, for changes outside the Hub
+// CLI's own files that would otherwise be left half made
+#[must_use]
+pub struct Deferral(());
+
+pub fn defer() -> Deferral {
+    DEFERRED.fetch_add(1, Ordering::SeqCst);
+    Deferral(())
+}
+
+impl Drop for Deferral {
+    fn drop(&mut self) {
+        if DEFERRED.fetch_sub(1, Ordering::SeqCst) == 1 && INTERRUPTED.load(Ordering::SeqCst) {
+            exit();
+        }
+    }
+}
+
+fn pending() -> std::sync::MutexGuard<'static, Vec<PathBuf>> {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn remove(path: &Path) {
+    if path.is_dir() {
+        _ = std::fs::remove_dir_all(path);
+    } else if path.exists() {
+        _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,140 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use tracing::span;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::{LookupSpan, Registry};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::settings::GlobalArgs;
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+// Keeps the trace file open until the end of the program
+pub struct LogGuard {
+    _chrome: Option<tracing_chrome::FlushGuard>,
+}
+
+// SPIN_HUB_LOG takes tracing filter directives such as `hub=debug,reqwest=trace`
+// and overrides the verbosity flags
+pub fn init(args: &GlobalArgs) -> anyhow::Result<LogGuard> {
+    let filter = match EnvFilter::try_from_env("SPIN_HUB_LOG") {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::new(default_directive(args.verbose, args.quiet)),
+    };
+
+    let mut layers: Vec<BoxedLayer> = vec![];
+    match &args.log_file {
+        Some(path) => {
+            let file = std::fs::File::create(path)
+                .with_context(|| format!("Failed to create log file {}", path.display()))?;
+            layers.push(tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .with_filter(filter)
+                .boxed());
+        }
+        None => layers.push(tracing_subscriber::fmt::layer()
+            .with_target(args.verbose > 1)
+            .without_time()
+            .with_writer(std::io::stderr)
+            .with_filter(filter)
+            .boxed()),
+    }
+
+    if args.timings {
+        *started() = Some(Instant::now());
+        layers.push(TimingsLayer.with_filter(own_spans()).boxed());
+    }
+
+    let mut chrome_guard = None;
+    if let Some(path) = &args.trace_file {
+        let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new().file(path).include_args(true).build();
+        layers.push(layer.with_filter(own_spans()).boxed());
+        chrome_guard = Some(guard);
+    }
+
+    tracing_subscriber::registry().with(layers).init();
+    Ok(LogGuard { _chrome: chrome_guard })
+}
+
+// Only the Hub CLI's own logs, until -vvv asks for everything
+fn default_directive(verbosity: u8, quiet: bool) -> &'static str {
+    match (quiet, verbosity) {
+        (true, _) => "hub=error,spin_hub_api=error",
+        (false, 0) => "hub=warn,spin_hub_api=warn",
+        (false, 1) => "hub=info,spin_hub_api=info",
+        (false, 2) => "hub=debug,spin_hub_api=debug",
+        (false, _) => "trace",
+    }
+}
+
+fn own_spans() -> Targets {
+    Targets::new()
+        .with_target("hub", LevelFilter::INFO)
+        .with_target("spin_hub_api", LevelFilter::INFO)
+}
+
+static TIMINGS: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+static STARTED: Mutex<Option<Instant>> = Mutex::new(None);
+
+fn started() -> std::sync::MutexGuard<'static, Option<Instant>> {
+    STARTED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Records how long each span was open
+struct TimingsLayer;
+
+struct SpanStart(Instant);
+
+impl<S> Layer<S> for TimingsLayer
+where
+    S: tracing::Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanStart(Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        if let Some(SpanStart(start)) = span.extensions().get::<SpanStart>() {
+            TIMINGS.lock().unwrap_or_else(|e| e.into_inner()).push((span.metadata().name(), start.elapsed()));
+        }
+    }
+}
+
+pub fn print_timings() {
+    // Totals for each phase, in the order the phases first ran
+    let mut phases: Vec<(&str, usize, Duration)> = vec![];
+    for (name, duration) in TIMINGS.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+        match phases.iter_mut().find(|(phase, _, _)| phase == name) {
+            Some((_, count, total)) => {
+                *count += 1;
+                *total += *duration;
+            }
+            None => phases.push((name, 1, *duration)),
+        }
+    }
+
+    let mut table = crate::output::table(crate::output::OutputFormat::Table);
+    table.set_header(["Phase", "Count", "Time"]);
+    for (name, count, total) in phases {
+        table.add_row([name.to_owned(), count.to_string(), format_duration(total)]);
+    }
+    if let Some(started) = *started() {
+        table.add_row(["total".to_owned(), String::new(), format_duration(started.elapsed())]);
+    }
+    eprintln!("{table}");
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
@@ -1,31 +1,218 @@
-use clap::{Parser};
+use std::process::ExitCode;
 
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+
+mod archive;
+mod auth;
+mod cache;
 mod commands;
+mod config;
+mod exit;
+mod fuzzy;
 mod git;
+mod github;
+mod history;
+mod http;
 mod hub_api;
+mod hub_entry;
+mod installed;
+mod interrupt;
+mod logging;
+mod markdown;
+mod oci;
+mod output;
+mod pager;
+mod progress;
+mod provenance;
+mod ranking;
+mod readme;
+mod retry;
+mod settings;
 mod spin;
+mod stars;
+mod style;
+mod telemetry;
+mod trust;
+mod verify;
 
-use commands::{NewCommand, RunCommand, SearchCommand};
+use commands::{AddCommand, AuthorsCommand, BrowseCommand, CacheCommand, CategoriesCommand, CompareCommand, CompleteCommand, CompletionsCommand, ComponentCommand, ConfigCommand, DiffCommand, DoctorCommand, EntryCommand, HistoryCommand, IndexCommand, LoginCommand, LogoutCommand, MirrorCommand, NewCommand, OpenCommand, OutdatedCommand, PluginCommand, RestoreCommand, RunCommand, SampleCommand, SearchCommand, SelfCommand, ServeCommand, ShowCommand, StackCommand, StarCommand, StarsCommand, StatsCommand, TagsCommand, TelemetryCommand, TemplatesCommand, TrustCommand, UnstarCommand, UpdateNotice, UpgradeCommand};
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    HubCommand::parse().run().await
+async fn main() -> ExitCode {
+    let matches = Hub::command().get_matches();
+    let hub = Hub::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command = telemetry::command_path(&Hub::command(), &matches);
+    // Taken now, so errors before settings are resolved are reported the same way
+    let error_format = hub.global.error_format;
+    match run(hub, &command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            exit::report(&e, error_format);
+            ExitCode::from(exit::code(&e))
+        }
+    }
+}
+
+async fn run(hub: Hub, command: &str) -> anyhow::Result<()> {
+    let _log_guard = logging::init(&hub.global)?;
+    let config = config::load()?;
+    // `config set` can create a profile, so it doesn't need one to exist yet
+    let config = match hub.global.profile.as_deref() {
+        Some(profile) if !matches!(hub.command, HubCommand::Config(_)) => config.with_profile(profile)?,
+        _ => config,
+    };
+    settings::init(settings::Settings::resolve(&hub.global, &config)?);
+    let settings = settings::get();
+    style::init(settings.color, settings.porcelain);
+    // Not the whole of Settings, which has credentials in it
+    tracing::debug!(profile = ?settings.profile, hub_urls = ?settings.hub_urls, hub_file = ?settings.hub_file, cache_ttl = ?settings.cache_ttl, "Resolved settings");
+    git::configure(&settings.git_credentials, &settings.git_ssh_hosts);
+    let update_notice = hub.command.shows_update_notice().then(UpdateNotice::start);
+    let paged = if hub.command.is_paged() && pager::should_page() { pager::run_paged().await } else { None };
+    let is_paged = paged.is_some();
+    let result = match paged {
+        Some(result) => result,
+        None => {
+            interrupt::install_handler();
+            hub.command.run().await
+        }
+    };
+    if let Some(update_notice) = update_notice {
+        update_notice.finish().await;
+    }
+    // Completion runs on every Tab press, so it isn't worth counting. Paged
+    // commands are counted by the copy that ran them.
+    if !matches!(hub.command, HubCommand::Complete(_)) && !is_paged {
+        telemetry::record(command, &result).await;
+    }
+    if hub.global.timings {
+        logging::print_timings();
+    }
+    result
 }
 
 #[derive(Parser)]
-#[clap(about = "Commands for using content from the Spin Up Hub")]
+#[clap(about = "Commands for using content from the Spin Up Hub", after_long_help = exit::CODES_HELP)]
+struct Hub {
+    #[clap(flatten)]
+    global: settings::GlobalArgs,
+
+    #[clap(subcommand)]
+    command: HubCommand,
+}
+
+#[derive(Subcommand)]
 enum HubCommand {
+    Add(AddCommand),
+    Authors(AuthorsCommand),
+    Browse(BrowseCommand),
+    #[clap(subcommand, about = "Inspect and clean the Hub CLI's cache")]
+    Cache(CacheCommand),
+    Categories(CategoriesCommand),
+    Compare(CompareCommand),
+    Completions(CompletionsCommand),
+    #[clap(name = "__complete", hide = true)]
+    Complete(CompleteCommand),
+    #[clap(subcommand, about = "Add prebuilt Wasm components from the Hub to applications")]
+    Component(ComponentCommand),
+    #[clap(subcommand, about = "View and change Hub CLI settings")]
+    Config(ConfigCommand),
+    Diff(DiffCommand),
+    Doctor(DoctorCommand),
+    #[clap(subcommand, about = "Prepare entries to submit to the Hub")]
+    Entry(EntryCommand),
+    History(HistoryCommand),
+    #[clap(subcommand, about = "Export and import the Hub index")]
+    Index(IndexCommand),
+    Login(LoginCommand),
+    Logout(LogoutCommand),
+    Mirror(MirrorCommand),
     New(NewCommand),
+    Open(OpenCommand),
+    Outdated(OutdatedCommand),
+    #[clap(subcommand, about = "Install Spin plugins from the Hub")]
+    Plugin(PluginCommand),
+    Restore(RestoreCommand),
     Run(RunCommand),
+    Sample(SampleCommand),
+    #[clap(visible_alias = "list")]
     Search(SearchCommand),
+    #[clap(name = "self", subcommand, about = "Manage the Hub CLI itself")]
+    Itself(SelfCommand),
+    Serve(ServeCommand),
+    Show(ShowCommand),
+    #[clap(subcommand, about = "Create applications that combine several templates, by hand or from blueprint files")]
+    Stack(StackCommand),
+    Star(StarCommand),
+    Stars(StarsCommand),
+    Stats(StatsCommand),
+    Tags(TagsCommand),
+    #[clap(subcommand, about = "Choose whether to send anonymous usage reports")]
+    Telemetry(TelemetryCommand),
+    #[clap(subcommand, about = "Manage templates installed from the Hub")]
+    Templates(TemplatesCommand),
+    #[clap(subcommand, about = "Choose whose templates and plugins to trust")]
+    Trust(TrustCommand),
+    Unstar(UnstarCommand),
+    Upgrade(UpgradeCommand),
+    // Anything else is run as a `spin-hub-*` program from PATH
+    #[clap(external_subcommand)]
+    External(Vec<String>),
 }
 
 impl HubCommand {
+    // Not for commands that update the CLI, run in the background, feed the shell or aren't ours
+    fn shows_update_notice(&self) -> bool {
+        !matches!(self, Self::Itself(_) | Self::Serve(_) | Self::Completions(_) | Self::Complete(_) | Self::External(_))
+    }
+
+    // Listings that can run long, and never prompt
+    fn is_paged(&self) -> bool {
+        matches!(self, Self::Search(_) | Self::Show(_) | Self::Stars(_) | Self::History(_) | Self::Authors(_) | Self::Categories(_) | Self::Tags(_) | Self::Stats(_))
+    }
+
     async fn run(&self) -> anyhow::Result<()> {
         match self {
+            Self::Add(cmd) => cmd.run().await,
+            Self::Authors(cmd) => cmd.run().await,
+            Self::Browse(cmd) => cmd.run().await,
+            Self::Cache(cmd) => cmd.run().await,
+            Self::Categories(cmd) => cmd.run().await,
+            Self::Compare(cmd) => cmd.run().await,
+            Self::Completions(cmd) => cmd.run().await,
+            Self::Complete(cmd) => cmd.run().await,
+            Self::Component(cmd) => cmd.run().await,
+            Self::Config(cmd) => cmd.run().await,
+            Self::Diff(cmd) => cmd.run().await,
+            Self::Doctor(cmd) => cmd.run().await,
+            Self::Entry(cmd) => cmd.run().await,
+            Self::History(cmd) => cmd.run().await,
+            Self::Index(cmd) => cmd.run().await,
+            Self::Login(cmd) => cmd.run().await,
+            Self::Logout(cmd) => cmd.run().await,
+            Self::Mirror(cmd) => cmd.run().await,
             Self::New(cmd) => cmd.run().await,
+            Self::Open(cmd) => cmd.run().await,
+            Self::Outdated(cmd) => cmd.run().await,
+            Self::Plugin(cmd) => cmd.run().await,
+            Self::Restore(cmd) => cmd.run().await,
             Self::Run(cmd) => cmd.run().await,
+            Self::Sample(cmd) => cmd.run().await,
             Self::Search(cmd) => cmd.run().await,
+            Self::Itself(cmd) => cmd.run().await,
+            Self::Serve(cmd) => cmd.run().await,
+            Self::Show(cmd) => cmd.run().await,
+            Self::Stack(cmd) => cmd.run().await,
+            Self::Star(cmd) => cmd.run().await,
+            Self::Stars(cmd) => cmd.run().await,
+            Self::Stats(cmd) => cmd.run().await,
+            Self::Tags(cmd) => cmd.run().await,
+            Self::Telemetry(cmd) => cmd.run().await,
+            Self::Templates(cmd) => cmd.run().await,
+            Self::Trust(cmd) => cmd.run().await,
+            Self::Unstar(cmd) => cmd.run().await,
+            Self::Upgrade(cmd) => cmd.run().await,
+            Self::External(args) => commands::run_external(args).await,
         }
     }
 }
@@ -0,0 +1,191 @@
+use crossterm::style::Stylize;
+use pulldown_cmark::{Event, HeadingLevel, Tag};
+
+use crate::style;
+
+// Renders markdown for reading in the terminal: styles when color is on, and
+// plain text otherwise. Links keep their URL, since it can't be clicked.
+pub fn render(markdown: &str) -> String {
+    let mut renderer = Renderer {
+        styled: style::enabled(),
+        ..Default::default()
+    };
+    for event in pulldown_cmark::Parser::new(markdown) {
+        renderer.event(event);
+    }
+    renderer.out.trim_end().to_owned() + "\n"
+}
+
+#[derive(Default)]
+struct Renderer {
+    out: String,
+    styled: bool,
+    bold: usize,
+    italic: usize,
+    in_code_block: bool,
+    // The next number of each ordered list, or None for bullet lists
+    lists: Vec<Option<u64>>,
+    link: Option<String>,
+    link_text: String,
+    // The plain text of the current heading, to underline it
+    heading: Option<String>,
+}
+
+impl Renderer {
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.in_code_block => {
+                for line in text.lines() {
+                    let line = format!("    {line}");
+                    let line = if self.styled { line.dark_grey().to_string() } else { line };
+                    self.out.push_str(&line);
+                    self.out.push('\n');
+                }
+            }
+            Event::Text(text) => self.text(&text),
+            Event::Code(code) => {
+                let code = if self.styled { code.to_string().cyan().to_string() } else { format!("`{code}`") };
+                self.push(&code);
+            }
+            Event::SoftBreak => self.push(" "),
+            Event::HardBreak => self.push("\n"),
+            Event::Rule => self.out.push_str("----\n\n"),
+            Event::TaskListMarker(done) => self.push(if done { "[x] " } else { "[ ] " }),
+            Event::Html(_) | Event::FootnoteReference(_) => (),
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading(..) => {
+                self.bold += 1;
+                self.heading = Some(String::new());
+            }
+            Tag::Strong => self.bold += 1,
+            Tag::Emphasis => self.italic += 1,
+            Tag::CodeBlock(_) => self.in_code_block = true,
+            Tag::List(start) => {
+                // A list inside a list item starts on its own line
+                if !self.lists.is_empty() {
+                    self.out.push('\n');
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "* ".to_owned(),
+                };
+                self.out.push_str(&indent);
+                self.out.push_str(&marker);
+            }
+            Tag::Link(_, url, _) => {
+                self.link = Some(url.to_string());
+                self.link_text.clear();
+            }
+            Tag::Image(_, _, _) => self.push("[image: "),
+            _ => (),
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading(level, ..) => {
+                self.bold -= 1;
+                let heading = self.heading.take().unwrap_or_default();
+                if level == HeadingLevel::H1 {
+                    let width = heading.chars().count();
+                    self.out.push('\n');
+                    self.out.push_str(&"=".repeat(width.min(80)));
+                }
+                self.out.push_str("\n\n");
+            }
+            Tag::Strong => self.bold -= 1,
+            Tag::Emphasis => self.italic -= 1,
+            Tag::Paragraph | Tag::BlockQuote => {
+                if self.lists.is_empty() {
+                    self.out.push_str("\n\n");
+                }
+            }
+            Tag::CodeBlock(_) => {
+                self.in_code_block = false;
+                self.out.push('\n');
+            }
+            Tag::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.out.push('\n');
+                }
+            }
+            Tag::Item => {
+                if !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+            }
+            Tag::Link(_, url, _) => {
+                self.link = None;
+                if self.link_text != url.as_ref() && !url.starts_with('#') {
+                    self.push(&format!(" ({url})"));
+                }
+            }
+            Tag::Image(_, _, _) => self.push("]"),
+            _ => (),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.link.is_some() {
+            self.link_text.push_str(text);
+        }
+        if let Some(heading) = &mut self.heading {
+            heading.push_str(text);
+        }
+        if !self.styled {
+            self.push(text);
+            return;
+        }
+        let mut content = crossterm::style::style(text);
+        if self.bold > 0 {
+            content = content.bold();
+        }
+        if self.italic > 0 {
+            content = content.italic();
+        }
+        if self.link.is_some() {
+            content = content.underlined();
+        }
+        self.push(&content.to_string());
+    }
+
+    fn push(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn plain(markdown: &str) -> String {
+        let mut renderer = Renderer::default();
+        for event in pulldown_cmark::Parser::new(markdown) {
+            renderer.event(event);
+        }
+        renderer.out.trim_end().to_owned()
+    }
+
+    #[test]
+    fn markdown_is_rendered_as_plain_text() {
+        assert_eq!("Usage\n=====\n\nRun `spin build`.", plain("# Usage\n\nRun `spin build`."));
+        assert_eq!("See the docs (https://example.com/docs)", plain("See [the docs](https://example.com/docs)"));
+        assert_eq!("* one\n* two", plain("- one\n- two"));
+        assert_eq!("1. first\n2. second", plain("1. first\n2. second"));
+        assert_eq!("    let x = 1;", plain("```rust\nlet x = 1;\n```"));
+    }
+}
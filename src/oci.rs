@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use reqwest::header::{ACCEPT, WWW_AUTHENTICATE};
+
+use crate::{auth::{self, Credentials}, http, verify};
+
+const MANIFEST_TYPES: &str = "application/vnd.oci.image.manifest.v1+json, application/vnd.docker.distribution.manifest.v2+json";
+
+// Hub entries give OCI sources as oci://REGISTRY/REPOSITORY[:TAG][@DIGEST]
+pub fn is_oci(source: &str) -> bool {
+    source.starts_with("oci://")
+}
+
+#[derive(Debug, PartialEq)]
+struct Reference {
+    registry: String,
+    repository: String,
+    // A tag or a digest
+    reference: String,
+}
+
+impl Reference {
+    // `version`, a tag or digest, replaces the one in the source
+    fn parse(source: &str, version: Option<&str>) -> anyhow::Result<Self> {
+        let invalid = || anyhow!("'{source}' should be of the form oci://REGISTRY/REPOSITORY[:TAG]");
+        let rest = source.strip_prefix("oci://").ok_or_else(invalid)?;
+        let (registry, path) = rest.split_once('/').filter(|(r, p)| !r.is_empty() && !p.is_empty()).ok_or_else(invalid)?;
+        let (repository, reference) = match path.split_once('@') {
+            Some((repository, digest)) => (repository, digest),
+            None => match path.rsplit_once(':') {
+                Some((repository, tag)) if !tag.contains('/') => (repository, tag),
+                _ => (path, "latest"),
+            },
+        };
+        Ok(Self {
+            registry: registry.to_owned(),
+            repository: repository.to_owned(),
+            reference: version.unwrap_or(reference).to_owned(),
+        })
+    }
+
+    fn url(&self, kind: &str, reference: &str) -> String {
+        format!("https://{}/v2/{}/{kind}/{reference}", self.registry, self.repository)
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Manifest {
+    layers: Vec<Descriptor>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    media_type: String,
+    digest: String,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+// Unpacks the artifact's layers into `dest`: archives are extracted and
+// anything else is written as a file. Returns the manifest digest.
+pub async fn pull(source: &str, version: Option<&str>, dest: &Path) -> anyhow::Result<String> {
+    let mut registry = Registry::new(Reference::parse(source, version)?)?;
+    let (manifest, digest) = registry.manifest().await?;
+    std::fs::create_dir_all(dest)?;
+    for layer in &manifest.layers {
+        let blob = registry.blob(layer).await?;
+        if layer.media_type.ends_with("tar+gzip") {
+            tar::Archive::new(flate2::read::GzDecoder::new(blob.as_slice())).unpack(dest)?;
+        } else if layer.media_type.ends_with("tar") {
+            tar::Archive::new(blob.as_slice()).unpack(dest)?;
+        } else {
+            let name = layer.annotations.get("org.opencontainers.image.title")
+                .cloned()
+                .unwrap_or_else(|| layer.digest.replace(':', "-"));
+            // Titles come from the registry, so don't let them point outside `dest`
+            let name = Path::new(&name).file_name().ok_or_else(|| anyhow!("Layer {} has an invalid title", layer.digest))?;
+            std::fs::write(dest.join(name), blob)?;
+        }
+    }
+    Ok(digest)
+}
+
+// A Wasm component, published as an artifact with a single Wasm layer
+pub async fn pull_wasm(source: &str) -> anyhow::Result<Vec<u8>> {
+    let mut registry = Registry::new(Reference::parse(source, None)?)?;
+    let (manifest, _) = registry.manifest().await?;
+    let layer = match manifest.layers.as_slice() {
+        [layer] => layer,
+        layers => layers.iter().find(|l| l.media_type.contains("wasm"))
+            .ok_or_else(|| anyhow!("{source} doesn't contain a Wasm layer"))?,
+    };
+    registry.blob(layer).await
+}
+
+// The digest of the artifact's manifest, which pins it like a Git commit does
+pub async fn resolve_digest(source: &str, version: Option<&str>) -> anyhow::Result<String> {
+    let mut registry = Registry::new(Reference::parse(source, version)?)?;
+    let (_, digest) = registry.manifest().await?;
+    Ok(digest)
+}
+
+struct Registry {
+    client: reqwest::Client,
+    reference: Reference,
+    token: Option<String>,
+}
+
+impl Registry {
+    fn new(reference: Reference) -> anyhow::Result<Self> {
+        Ok(Self { client: http::client()?, reference, token: None })
+    }
+
+    async fn manifest(&mut self) -> anyhow::Result<(Manifest, String)> {
+        let url = self.reference.url("manifests", &self.reference.reference);
+        let bytes = self.get(&url, Some(MANIFEST_TYPES)).await?;
+        let manifest = serde_json::from_slice(&bytes)
+            .with_context(|| format!("{url} is not an OCI image manifest"))?;
+        Ok((manifest, format!("sha256:{}", verify::sha256(&bytes))))
+    }
+
+    async fn blob(&mut self, layer: &Descriptor) -> anyhow::Result<Vec<u8>> {
+        let url = self.reference.url("blobs", &layer.digest);
+        let bytes = self.get(&url, None).await?;
+        let expected = layer.digest.strip_prefix("sha256:")
+            .ok_or_else(|| anyhow!("Layer {} doesn't have a SHA-256 digest", layer.digest))?;
+        verify::check_sha256(&bytes, expected, &url)?;
+        Ok(bytes)
+    }
+
+    // Registries say how to get a token in their response to an
+    // unauthenticated request, so only ask for one when told to
+    async fn get(&mut self, url: &str, accept: Option<&str>) -> anyhow::Result<Vec<u8>> {
+        tracing::debug!(%url, "GET");
+        let mut response = self.send(url, accept).await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED && self.token.is_none() {
+            let challenge = response.headers().get(WWW_AUTHENTICATE).and_then(|v| v.to_str().ok()).map(|v| v.to_owned());
+            self.token = Some(self.fetch_token(challenge.as_deref()).await?);
+            response = self.send(url, accept).await?;
+        }
+        match response.status() {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => Err(anyhow!(
+                "{} refused access to {}. Log in with `hub login --registry {}`",
+                self.reference.registry, self.reference.repository, self.reference.registry
+            )),
+            status if !status.is_success() => Err(anyhow!("Failed to download {url}: {status}")),
+            _ => Ok(response.bytes().await?.to_vec()),
+        }
+    }
+
+    async fn send(&self, url: &str, accept: Option<&str>) -> anyhow::Result<reqwest::Response> {
+        let mut request = self.client.get(url);
+        if let Some(accept) = accept {
+            request = request.header(ACCEPT, accept);
+        }
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        request.send().await.with_context(|| format!("Failed to download {url}"))
+    }
+
+    async fn fetch_token(&self, challenge: Option<&str>) -> anyhow::Result<String> {
+        let credentials = auth::load_stored(&registry_url(&self.reference.registry)?);
+        if let Some(Credentials::Bearer { token }) = credentials {
+            return Ok(token);
+        }
+        let mut params = challenge.and_then(parse_challenge).ok_or_else(|| anyhow!(
+            "{} requires authentication. Log in with `hub login --registry {}`",
+            self.reference.registry, self.reference.registry
+        ))?;
+        let realm = params.remove("realm").unwrap_or_default();
+        params.entry("scope".to_owned()).or_insert_with(|| format!("repository:{}:pull", self.reference.repository));
+
+        let mut request = self.client.get(&realm).query(&params);
+        if let Some(Credentials::Basic { username, password }) = &credentials {
+            request = request.basic_auth(username, Some(password));
+        }
+        let response = request.send().await
+            .and_then(|r| r.error_for_status())
+            .with_context(|| format!("Failed to get a token for {} from {realm}", self.reference.registry))?;
+        let body: serde_json::Value = serde_json::from_slice(&response.bytes().await?)?;
+        body["token"].as_str().or(body["access_token"].as_str())
+            .map(|t| t.to_owned())
+            .ok_or_else(|| anyhow!("{realm} didn't return a token"))
+    }
+}
+
+// Registry credentials are kept like Hub ones, under the registry's URL
+pub fn registry_url(registry: &str) -> anyhow::Result<url::Url> {
+    url::Url::parse(&format!("https://{registry}/")).with_context(|| format!("'{registry}' is not a valid registry host"))
+}
+
+// A `Bearer realm="...",service="...",scope="..."` challenge, as parameters
+// for the token request. None if it isn't a bearer challenge.
+fn parse_challenge(challenge: &str) -> Option<BTreeMap<String, String>> {
+    let params = challenge.strip_prefix("Bearer ")?;
+    let mut parsed = BTreeMap::new();
+    let mut rest = params.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let after = after.strip_prefix('"')?;
+        let (value, after) = after.split_once('"')?;
+        parsed.insert(key.trim().to_owned(), value.to_owned());
+        rest = after.trim_start_matches(',').trim();
+    }
+    parsed.contains_key("realm").then_some(parsed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn references_are_parsed() {
+        let reference = Reference::parse("oci://ghcr.io/fermyon/templates/http-rust:v2", None).unwrap();
+        assert_eq!("ghcr.io", reference.registry);
+        assert_eq!("fermyon/templates/http-rust", reference.repository);
+        assert_eq!("v2", reference.reference);
+
+        assert_eq!("latest", Reference::parse("oci://localhost:5000/t", None).unwrap().reference);
+        assert_eq!("sha256:abc", Reference::parse("oci://ghcr.io/t@sha256:abc", None).unwrap().reference);
+        assert_eq!("v3", Reference::parse("oci://ghcr.io/t:v2", Some("v3")).unwrap().reference);
+        assert!(Reference::parse("https://ghcr.io/t", None).is_err());
+        assert!(Reference::parse("oci://ghcr.io", None).is_err());
+    }
+
+    #[test]
+    fn bearer_challenges_are_parsed() {
+        let parsed = parse_challenge(r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:t:pull""#).unwrap();
+        assert_eq!("https://ghcr.io/token", parsed["realm"]);
+        assert_eq!("ghcr.io", parsed["service"]);
+        assert_eq!("repository:t:pull", parsed["scope"]);
+        assert_eq!(None, parse_challenge(r#"Basic realm="registry""#));
+    }
+}
@@ -0,0 +1,140 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Plain,
+    Json,
+    // One JSON object per line, written as each is ready
+    Jsonl,
+    // Tab-separated, one entry per line with no header, for scripts. The
+    // columns of a listing only change in a major version.
+    Porcelain,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+pub fn print_json<T: serde::Serialize + ?Sized>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+pub struct Table {
+    table: comfy_table::Table,
+    // Rows as porcelain lines, when that's the format
+    lines: Option<Vec<String>>,
+}
+
+impl Table {
+    pub fn set_header(&mut self, header: impl Into<comfy_table::Row>) -> &mut Self {
+        if self.lines.is_none() {
+            self.table.set_header(header);
+        }
+        self
+    }
+
+    pub fn add_row(&mut self, row: impl Into<comfy_table::Row>) -> &mut Self {
+        match &mut self.lines {
+            Some(lines) => lines.push(porcelain_line(&row.into())),
+            None => {
+                self.table.add_row(row);
+            }
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.lines {
+            Some(lines) => write!(f, "{}", lines.join("\n")),
+            None => write!(f, "{}", self.table),
+        }
+    }
+}
+
+pub fn print_json_lines<T: serde::Serialize>(values: impl IntoIterator<Item = T>) -> anyhow::Result<()> {
+    use std::io::Write;
+    let mut stdout = std::io::stdout().lock();
+    for value in values {
+        serde_json::to_writer(&mut stdout, &value)?;
+        writeln!(stdout)?;
+        // So consumers see each one now, even when stdout is a pipe
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+// Rows are kept to one line each, and the columns shrink to fit the terminal,
+// so that long listings stay scannable.
+pub fn table(format: OutputFormat) -> Table {
+    let mut table = comfy_table::Table::new();
+    match format {
+        OutputFormat::Plain => table.load_preset(comfy_table::presets::NOTHING),
+        _ => table.load_preset(comfy_table::presets::ASCII_BORDERS_ONLY_CONDENSED),
+    };
+    table.set_content_arrangement(comfy_table::ContentArrangement::Dynamic);
+    // Output going to a pager isn't a terminal, so it's told how wide that is
+    if let Some(width) = crate::pager::width() {
+        table.set_width(width);
+    }
+    let lines = (format == OutputFormat::Porcelain).then(Vec::new);
+    Table { table, lines }
+}
+
+pub fn single_line_row(cells: Vec<String>) -> comfy_table::Row {
+    let mut row = comfy_table::Row::from(cells);
+    row.max_height(1);
+    row
+}
+
+// What a command produced, set off from what went before. With --quiet only
+// the essential part is printed, such as the path, for use in scripts.
+pub fn print_outcome(message: &str, essential: &str) {
+    if crate::settings::get().quiet {
+        println!("{essential}");
+    } else {
+        println!();
+        println!("{message}");
+    }
+}
+
+// Tabs and line breaks in values would break up the columns
+fn porcelain_line(row: &comfy_table::Row) -> String {
+    row.cell_iter()
+        .map(|cell| cell.content().replace(['\t', '\n', '\r'], " "))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
+
+// Like println!, but left out with --quiet, which only prints what a command
+// produced such as the path of a new application, and with --porcelain
+macro_rules! note {
+    ($($arg:tt)*) => {
+        if !$crate::settings::get().quiet && !$crate::settings::get().porcelain {
+            println!($($arg)*)
+        }
+    };
+}
+pub(crate) use note;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn porcelain_tables_are_tab_separated_without_a_header() {
+        let mut table = table(OutputFormat::Porcelain);
+        table.set_header(["Name", "Tags"]);
+        table.add_row(single_line_row(vec!["Rust HTTP".to_owned(), "http,\trust".to_owned()]));
+        table.add_row(["Redis", "multi\nline"]);
+        assert_eq!("Rust HTTP\thttp, rust\nRedis\tmulti line", table.to_string());
+    }
+}
@@ -0,0 +1,77 @@
+use std::io::IsTerminal;
+use std::process::Stdio;
+
+use anyhow::Context;
+
+use crate::{exit::{self, Failure}, settings, style};
+
+// Set for the copy of the Hub CLI whose output goes to the pager, so it
+// doesn't start another, and so its tables still fit the terminal
+const CHILD_VAR: &str = "SPIN_HUB_PAGED";
+const WIDTH_VAR: &str = "SPIN_HUB_PAGED_WIDTH";
+
+pub fn is_child() -> bool {
+    std::env::var_os(CHILD_VAR).is_some()
+}
+
+pub fn width() -> Option<u16> {
+    std::env::var(WIDTH_VAR).ok()?.parse().ok()
+}
+
+pub fn should_page() -> bool {
+    let settings = settings::get();
+    settings.pager.is_some() && !settings.porcelain && !is_child() && std::io::stdout().is_terminal()
+}
+
+// Runs the Hub CLI again with the same arguments and its output piped into
+// the pager, as there's no portable way to point our own stdout at it. None
+// if the pager can't be started, so the output goes to the terminal instead.
+pub async fn run_paged() -> Option<anyhow::Result<()>> {
+    let pager = settings::get().pager.as_deref()?;
+    let mut words = pager.split_whitespace();
+    let mut command = tokio::process::Command::new(words.next()?);
+    command.args(words).stdin(Stdio::piped());
+    // As git does: quit if it fits on one screen, pass colors through, and leave the output on screen
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let mut pager_process = match command.spawn() {
+        Ok(process) => process,
+        Err(e) => {
+            tracing::debug!("Couldn't start the pager '{pager}': {e}");
+            return None;
+        }
+    };
+    Some(page_into(&mut pager_process).await)
+}
+
+async fn page_into(pager: &mut tokio::process::Child) -> anyhow::Result<()> {
+    // Ctrl-C is for the pager and the command it shows, which stop by themselves
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {}
+    });
+
+    let pager_stdin: Stdio = pager.stdin.take().context("The pager has no input")?.try_into()?;
+    let exe = std::env::current_exe().context("Can't find the running executable")?;
+    // The command must be dropped once started, or it holds the pipe open and the pager never finishes
+    let mut child = {
+        let mut command = tokio::process::Command::new(exe);
+        command.args(std::env::args_os().skip(1))
+            .env(CHILD_VAR, "1")
+            .stdout(pager_stdin);
+        if style::enabled() {
+            command.env("SPIN_HUB_COLOR", "always");
+        }
+        if let Ok((width, _)) = crossterm::terminal::size() {
+            command.env(WIDTH_VAR, width.to_string());
+        }
+        command.spawn()?
+    };
+    let status = child.wait().await?;
+    pager.wait().await?;
+    if status.success() {
+        return Ok(());
+    }
+    let code = status.code().and_then(|c| u8::try_from(c).ok()).unwrap_or(exit::FAILURE);
+    Err(Failure::External { program: "hub".to_owned(), code }.into())
+}
@@ -0,0 +1,68 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::settings;
+
+pub struct Progress {
+    bar: Option<indicatif::ProgressBar>,
+    // What's being done, which the detail from a subprocess is shown after
+    message: Mutex<String>,
+}
+
+impl Progress {
+    pub fn start(message: impl Into<String>) -> Self {
+        let message = message.into();
+        if !settings::get().progress {
+            return Self { bar: None, message: Mutex::new(message) };
+        }
+        let bar = indicatif::ProgressBar::new_spinner();
+        bar.enable_steady_tick(Duration::from_millis(100));
+        bar.set_message(message.clone());
+        Self { bar: Some(bar), message: Mutex::new(message) }
+    }
+
+    pub fn set_message(&self, message: impl Into<String>) {
+        let message = message.into();
+        if let Some(bar) = &self.bar {
+            bar.set_message(message.clone());
+        }
+        *self.message.lock().unwrap() = message;
+    }
+
+    // For one step of several, as `[1/2] Fetching ...`
+    pub fn set_step(&self, step: usize, steps: usize, message: impl AsRef<str>) {
+        self.set_message(format!("[{step}/{steps}] {}", message.as_ref()));
+    }
+
+    // Shows how the current step is going, such as git's transfer progress,
+    // after its message. Empty to clear it.
+    pub fn set_detail(&self, detail: &str) {
+        if let Some(bar) = &self.bar {
+            let message = self.message.lock().unwrap();
+            if detail.is_empty() {
+                bar.set_message(message.clone());
+            } else {
+                bar.set_message(format!("{message} - {detail}"));
+            }
+        }
+    }
+
+    // Must be called before prompting, or the spinner will draw over the prompt
+    pub fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+impl spin_templates::ProgressReporter for Progress {
+    fn report(&self, message: impl AsRef<str>) {
+        self.set_detail(message.as_ref());
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+
+pub const FILE_NAME: &str = ".spin-hub.toml";
+pub const LOCK_FILE_NAME: &str = "spin-hub.lock";
+
+// Where a generated application came from, so `hub upgrade` can render the
+// template again. Values given on the command line or answered at prompts are
+// recorded; the template's defaults aren't.
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Provenance {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+    pub repo: String,
+    pub template_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    // The exact commit used, if the application was created with --lock or
+    // upgraded, so `hub upgrade` can tell the user's changes from the template's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, String>,
+}
+
+impl Provenance {
+    pub fn read(dir: &Path) -> anyhow::Result<Self> {
+        let path = dir.join(FILE_NAME);
+        if !path.exists() {
+            return Err(anyhow!("{} has no {FILE_NAME} file. Only applications created with `hub new` can be upgraded.", dir.display()));
+        }
+        read_file(&path)
+    }
+
+    pub fn write(&self, dir: &Path) -> anyhow::Result<()> {
+        write_file(&dir.join(FILE_NAME), "# Written by `hub new` so that `hub upgrade` can find the template", self)
+    }
+
+    pub fn read_lock(path: &Path) -> anyhow::Result<Self> {
+        let lock: Self = read_file(path)?;
+        if lock.commit.is_none() {
+            return Err(anyhow!("{} doesn't say which template commit to use", path.display()));
+        }
+        Ok(lock)
+    }
+
+    pub fn write_lock(&self, dir: &Path) -> anyhow::Result<()> {
+        write_file(&dir.join(LOCK_FILE_NAME), "# Written by `hub new --lock`. Use `hub restore` to create this application again.", self)
+    }
+}
+
+fn read_file(path: &Path) -> anyhow::Result<Provenance> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&text)
+        .with_context(|| format!("{} is not valid", path.display()))
+}
+
+fn write_file(path: &Path, comment: &str, provenance: &Provenance) -> anyhow::Result<()> {
+    let text = format!("{comment}\n{}", toml::to_string(provenance)?);
+    std::fs::write(path, text)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,147 @@
+use itertools::Itertools;
+
+use crate::hub_api::IndexEntry;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Field {
+    Title,
+    Tags,
+    Summary,
+    Description,
+}
+
+impl Field {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Title => "title",
+            Self::Tags => "tags",
+            Self::Summary => "summary",
+            Self::Description => "description",
+        }
+    }
+
+    fn weight(&self) -> u32 {
+        match self {
+            Self::Title => 4,
+            Self::Tags => 3,
+            Self::Summary => 2,
+            Self::Description => 1,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Relevance {
+    pub score: u32,
+    pub fields: Vec<Field>,
+    // How many of the terms were found
+    pub terms: usize,
+}
+
+impl Relevance {
+    pub fn is_match(&self) -> bool {
+        self.score > 0
+    }
+
+    pub fn matched_fields(&self) -> String {
+        self.fields.iter().map(|f| f.name()).join(", ")
+    }
+}
+
+// A whole-word hit counts double compared to a hit somewhere inside a word,
+// so that e.g. "redis" ranks an entry tagged "redis" above one mentioning "redistribute".
+pub fn score(entry: &IndexEntry, terms: &[String]) -> Relevance {
+    let fields = field_words(entry);
+
+    let mut relevance = Relevance::default();
+
+    for term in terms.iter().map(|t| t.to_lowercase()) {
+        let score = relevance.score;
+        for (field, field_words) in &fields {
+            let hit = if field_words.contains(&term) {
+                2
+            } else if field_words.iter().any(|w| w.contains(&term)) {
+                1
+            } else {
+                0
+            };
+            if hit > 0 {
+                relevance.score += hit * field.weight();
+                if !relevance.fields.contains(field) {
+                    relevance.fields.push(*field);
+                }
+            }
+        }
+        if relevance.score > score {
+            relevance.terms += 1;
+        }
+    }
+
+    relevance.fields.sort();
+    relevance
+}
+
+// Whether any field has `term` as a whole word, for leaving entries out, where
+// "redis" shouldn't also drop entries that "redistribute"
+pub fn has_word(entry: &IndexEntry, term: &str) -> bool {
+    let term = term.to_lowercase();
+    field_words(entry).iter().any(|(_, words)| words.contains(&term))
+}
+
+fn field_words(entry: &IndexEntry) -> [(Field, Vec<String>); 4] {
+    [
+        (Field::Title, entry.title_words()),
+        (Field::Tags, entry.tags()),
+        (Field::Summary, words(entry.summary())),
+        (Field::Description, words(entry.description().unwrap_or_default())),
+    ]
+}
+
+pub fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(title: &str, summary: &str, tags: &[&str]) -> IndexEntry {
+        serde_json::from_value(serde_json::json!({
+            "title": title,
+            "summary": summary,
+            "category": "Template",
+            "language": "Rust",
+            "author": "fermyon",
+            "tags": tags,
+            "repo_url": "https://github.com/fermyon/spin",
+            "template_id": "http-rust",
+            "path": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn whole_words_outrank_partial_matches() {
+        let terms = vec!["redis".to_owned()];
+        let tagged = score(&entry("Key value store", "Stores things", &["redis"]), &terms);
+        let partial = score(&entry("Key value store", "Redistributes things", &[]), &terms);
+        let none = score(&entry("Key value store", "Stores things", &[]), &terms);
+
+        assert!(tagged.score > partial.score);
+        assert_eq!(vec![Field::Tags], tagged.fields);
+        assert_eq!("summary", partial.matched_fields());
+        assert!(!none.is_match());
+    }
+
+    #[test]
+    fn only_whole_words_are_words_of_an_entry() {
+        let entry = entry("Key value store", "Redistributes things", &["kv"]);
+        assert!(has_word(&entry, "KV"));
+        assert!(has_word(&entry, "redistributes"));
+        assert!(!has_word(&entry, "redis"));
+        assert_eq!(1, score(&entry, &["kv".to_owned(), "sql".to_owned()]).terms);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{http, markdown};
+
+// README file names to try, in order
+const NAMES: &[&str] = &["README.md", "readme.md", "README"];
+
+// Only GitHub repositories are supported, since other hosts don't have a
+// common way of serving raw files. Returns None if there's no README.
+pub async fn fetch(repo_url: &str, git_ref: Option<&str>) -> anyhow::Result<Option<String>> {
+    let Some(base) = raw_base_url(repo_url, git_ref.unwrap_or("HEAD")) else {
+        tracing::debug!(%repo_url, "Can't fetch README from a repository not on GitHub");
+        return Ok(None);
+    };
+    let client = http::client()?;
+    for name in NAMES {
+        match http::download(&client, &format!("{base}/{name}")).await {
+            Ok(bytes) => return Ok(Some(String::from_utf8_lossy(&bytes).into_owned())),
+            Err(e) => tracing::debug!(%name, "No README: {e:#}"),
+        }
+    }
+    Ok(None)
+}
+
+pub async fn print(repo_url: &str, git_ref: Option<&str>) -> anyhow::Result<()> {
+    match fetch(repo_url, git_ref).await? {
+        Some(text) => print!("{}", markdown::render(&text)),
+        None => println!("No README found for {repo_url}"),
+    }
+    Ok(())
+}
+
+fn raw_base_url(repo_url: &str, git_ref: &str) -> Option<String> {
+    let url = url::Url::parse(repo_url).ok()?;
+    if url.host_str() != Some("github.com") {
+        return None;
+    }
+    let mut segments = url.path_segments()?.filter(|s| !s.is_empty());
+    let owner = segments.next()?;
+    let repo = segments.next()?;
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    Some(format!("https://raw.githubusercontent.com/{owner}/{repo}/{git_ref}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn raw_urls_are_only_made_for_github_repos() {
+        assert_eq!(
+            Some("https://raw.githubusercontent.com/fermyon/spin/HEAD".to_owned()),
+            raw_base_url("https://github.com/fermyon/spin.git", "HEAD")
+        );
+        assert_eq!(
+            Some("https://raw.githubusercontent.com/fermyon/spin/v2.0".to_owned()),
+            raw_base_url("https://github.com/fermyon/spin/", "v2.0")
+        );
+        assert_eq!(None, raw_base_url("https://gitlab.com/fermyon/spin", "HEAD"));
+    }
+}
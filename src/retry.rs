@@ -0,0 +1,76 @@
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RetryPolicy {
+    // Full jitter: a random delay up to the exponential backoff, so that many
+    // clients that failed together don't all retry together.
+    fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self.base_delay.saturating_mul(2u32.saturating_pow(attempt)).min(MAX_DELAY);
+        let millis = backoff.as_millis() as u64;
+        Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+    }
+
+    pub async fn run<T, E, F, Fut>(&self, is_transient: impl Fn(&E) -> bool, mut f: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Err(e) if attempt + 1 < self.max_attempts && is_transient(&e) => {
+                    let delay = self.delay(attempt);
+                    tracing::info!(attempt = attempt + 1, delay_ms = delay.as_millis() as u64, "Retrying after a transient failure");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn retries_only_transient_errors() {
+        let policy = RetryPolicy { max_attempts: 3, base_delay: Duration::ZERO };
+
+        let mut calls = 0;
+        let result: Result<(), &str> = policy.run(|e: &&str| *e == "transient", || {
+            calls += 1;
+            async { Err("transient") }
+        }).await;
+        assert!(result.is_err());
+        assert_eq!(3, calls);
+
+        let mut calls = 0;
+        let result: Result<(), &str> = policy.run(|e: &&str| *e == "transient", || {
+            calls += 1;
+            async { Err("permanent") }
+        }).await;
+        assert!(result.is_err());
+        assert_eq!(1, calls);
+    }
+}
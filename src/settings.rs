@@ -0,0 +1,257 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::Context;
+
+use crate::config::Config;
+use crate::exit::ErrorFormat;
+use crate::output::{ColorMode, OutputFormat};
+use crate::retry::RetryPolicy;
+
+const DEFAULT_HUB_URL: &str = spin_hub_api::DEFAULT_HUB_URL;
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const DEFAULT_UPDATE_CHECK_HOURS: u64 = 24;
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+#[derive(clap::Args, Debug)]
+pub struct GlobalArgs {
+    #[clap(long, global = true, help = "Fetch the Hub index and template repositories again, even if cached copies are still fresh")]
+    refresh: bool,
+
+    #[clap(short = 'q', long, global = true, conflicts_with = "verbose", help = "Only print what the command produced, such as the path of a new application, without progress or other messages")]
+    pub quiet: bool,
+
+    #[clap(long, global = true, help = "Print listings in a stable format for scripts: tab-separated, one entry per line, with no header")]
+    porcelain: bool,
+
+    #[clap(short = 'v', long, global = true, action = clap::ArgAction::Count, help = "Log what the Hub CLI is doing. Repeat for more detail (-vv, -vvv)")]
+    pub verbose: u8,
+
+    #[clap(long = "log-file", global = true, value_name = "PATH", help = "Write logs to this file instead of the terminal")]
+    pub log_file: Option<PathBuf>,
+
+    #[clap(long, global = true, value_enum, env = "SPIN_HUB_COLOR", help = "When to use color in output. NO_COLOR is respected unless this is 'always' [default: auto]")]
+    color: Option<ColorMode>,
+
+    #[clap(long = "error-format", global = true, value_enum, default_value_t, env = "SPIN_HUB_ERROR_FORMAT", help = "How to print errors: as text, or as JSON with the exit code, a hint and the causes")]
+    pub error_format: ErrorFormat,
+
+    #[clap(long = "no-pager", global = true, help = "Print long output straight to the terminal, instead of through $PAGER")]
+    no_pager: bool,
+
+    #[clap(long, global = true, help = "Show how long each part of the command took")]
+    pub timings: bool,
+
+    #[clap(long = "trace-file", global = true, value_name = "PATH", help = "Write a Chrome trace of the command to this file, for chrome://tracing or Perfetto")]
+    pub trace_file: Option<PathBuf>,
+
+    #[clap(long = "hub-url", global = true, env = "SPIN_HUB_URL", help = "Base URL of the Hub to fetch the index from")]
+    hub_url: Option<String>,
+
+    #[clap(long = "hub-mirror", global = true, env = "SPIN_HUB_MIRRORS", value_delimiter = ',', help = "Fallback Hub URLs, tried in order if the Hub can't be reached")]
+    mirrors: Vec<String>,
+
+    #[clap(long = "hub-file", global = true, env = "SPIN_HUB_FILE", help = "Read the Hub index from this file, such as one written by `hub mirror`, instead of the network")]
+    hub_file: Option<PathBuf>,
+
+    #[clap(long, global = true, env = "SPIN_HUB_PROXY", help = "Proxy to use for Hub requests. By default HTTP_PROXY and HTTPS_PROXY are used")]
+    proxy: Option<String>,
+
+    #[clap(long, global = true, env = "SPIN_HUB_PROFILE", help = "Use the settings from this profile in the config file")]
+    pub profile: Option<String>,
+
+    #[clap(long, global = true, env = "SPIN_HUB_CACERT", help = "Additional PEM-encoded CA certificate to trust for Hub requests")]
+    cacert: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct Settings {
+    pub profile: Option<String>,
+    pub refresh: bool,
+    pub quiet: bool,
+    pub porcelain: bool,
+    pub error_format: ErrorFormat,
+    // Spinners would be drawn over log lines
+    pub progress: bool,
+    pub hub_urls: Vec<url::Url>,
+    pub hub_file: Option<PathBuf>,
+    pub cache_ttl: Duration,
+    pub output: OutputFormat,
+    pub color: ColorMode,
+    pub default_terms: Vec<String>,
+    pub retry: RetryPolicy,
+    pub proxy: Option<String>,
+    pub cacert: Option<PathBuf>,
+    // Public key that Hub signatures are checked against
+    pub trust_root: Option<PathBuf>,
+    // Tokens for HTTPS access to private template repositories, by host
+    pub git_credentials: Vec<(String, String)>,
+    // Hosts to reach over SSH instead of HTTPS, to use the SSH agent
+    pub git_ssh_hosts: Vec<String>,
+    // The command to page long output through, or None not to
+    pub pager: Option<String>,
+    // For GitHub API calls, ahead of a token stored in the keyring
+    pub github_token: Option<String>,
+    // How often to look for updates to the Hub CLI and what it installed, or None not to
+    pub update_check: Option<Duration>,
+}
+
+impl Settings {
+    pub fn resolve(args: &GlobalArgs, config: &Config) -> anyhow::Result<Self> {
+        let hub_url = args.hub_url.as_deref()
+            .or(config.hub_url.as_deref())
+            .unwrap_or(DEFAULT_HUB_URL);
+        let mirrors = if args.mirrors.is_empty() {
+            &config.mirrors
+        } else {
+            &args.mirrors
+        };
+        let hub_urls = std::iter::once(hub_url)
+            .chain(mirrors.iter().map(|m| m.as_str()))
+            .map(parse_base_url)
+            .collect::<anyhow::Result<_>>()?;
+
+        let cache_ttl = env_or("SPIN_HUB_CACHE_TTL", config.cache_ttl)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_CACHE_TTL);
+
+        let default_retry = RetryPolicy::default();
+        let retry = RetryPolicy {
+            max_attempts: env_or("SPIN_HUB_RETRIES", config.retries).unwrap_or(default_retry.max_attempts).max(1),
+            base_delay: env_or("SPIN_HUB_RETRY_DELAY", config.retry_delay).map(Duration::from_millis).unwrap_or(default_retry.base_delay),
+        };
+
+        Ok(Self {
+            profile: args.profile.clone(),
+            refresh: args.refresh,
+            quiet: args.quiet,
+            porcelain: args.porcelain,
+            error_format: args.error_format,
+            progress: !args.quiet && (args.verbose == 0 || args.log_file.is_some()),
+            hub_urls,
+            hub_file: args.hub_file.clone(),
+            cache_ttl,
+            output: if args.porcelain { OutputFormat::Porcelain } else { config.output.unwrap_or_default() },
+            color: args.color.or(config.color).unwrap_or_default(),
+            default_terms: config.default_terms.clone(),
+            retry,
+            proxy: args.proxy.clone(),
+            cacert: args.cacert.clone(),
+            trust_root: env_or("SPIN_HUB_TRUST_ROOT", config.trust_root.clone()),
+            git_credentials: env_list("SPIN_HUB_GIT_CREDENTIALS", &config.git_credentials)
+                .iter()
+                .filter_map(|c| c.split_once('='))
+                .map(|(host, token)| (host.trim().to_owned(), token.trim().to_owned()))
+                .collect(),
+            git_ssh_hosts: env_list("SPIN_HUB_GIT_SSH_HOSTS", &config.git_ssh_hosts),
+            pager: if args.no_pager { None } else { pager(config) },
+            github_token: std::env::var("GITHUB_TOKEN").ok()
+                .or_else(|| std::env::var("GH_TOKEN").ok())
+                .filter(|t| !t.is_empty())
+                .or_else(|| config.github_token.clone()),
+            update_check: update_check(config),
+        })
+    }
+
+    pub fn terms_or_default<'a>(&'a self, terms: &'a [String]) -> &'a [String] {
+        if terms.is_empty() {
+            &self.default_terms
+        } else {
+            terms
+        }
+    }
+
+    // --porcelain wins over a command's --output, which may come from SPIN_HUB_OUTPUT
+    pub fn output_format(&self, requested: Option<OutputFormat>) -> OutputFormat {
+        if self.porcelain {
+            OutputFormat::Porcelain
+        } else {
+            requested.unwrap_or(self.output)
+        }
+    }
+
+    pub fn hub_url(&self) -> &url::Url {
+        &self.hub_urls[0]
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            profile: None,
+            refresh: false,
+            quiet: false,
+            porcelain: false,
+            error_format: ErrorFormat::default(),
+            progress: true,
+            hub_urls: vec![parse_base_url(DEFAULT_HUB_URL).expect("Default Hub URL was malformed")],
+            hub_file: None,
+            cache_ttl: DEFAULT_CACHE_TTL,
+            output: OutputFormat::default(),
+            color: ColorMode::default(),
+            default_terms: vec![],
+            retry: RetryPolicy::default(),
+            proxy: None,
+            cacert: None,
+            trust_root: None,
+            git_credentials: vec![],
+            git_ssh_hosts: vec![],
+            pager: None,
+            github_token: None,
+            update_check: None,
+        }
+    }
+}
+
+// As git does, `cat` means no pager
+fn pager(config: &Config) -> Option<String> {
+    let pager = std::env::var("SPIN_HUB_PAGER").ok()
+        .or_else(|| config.pager.clone())
+        .or_else(|| std::env::var("PAGER").ok())
+        .unwrap_or_else(|| "less".to_owned());
+    Some(pager).filter(|p| !p.trim().is_empty() && p.trim() != "cat")
+}
+
+// Off in CI, where nobody reads the notice
+fn update_check(config: &Config) -> Option<Duration> {
+    let disabled = std::env::var_os("SPIN_HUB_NO_UPDATE_CHECK").is_some() || std::env::var_os("CI").is_some();
+    if disabled || config.update_check == Some(false) {
+        return None;
+    }
+    let hours = config.update_check_hours.unwrap_or(DEFAULT_UPDATE_CHECK_HOURS);
+    Some(Duration::from_secs(hours.saturating_mul(60 * 60)))
+}
+
+fn env_or<T: std::str::FromStr>(var: &str, config_value: Option<T>) -> Option<T> {
+    std::env::var(var)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .or(config_value)
+}
+
+fn env_list(var: &str, config_value: &[String]) -> Vec<String> {
+    match std::env::var(var) {
+        Ok(value) => value.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).map(|s| s.to_owned()).collect(),
+        Err(_) => config_value.to_vec(),
+    }
+}
+
+// Relative joins replace the last path segment unless the base ends with a slash
+fn parse_base_url(url: &str) -> anyhow::Result<url::Url> {
+    let url = if url.ends_with('/') {
+        url.to_owned()
+    } else {
+        format!("{url}/")
+    };
+    url::Url::parse(&url).with_context(|| format!("Invalid Hub URL '{url}'"))
+}
+
+pub fn init(settings: Settings) {
+    _ = SETTINGS.set(settings);
+}
+
+pub fn get() -> &'static Settings {
+    SETTINGS.get_or_init(Settings::default)
+}
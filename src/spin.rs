@@ -1,7 +1,66 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const FALLBACK_VERSION: &str = "2.0.0";
+
+static VERSION: OnceLock<String> = OnceLock::new();
+
+// Spin sets these when running the Hub CLI as a plugin. When they're missing
+// (e.g. when running the binary directly) look for `spin` on the PATH instead.
 pub fn version() -> String {
-    std::env::var("SPIN_VERSION").unwrap_or_else(|_| "2.0.0".to_owned())
+    VERSION.get_or_init(|| {
+        std::env::var("SPIN_VERSION")
+            .ok()
+            .or_else(detect_version)
+            .unwrap_or_else(|| FALLBACK_VERSION.to_owned())
+    }).clone()
+}
+
+// Finds the version without blocking, so that commands can do it while they
+// fetch the index. Later calls to `version()` use the result.
+pub async fn preload_version() {
+    if VERSION.get().is_some() {
+        return;
+    }
+    let version = match std::env::var("SPIN_VERSION") {
+        Ok(version) => Some(version),
+        Err(_) => detect_version_async().await,
+    };
+    _ = VERSION.set(version.unwrap_or_else(|| FALLBACK_VERSION.to_owned()));
+}
+
+// Template repositories can have a branch per Spin release, which
+// `spin templates install --git` prefers to the default branch
+pub fn templates_branch() -> Option<String> {
+    let version = spin_hub_api::compat::parse_spin_version(&version())?;
+    Some(format!("spin/templates/v{}.{}", version.major, version.minor))
+}
+
+pub fn bin_path() -> PathBuf {
+    std::env::var_os("SPIN_BIN_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("spin"))
 }
 
 pub fn bin() -> tokio::process::Command {
-    tokio::process::Command::new(std::env::var("SPIN_BIN_PATH").unwrap())
+    tokio::process::Command::new(bin_path())
+}
+
+pub fn detect_version() -> Option<String> {
+    let output = std::process::Command::new(bin_path()).arg("--version").output().ok()?;
+    parse_version_output(&output)
+}
+
+async fn detect_version_async() -> Option<String> {
+    let output = bin().arg("--version").output().await.ok()?;
+    parse_version_output(&output)
+}
+
+// `spin --version` prints e.g. `spin 2.0.1 (1d72f1c 2023-11-10)`
+fn parse_version_output(output: &std::process::Output) -> Option<String> {
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.split_whitespace().nth(1).map(|v| v.to_owned())
 }
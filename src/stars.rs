@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+
+// Entry IDs the user has starred, kept next to the config file but apart from
+// it, so that starring doesn't rewrite the user's settings
+fn stars_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("spin-hub").join("stars.json"))
+}
+
+pub fn starred() -> anyhow::Result<Vec<String>> {
+    let Some(path) = stars_path() else {
+        return Ok(vec![]);
+    };
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&text)
+        .with_context(|| format!("{} is not valid", path.display()))
+}
+
+// Returns whether the entry wasn't already starred
+pub fn star(id: &str) -> anyhow::Result<bool> {
+    let mut stars = starred()?;
+    if stars.iter().any(|s| s == id) {
+        return Ok(false);
+    }
+    stars.push(id.to_owned());
+    save(&stars)?;
+    Ok(true)
+}
+
+// Returns whether the entry was starred
+pub fn unstar(id: &str) -> anyhow::Result<bool> {
+    let mut stars = starred()?;
+    let count = stars.len();
+    stars.retain(|s| s != id);
+    if stars.len() == count {
+        return Ok(false);
+    }
+    save(&stars)?;
+    Ok(true)
+}
+
+fn save(stars: &[String]) -> anyhow::Result<()> {
+    let path = stars_path().ok_or_else(|| anyhow!("Can't determine the config directory"))?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(stars)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,138 @@
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+use crossterm::style::Stylize;
+
+use crate::exit::ErrorFormat;
+use crate::output::ColorMode;
+
+static COLORS: OnceLock<Colors> = OnceLock::new();
+
+// Whether each stream gets color, decided once for the whole command
+struct Colors {
+    stdout: bool,
+    stderr: bool,
+}
+
+impl Colors {
+    fn resolve(mode: ColorMode) -> Self {
+        match mode {
+            ColorMode::Always => Self { stdout: true, stderr: true },
+            ColorMode::Never => Self { stdout: false, stderr: false },
+            // https://no-color.org: set and not empty means no color
+            ColorMode::Auto => {
+                let allowed = std::env::var_os("NO_COLOR").map_or(true, |v| v.is_empty()) &&
+                    std::env::var("TERM").map_or(true, |t| t != "dumb");
+                Self {
+                    stdout: allowed && std::io::stdout().is_terminal(),
+                    stderr: allowed && std::io::stderr().is_terminal(),
+                }
+            }
+        }
+    }
+}
+
+// Porcelain output is for scripts, so never has color
+pub fn init(mode: ColorMode, porcelain: bool) {
+    if mode == ColorMode::Always {
+        // Otherwise crossterm leaves out colors when NO_COLOR is set
+        crossterm::style::force_color_output(true);
+    }
+    let mut colors = Colors::resolve(mode);
+    colors.stdout &= !porcelain;
+    _ = COLORS.set(colors);
+}
+
+fn colors() -> &'static Colors {
+    COLORS.get_or_init(|| Colors::resolve(ColorMode::Auto))
+}
+
+pub fn enabled() -> bool {
+    colors().stdout
+}
+
+pub fn warning_label() -> String {
+    if colors().stderr { "Warning:".yellow().bold().to_string() } else { "Warning:".to_owned() }
+}
+
+pub fn error_label() -> String {
+    if colors().stderr { "Error:".red().bold().to_string() } else { "Error:".to_owned() }
+}
+
+pub fn help_label() -> String {
+    if colors().stderr { "help:".cyan().bold().to_string() } else { "help:".to_owned() }
+}
+
+// For statuses in listings that need attention, such as a missing template
+pub fn attention(text: &str) -> String {
+    if enabled() { text.yellow().to_string() } else { text.to_owned() }
+}
+
+// Emphasises where the search terms occur in the text, as search results do
+pub fn highlight(text: &str, terms: &[String]) -> String {
+    if !enabled() || terms.is_empty() {
+        return text.to_owned();
+    }
+    let mut highlighted = String::new();
+    let mut end = 0;
+    for (start, stop) in matches(text, terms) {
+        highlighted.push_str(&text[end..start]);
+        highlighted.push_str(&text[start..stop].bold().yellow().to_string());
+        end = stop;
+    }
+    highlighted.push_str(&text[end..]);
+    highlighted
+}
+
+// Byte ranges of the terms in the text, ignoring (ASCII) case, with overlapping ones merged
+fn matches(text: &str, terms: &[String]) -> Vec<(usize, usize)> {
+    let lower = text.to_ascii_lowercase();
+    let mut ranges = terms.iter()
+        .map(|t| t.to_ascii_lowercase())
+        .filter(|t| !t.is_empty())
+        .flat_map(|t| lower.match_indices(&t).map(|(start, m)| (start, start + m.len())).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    ranges.sort();
+    let mut merged: Vec<(usize, usize)> = vec![];
+    for (start, stop) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(stop),
+            _ => merged.push((start, stop)),
+        }
+    }
+    merged
+}
+
+// Like eprintln!, with the message labelled (and colored) as a warning
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        $crate::style::print_warning(&format!($($arg)*))
+    };
+}
+pub(crate) use warning;
+
+// Left out with --quiet. With --error-format json it's a JSON object like
+// errors are, so everything on stderr can be parsed.
+pub fn print_warning(message: &str) {
+    let settings = crate::settings::get();
+    if settings.quiet {
+        return;
+    }
+    match settings.error_format {
+        ErrorFormat::Text => eprintln!("{} {message}", warning_label()),
+        ErrorFormat::Json => eprintln!("{}", serde_json::json!({ "kind": "warning", "message": message })),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_are_found_ignoring_case_and_merged() {
+        let terms = vec!["rust".to_owned(), "ST".to_owned(), "http".to_owned()];
+        assert_eq!(vec![(0, 4), (9, 13)], matches("Rust and HTTP", &terms));
+        assert_eq!(vec![(0, 5)], matches("trust", &["tr".to_owned(), "rust".to_owned()]));
+        assert!(matches("Redis", &terms).is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use itertools::Itertools;
+
+use crate::{exit, history::now, http, installed, settings};
+
+// Reports are sent once this many commands have been recorded, or a week after the last
+const BATCH_SIZE: usize = 20;
+const BATCH_AGE: u64 = 7 * 24 * 60 * 60;
+// Events that couldn't be sent are dropped past this, so the file doesn't grow forever
+const MAX_EVENTS: usize = 500;
+const SEND_TIMEOUT: Duration = Duration::from_secs(3);
+const DAY: u64 = 24 * 60 * 60;
+
+// Off unless the user turns it on with `hub telemetry enable`
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Telemetry {
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_sent: Option<u64>,
+    // Waiting to be sent
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+// Nothing that identifies the user, their machine or what they searched for:
+// only which command ran and how it ended
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Event {
+    // Seconds since the Unix epoch, rounded down to the day
+    pub time: u64,
+    // Subcommand names only, such as `templates installed`
+    pub command: String,
+    // `success`, or the kind of error, as in --error-format json
+    pub outcome: String,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    // Where the event is sent, which is the Hub in use when it was recorded, so
+    // nothing recorded with one profile goes to another's Hub. Not in the report.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct Report<'a> {
+    events: &'a [Event],
+}
+
+fn telemetry_path() -> Option<PathBuf> {
+    installed::data_dir().map(|dir| dir.join("telemetry.json"))
+}
+
+pub fn path() -> anyhow::Result<PathBuf> {
+    telemetry_path().ok_or_else(|| anyhow!("Can't determine the data directory"))
+}
+
+pub fn load() -> anyhow::Result<Telemetry> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Telemetry::default());
+    }
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&text)
+        .with_context(|| format!("{} is not valid", path.display()))
+}
+
+pub fn save(telemetry: &Telemetry) -> anyhow::Result<()> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(telemetry)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+// DO_NOT_TRACK is the common way to say no to every tool at once
+pub fn is_suppressed() -> bool {
+    std::env::var_os("SPIN_HUB_NO_TELEMETRY").is_some() || std::env::var("DO_NOT_TRACK").is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+// Where reports of what's run now go: the Hub the user already talks to
+pub fn endpoint() -> anyhow::Result<url::Url> {
+    if let Ok(url) = std::env::var("SPIN_HUB_TELEMETRY_URL") {
+        return url::Url::parse(&url).with_context(|| format!("Invalid telemetry URL '{url}'"));
+    }
+    Ok(settings::get().hub_url().join("api/telemetry")?)
+}
+
+// Telemetry must never get in the way, so problems are only logged
+pub async fn record(command: &str, result: &anyhow::Result<()>) {
+    if is_suppressed() {
+        return;
+    }
+    if let Err(e) = try_record(command, result).await {
+        tracing::debug!("Couldn't record telemetry: {e:#}");
+    }
+}
+
+async fn try_record(command: &str, result: &anyhow::Result<()>) -> anyhow::Result<()> {
+    let mut telemetry = load()?;
+    if !telemetry.enabled {
+        return Ok(());
+    }
+    telemetry.events.push(Event {
+        time: now() / DAY * DAY,
+        command: command.to_owned(),
+        outcome: match result {
+            Ok(()) => "success".to_owned(),
+            Err(e) => exit::kind(exit::code(e)).to_owned(),
+        },
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        os: std::env::consts::OS.to_owned(),
+        arch: std::env::consts::ARCH.to_owned(),
+        endpoint: Some(endpoint()?.to_string()),
+    });
+    let excess = telemetry.events.len().saturating_sub(MAX_EVENTS);
+    telemetry.events.drain(..excess);
+
+    if is_batch_due(&telemetry, now()) {
+        // Events from before they said where they go can't be sent safely
+        telemetry.events.retain(|e| e.endpoint.is_some());
+        let endpoints = telemetry.events.iter().filter_map(|e| e.endpoint.clone()).unique().collect_vec();
+        for endpoint in endpoints {
+            let (batch, rest) = telemetry.events.into_iter().partition::<Vec<_>, _>(|e| e.endpoint.as_ref() == Some(&endpoint));
+            telemetry.events = rest;
+            match send(&endpoint, &batch).await {
+                Ok(()) => telemetry.last_sent = Some(now()),
+                Err(e) => {
+                    tracing::debug!("Couldn't send telemetry to {endpoint}: {e:#}");
+                    telemetry.events.extend(batch);
+                }
+            }
+        }
+    }
+    save(&telemetry)
+}
+
+fn is_batch_due(telemetry: &Telemetry, now: u64) -> bool {
+    let oldest = telemetry.last_sent.or(telemetry.events.first().map(|e| e.time));
+    telemetry.events.len() >= BATCH_SIZE || oldest.is_some_and(|t| now.saturating_sub(t) >= BATCH_AGE)
+}
+
+async fn send(endpoint: &str, events: &[Event]) -> anyhow::Result<()> {
+    let url = url::Url::parse(endpoint)?;
+    tracing::debug!(%url, events = events.len(), "Sending telemetry");
+    let events = events.iter().map(|e| Event { endpoint: None, ..e.clone() }).collect_vec();
+    http::client()?
+        .post(url)
+        .json(&Report { events: &events })
+        .timeout(SEND_TIMEOUT)
+        .send().await?
+        .error_for_status()?;
+    Ok(())
+}
+
+// The subcommands that ran, without any arguments, so nothing the user typed
+// is recorded. Extensions are recorded as `external`, whatever they're called.
+pub fn command_path(command: &clap::Command, matches: &clap::ArgMatches) -> String {
+    let mut names = vec![];
+    let (mut command, mut matches) = (command, matches);
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        let Some(sub) = command.find_subcommand(name) else {
+            return "external".to_owned();
+        };
+        names.push(sub.get_name().to_owned());
+        (command, matches) = (sub, sub_matches);
+    }
+    names.join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn batches_are_sent_when_full_or_old() {
+        let event = |time| Event { time, command: "search".to_owned(), outcome: "success".to_owned(), version: "0.1.0".to_owned(), os: "linux".to_owned(), arch: "x86_64".to_owned(), endpoint: None };
+        let mut telemetry = Telemetry { enabled: true, last_sent: Some(0), events: vec![event(0)] };
+        assert!(!is_batch_due(&telemetry, DAY));
+        assert!(is_batch_due(&telemetry, BATCH_AGE));
+
+        telemetry.events = (0..BATCH_SIZE).map(|_| event(0)).collect();
+        assert!(is_batch_due(&telemetry, DAY));
+    }
+
+    #[test]
+    fn command_paths_leave_out_arguments_and_extension_names() {
+        let command = clap::Command::new("hub")
+            .allow_external_subcommands(true)
+            .subcommand(clap::Command::new("templates").subcommand(clap::Command::new("installed").arg(clap::Arg::new("filter"))));
+
+        let matches = command.clone().get_matches_from(["hub", "templates", "installed", "secret"]);
+        assert_eq!("templates installed", command_path(&command, &matches));
+        let matches = command.clone().get_matches_from(["hub", "my-extension", "--flag"]);
+        assert_eq!("external", command_path(&command, &matches));
+    }
+}
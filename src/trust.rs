@@ -0,0 +1,119 @@
+use crate::{config, exit::Failure, settings};
+
+// Who is behind a template or plugin. Trust patterns can name the author, the
+// repository host (e.g. `github.com`) or an organisation (`github.com/fermyon`
+// or just `fermyon`).
+pub struct Publisher<'a> {
+    pub author: Option<&'a str>,
+    pub repo_url: &'a str,
+}
+
+impl Publisher<'_> {
+    fn identities(&self) -> Vec<String> {
+        let mut identities = vec![];
+        if let Some(author) = self.author {
+            identities.push(author.to_lowercase());
+        }
+        if let Ok(url) = url::Url::parse(self.repo_url) {
+            if let Some(host) = url.host_str() {
+                let host = host.to_lowercase();
+                if let Some(org) = url.path_segments().and_then(|mut s| s.next()).filter(|s| !s.is_empty()) {
+                    let org = org.to_lowercase();
+                    identities.push(format!("{host}/{org}"));
+                    identities.push(org);
+                }
+                identities.push(host);
+            }
+        }
+        identities
+    }
+
+    fn matches_any(&self, patterns: &[String]) -> Option<String> {
+        let identities = self.identities();
+        patterns.iter()
+            .find(|p| identities.contains(&p.trim_end_matches('/').to_lowercase()))
+            .cloned()
+    }
+
+    fn describe(&self) -> String {
+        match self.author {
+            Some(author) => format!("{author} ({})", self.repo_url),
+            None => self.repo_url.to_owned(),
+        }
+    }
+}
+
+// Blocked publishers are always refused. Once anything is trusted, publishers
+// that aren't need the user's say-so; with no trusted list, everyone is.
+pub fn check(publisher: &Publisher, no_prompt: bool) -> anyhow::Result<()> {
+    let (trusted, blocked) = lists()?;
+
+    if let Some(pattern) = publisher.matches_any(&blocked) {
+        return Err(Failure::Verification(format!("{} is blocked by the trust policy ('{pattern}'). Use `hub trust remove {pattern}` to unblock it.", publisher.describe())).into());
+    }
+    if trusted.is_empty() || publisher.matches_any(&trusted).is_some() {
+        return Ok(());
+    }
+    if no_prompt {
+        return Err(Failure::Verification(format!("{} is not in the trusted list. Use `hub trust add` to trust it.", publisher.describe())).into());
+    }
+
+    let suggestion = publisher.author.map(|a| a.to_lowercase()).unwrap_or_else(|| publisher.identities().into_iter().next().unwrap_or_default());
+    let choices = [
+        format!("Yes, always trust '{suggestion}'"),
+        "Yes, just this once".to_owned(),
+        "No".to_owned(),
+    ];
+    let choice = dialoguer::Select::new()
+        .with_prompt(format!("{} is not in your trusted list. Trust it?", publisher.describe()))
+        .items(&choices)
+        .default(2)
+        .interact_opt()?;
+    match choice {
+        Some(0) => update(|trusted, _| trusted.push(suggestion)),
+        Some(1) => Ok(()),
+        _ => Err(Failure::Verification(format!("Not trusted: {}", publisher.describe())).into()),
+    }
+}
+
+// The trusted and blocked lists of the active profile
+pub fn lists() -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    let config = config::load_for_profile(settings::get().profile.as_deref())?;
+    Ok((config.trusted, config.blocked))
+}
+
+// Changes the trusted and blocked lists of the active profile, as `config set`
+// does. A profile's list starts as the top-level one it inherits, and goes on
+// inheriting it if left unchanged.
+pub fn update<T>(change: impl FnOnce(&mut Vec<String>, &mut Vec<String>) -> T) -> anyhow::Result<T> {
+    let profile = settings::get().profile.as_deref();
+    let (trusted, blocked) = lists()?;
+    let mut config = config::load()?;
+    let section = config.section_mut(profile);
+    let inherited = (section.trusted.is_empty().then(|| trusted.clone()), section.blocked.is_empty().then(|| blocked.clone()));
+    section.trusted = trusted;
+    section.blocked = blocked;
+    let result = change(&mut section.trusted, &mut section.blocked);
+    if inherited.0.is_some_and(|list| list == section.trusted) {
+        section.trusted.clear();
+    }
+    if inherited.1.is_some_and(|list| list == section.blocked) {
+        section.blocked.clear();
+    }
+    config.save()?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn patterns_match_author_host_or_organisation() {
+        let publisher = Publisher { author: Some("Fermyon"), repo_url: "https://github.com/fermyon/spin" };
+        for pattern in ["fermyon", "github.com/fermyon/", "GitHub.com"] {
+            assert!(publisher.matches_any(&[pattern.to_owned()]).is_some(), "{pattern} should match");
+        }
+        assert!(publisher.matches_any(&["github.com/other".to_owned(), "spin".to_owned()]).is_none());
+    }
+}
@@ -0,0 +1,48 @@
+use std::path::Path;
+
+use anyhow::anyhow;
+use sha2::{Digest, Sha256};
+
+use crate::exit::Failure;
+
+pub fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+pub fn check_sha256(bytes: &[u8], expected: &str, what: &str) -> anyhow::Result<()> {
+    let actual = sha256(bytes);
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(Failure::Verification(format!("Checksum mismatch for {what}\n    expected sha256: {expected}\n    actual sha256:   {actual}")).into())
+    }
+}
+
+pub async fn check_cosign_signature(blob: &Path, signature: &Path, key: &Path) -> anyhow::Result<()> {
+    let output = tokio::process::Command::new("cosign")
+        .arg("verify-blob")
+        .arg("--key").arg(key)
+        .arg("--signature").arg(signature)
+        .arg(blob)
+        .output()
+        .await
+        .map_err(|e| anyhow!("Can't run cosign to check the signature ({e}). Install cosign from https://docs.sigstore.dev/"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Failure::Verification(format!("Signature check failed: {}", String::from_utf8_lossy(&output.stderr).trim())).into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checksums_are_compared_case_insensitively() {
+        let digest = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert_eq!(digest, sha256(b"hello"));
+        assert!(check_sha256(b"hello", &digest.to_uppercase(), "test").is_ok());
+        assert!(check_sha256(b"hello!", digest, "test").is_err());
+    }
+}